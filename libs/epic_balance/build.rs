@@ -1,5 +1,6 @@
 use std::{env, fs};
-use std::path::PathBuf;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
 
 #[cfg(feature = "serde")]
 use pbjson_build;
@@ -7,16 +8,26 @@ use pbjson_build;
 fn main() -> Result<(), std::io::Error> {
     let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("proto");
     let proto_files = vec![root.join("balancing.proto")];
+    let types_manifest = root.join("balancing_types.txt");
+    #[cfg(feature = "serde")]
+    let dates_manifest = root.join("date_fields.txt");
+    #[cfg(feature = "reflect")]
+    let revisions_manifest = root.join("revisions.txt");
 
     for proto_file in &proto_files {
         println!("cargo:rerun-if-changed={}", proto_file.display());
     }
+    println!("cargo:rerun-if-changed={}", types_manifest.display());
+    #[cfg(feature = "serde")]
+    println!("cargo:rerun-if-changed={}", dates_manifest.display());
+    #[cfg(feature = "reflect")]
+    println!("cargo:rerun-if-changed={}", revisions_manifest.display());
 
     let descriptor_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("proto_descriptor.bin");
 
     prost_build::Config::new()
         .file_descriptor_set_path(&descriptor_path)
-        .compile_protos(&proto_files, &[root])?;
+        .compile_protos(&proto_files, &[&root])?;
 
     #[cfg(feature = "serde")]
     {
@@ -28,6 +39,11 @@ fn main() -> Result<(), std::io::Error> {
     }
 
     let build_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    generate_balancing_types(&types_manifest, &proto_files[0], &build_dir.join("balancing_types.rs"))?;
+    #[cfg(feature = "reflect")]
+    generate_schema_revisions(&revisions_manifest, &root, &proto_files[0], &build_dir)?;
+
     let build_files = fs::read_dir(&build_dir).unwrap();
     for file in build_files {
         let file = file.unwrap();
@@ -40,5 +56,306 @@ fn main() -> Result<(), std::io::Error> {
         }
     }
 
+    #[cfg(feature = "serde")]
+    generate_date_fields(&dates_manifest, &proto_files[0], &build_dir)?;
+
     Ok(())
 }
+
+struct BalancingType {
+    group: &'static str,
+    type_name: String,
+    message_name: String,
+    variant: String,
+}
+
+//generates BalancingDataTypes, its FromStr/Display impls and for_each_balancing_data_type! from the types manifest
+fn generate_balancing_types(manifest: &Path, proto_file: &Path, output: &Path) -> Result<(), std::io::Error> {
+    let manifest_content = fs::read_to_string(manifest)?;
+    let proto_content = fs::read_to_string(proto_file)?;
+
+    let mut types = Vec::new();
+    for (line_number, line) in manifest_content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let [group, type_name, message_name] = parts[..] else {
+            panic!("{}:{}: expected `<group> <type name> <message>`", manifest.display(), line_number + 1);
+        };
+
+        let group = match group {
+            "normal" => "Normal",
+            "event" => "Event",
+            _ => panic!("{}:{}: unknown group `{}`", manifest.display(), line_number + 1, group),
+        };
+
+        if !proto_content.contains(&format!("message {} {{", message_name)) {
+            panic!("{}:{}: message `{}` is not defined in {}", manifest.display(), line_number + 1, message_name, proto_file.display());
+        }
+
+        let variant = type_name.rsplit('.').next().unwrap().to_string();
+        if types.iter().any(|t: &BalancingType| t.variant == variant) {
+            panic!("{}:{}: duplicate type `{}`", manifest.display(), line_number + 1, variant);
+        }
+
+        types.push(BalancingType {
+            group,
+            type_name: type_name.to_string(),
+            message_name: message_name.to_string(),
+            variant,
+        });
+    }
+
+    let mut out = String::new();
+
+    writeln!(out, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]").unwrap();
+    writeln!(out, "pub enum BalancingDataTypes {{").unwrap();
+    for t in &types {
+        writeln!(out, "    {},", t.variant).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl ::std::str::FromStr for BalancingDataTypes {{").unwrap();
+    writeln!(out, "    type Err = ::anyhow::Error;\n").unwrap();
+    writeln!(out, "    fn from_str(s: &str) -> Result<Self, Self::Err> {{").unwrap();
+    writeln!(out, "        match s {{").unwrap();
+    for t in &types {
+        writeln!(out, "            \"{}\" => Ok(BalancingDataTypes::{}),", t.type_name, t.variant).unwrap();
+    }
+    writeln!(out, "            _ => ::anyhow::bail!(\"Unknown BalancingData type: {{}}\", s),").unwrap();
+    writeln!(out, "        }}\n    }}\n}}\n").unwrap();
+
+    writeln!(out, "impl ::std::fmt::Display for BalancingDataTypes {{").unwrap();
+    writeln!(out, "    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {{").unwrap();
+    writeln!(out, "        let str = match self {{").unwrap();
+    for t in &types {
+        writeln!(out, "            BalancingDataTypes::{} => \"{}\",", t.variant, t.type_name).unwrap();
+    }
+    writeln!(out, "        }};\n        write!(f, \"{{}}\", str)\n    }}\n}}\n").unwrap();
+
+    writeln!(out, "/// Calls `$callback!` with every balancing data container type the crate knows about.").unwrap();
+    writeln!(out, "///").unwrap();
+    writeln!(out, "/// Every entry has the form `{{ Group, \"C#.Type.Name\", \"ProtoMessage\", Variant => proto::Type }}`,").unwrap();
+    writeln!(out, "/// anything passed to the callback before the list is forwarded as is,").unwrap();
+    writeln!(out, "/// e.g. `for_each_balancing_data_type!(my_match!(reader, key,))`").unwrap();
+    writeln!(out, "#[macro_export]").unwrap();
+    writeln!(out, "macro_rules! for_each_balancing_data_type {{").unwrap();
+    writeln!(out, "    ($callback:ident!($($prefix:tt)*)) => {{").unwrap();
+    writeln!(out, "        $callback! {{ $($prefix)*").unwrap();
+    for t in &types {
+        writeln!(
+            out,
+            "            {{ {}, \"{}\", \"{}\", {} => $crate::proto::{} }},",
+            t.group, t.type_name, t.message_name, t.variant, t.message_name
+        )
+        .unwrap();
+    }
+    writeln!(out, "        }}\n    }};\n}}").unwrap();
+
+    fs::write(output, out)
+}
+
+#[cfg(feature = "reflect")]
+struct Revision {
+    since: String,
+    name: String,
+    descriptor_file: String,
+    changes: Vec<String>,
+}
+
+//compiles the older balancing.proto revisions listed in the revisions manifest and generates SCHEMA_REVISIONS
+#[cfg(feature = "reflect")]
+fn generate_schema_revisions(manifest: &Path, root: &Path, current_proto: &Path, build_dir: &Path) -> Result<(), std::io::Error> {
+    let manifest_content = fs::read_to_string(manifest)?;
+
+    let mut revisions: Vec<Revision> = Vec::new();
+    for (line_number, line) in manifest_content.lines().enumerate() {
+        let indented = line.starts_with(char::is_whitespace);
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+
+        //changes belong to the revision above them
+        if indented {
+            let Some(revision) = revisions.last_mut() else {
+                panic!("{}:{}: change before the first revision", manifest.display(), line_number + 1);
+            };
+
+            let change = match parts[..] {
+                ["added", message, field] => format!("SchemaChange::Added {{ message: {:?}, field: {:?} }}", message, field),
+                ["renamed", message, from, to] => {
+                    format!("SchemaChange::Renamed {{ message: {:?}, from: {:?}, to: {:?} }}", message, from, to)
+                }
+                ["retired", message, field] => format!("SchemaChange::Retired {{ message: {:?}, field: {:?} }}", message, field),
+                _ => panic!(
+                    "{}:{}: expected `added <message> <field>`, `renamed <message> <old field> <new field>` or `retired <message> <field>`",
+                    manifest.display(),
+                    line_number + 1
+                ),
+            };
+            revision.changes.push(change);
+            continue;
+        }
+
+        let [since, name, proto_file] = parts[..] else {
+            panic!("{}:{}: expected `<first game version> <name> <proto file>`", manifest.display(), line_number + 1);
+        };
+
+        if !since.split('.').all(|part| part.starts_with(|c: char| c.is_ascii_digit())) {
+            panic!("{}:{}: `{}` isn't a game version like 3.0.0", manifest.display(), line_number + 1, since);
+        }
+
+        if revisions.iter().any(|revision| revision.name == name) {
+            panic!("{}:{}: duplicate revision `{}`", manifest.display(), line_number + 1, name);
+        }
+
+        let proto_file = root.join(proto_file);
+        let descriptor_file = if proto_file == current_proto {
+            "proto_descriptor.bin".to_string()
+        } else {
+            println!("cargo:rerun-if-changed={}", proto_file.display());
+
+            //the generated code of older revisions isn't used, it just has to go somewhere
+            let out_dir = build_dir.join("revisions").join(name);
+            fs::create_dir_all(&out_dir)?;
+
+            let descriptor_file = format!("revision_{}.bin", name);
+            prost_build::Config::new()
+                .out_dir(&out_dir)
+                .file_descriptor_set_path(build_dir.join(&descriptor_file))
+                .compile_protos(&[&proto_file], &[proto_file.parent().unwrap()])?;
+            descriptor_file
+        };
+
+        revisions.push(Revision {
+            since: since.to_string(),
+            name: name.to_string(),
+            descriptor_file,
+            changes: Vec::new(),
+        });
+    }
+
+    //conversions always go through the revision the generated types are made from
+    if revisions.last().map(|revision| revision.descriptor_file.as_str()) != Some("proto_descriptor.bin") {
+        panic!("{}: the last revision has to be {}", manifest.display(), current_proto.display());
+    }
+
+    let mut out = String::new();
+
+    writeln!(out, "static SCHEMA_REVISIONS: [SchemaRevision; {}] = [", revisions.len()).unwrap();
+    for revision in &revisions {
+        writeln!(out, "    SchemaRevision::compiled(").unwrap();
+        writeln!(out, "        {:?},", revision.name).unwrap();
+        writeln!(out, "        {:?},", revision.since).unwrap();
+        writeln!(out, "        include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{}\")),", revision.descriptor_file).unwrap();
+        if revision.changes.is_empty() {
+            writeln!(out, "        &[],").unwrap();
+        } else {
+            writeln!(out, "        &[").unwrap();
+            for change in &revision.changes {
+                writeln!(out, "            {},", change).unwrap();
+            }
+            writeln!(out, "        ],").unwrap();
+        }
+        writeln!(out, "    ),").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    fs::write(build_dir.join("schema_revisions.rs"), out)
+}
+
+//`start_date` to `startDate`, the same way protoc and pbjson do it
+#[cfg(feature = "serde")]
+fn json_name(field: &str) -> String {
+    let mut json_name = String::with_capacity(field.len());
+    let mut capitalize = false;
+
+    for c in field.chars() {
+        if c == '_' {
+            capitalize = true;
+        } else if capitalize {
+            json_name.push(c.to_ascii_uppercase());
+            capitalize = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+
+    json_name
+}
+
+//points the pbjson code of the fields in the dates manifest at crate::dates::Date and generates DATE_FIELDS
+#[cfg(feature = "serde")]
+fn generate_date_fields(manifest: &Path, proto_file: &Path, build_dir: &Path) -> Result<(), std::io::Error> {
+    let manifest_content = fs::read_to_string(manifest)?;
+    let proto_content = fs::read_to_string(proto_file)?;
+    let package = proto_content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("package ")?.strip_suffix(';'))
+        .unwrap_or_else(|| panic!("{} has no package", proto_file.display()));
+
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for (line_number, line) in manifest_content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let [message, field] = parts[..] else {
+            panic!("{}:{}: expected `<message> <field>`", manifest.display(), line_number + 1);
+        };
+        fields.push((format!("{}.{}", package, message), json_name(field)));
+    }
+
+    let serde_path = build_dir.join(format!("{}.serde.rs", package));
+    let serde_content = fs::read_to_string(&serde_path)?;
+
+    //every message has its own top level impls, the message name is in the serialize_struct/deserialize_struct call
+    let mut impls: Vec<String> = serde_content.split_inclusive("\n}\n").map(str::to_string).collect();
+    for (message, field) in &fields {
+        let serialize = format!("serialize_struct(\"{}\",", message);
+        let deserialize = format!("deserialize_struct(\"{}\",", message);
+        let written = format!("struct_ser.serialize_field(\"{}\", v)?;", field);
+        let duplicate = format!("duplicate_field(\"{}\")", field);
+        let number = "::pbjson::private::NumberDeserialize<_>";
+
+        let mut found = (false, false);
+        for code in impls.iter_mut() {
+            if code.contains(&serialize) && code.contains(&written) {
+                *code = code.replace(&written, &format!("struct_ser.serialize_field(\"{}\", &crate::dates::Date(v))?;", field));
+                found.0 = true;
+            }
+
+            if let (true, Some(start)) = (code.contains(&deserialize), code.find(&duplicate)) {
+                if let Some(offset) = code[start..].find(number) {
+                    code.replace_range(start + offset..start + offset + number.len(), "crate::dates::Date<_>");
+                    found.1 = true;
+                }
+            }
+        }
+
+        if found != (true, true) {
+            panic!("{}: {}.{} isn't an int32 or uint32 field pbjson generated code for", manifest.display(), message, field);
+        }
+    }
+    fs::write(&serde_path, impls.concat())?;
+
+    let mut out = String::new();
+    writeln!(out, "/// Fields holding Unix seconds, as the message name pbjson gives serde and the json name of the field.").unwrap();
+    writeln!(out, "/// With [`ReadableDates`] they are written as RFC 3339 dates, both forms are read back.").unwrap();
+    writeln!(out, "/// Generated from proto/date_fields.txt").unwrap();
+    writeln!(out, "pub static DATE_FIELDS: &[(&str, &str)] = &[").unwrap();
+    for (message, field) in &fields {
+        writeln!(out, "    ({:?}, {:?}),", message, field).unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    fs::write(build_dir.join("date_fields.rs"), out)
+}
@@ -0,0 +1,111 @@
+use crate::BalancingDataTypes;
+use prost::{bytes::Buf, DecodeError, Message};
+
+#[cfg(feature = "ron")]
+use crate::ron_options;
+
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeSeed, Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! generate_any_balancing_data {
+    ($({ $group:ident, $type_name:literal, $message_name:literal, $variant:ident => $proto_type:ty }),+ $(,)?) => {
+        /// Decoded contents of any balancing data container, tagged with the container type it came from
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum AnyBalancingData {
+            $($variant($proto_type),)+
+        }
+
+        impl AnyBalancingData {
+            pub fn default_for(data_type: BalancingDataTypes) -> Self {
+                match data_type {
+                    $(BalancingDataTypes::$variant => Self::$variant(Default::default()),)+
+                }
+            }
+
+            pub fn decode<B>(data_type: BalancingDataTypes, buf: B) -> Result<Self, DecodeError>
+            where
+                B: Buf,
+            {
+                Ok(match data_type {
+                    $(BalancingDataTypes::$variant => Self::$variant(<$proto_type>::decode(buf)?),)+
+                })
+            }
+
+            pub fn data_type(&self) -> BalancingDataTypes {
+                match self {
+                    $(Self::$variant(_) => BalancingDataTypes::$variant,)+
+                }
+            }
+
+            pub fn encode_to_vec(&self) -> Vec<u8> {
+                match self {
+                    $(Self::$variant(data) => data.encode_to_vec(),)+
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl Serialize for AnyBalancingData {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                match self {
+                    $(Self::$variant(data) => data.serialize(serializer),)+
+                }
+            }
+        }
+
+        //the container type is not part of the serialized data, so it has to be passed in as a seed
+        #[cfg(feature = "serde")]
+        impl<'de> DeserializeSeed<'de> for BalancingDataTypes {
+            type Value = AnyBalancingData;
+
+            fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                Ok(match self {
+                    $(BalancingDataTypes::$variant => AnyBalancingData::$variant(<$proto_type>::deserialize(deserializer)?),)+
+                })
+            }
+        }
+    };
+}
+
+for_each_balancing_data_type!(generate_any_balancing_data!());
+
+impl AnyBalancingData {
+    #[cfg(feature = "json")]
+    pub fn from_json(data_type: BalancingDataTypes, json: &str) -> anyhow::Result<Self> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let data = data_type.deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(data)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn from_ron(data_type: BalancingDataTypes, ron: &str) -> anyhow::Result<Self> {
+        Ok(ron_options().from_str_seed(ron, data_type)?)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn to_ron(&self) -> ron::Result<String> {
+        ron::ser::to_string(self)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn to_ron_pretty(&self) -> ron::Result<String> {
+        ron::ser::to_string_pretty(self, Default::default())
+    }
+}
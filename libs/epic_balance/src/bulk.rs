@@ -0,0 +1,81 @@
+use std::str::FromStr;
+
+use epic_wire::UnknownFields;
+use prost::DecodeError;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::{AnyBalancingData, BalancingDataArchive, BalancingDataTypes};
+
+pub type DecodedBalancingData = Result<(AnyBalancingData, UnknownFields), DecodeError>;
+
+//runs on the rayon pool with the parallel feature, one after another without it. Results keep the input order
+pub(crate) fn map_all<T, R, F>(items: Vec<T>, f: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(T) -> R + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    return items.into_par_iter().map(f).collect();
+
+    #[cfg(not(feature = "parallel"))]
+    items.into_iter().map(f).collect()
+}
+
+impl BalancingDataArchive {
+    /// Every key of a known type, in container order
+    pub(crate) fn known_keys(&self) -> Vec<BalancingDataTypes> {
+        self.get_balaning_data_keys()
+            .iter()
+            .filter_map(|key| BalancingDataTypes::from_str(key).ok())
+            .collect()
+    }
+
+    /// Decodes every container of a known type, containers of an unknown type are left out
+    pub fn get_all_data_any(&self) -> Vec<(BalancingDataTypes, DecodedBalancingData)> {
+        map_all(self.known_keys(), |key| (key, self.get_data_any_with_unknown(key)))
+    }
+
+    #[cfg(feature = "json")]
+    pub fn get_all_data_any_json(&self) -> Vec<(BalancingDataTypes, anyhow::Result<String>)> {
+        map_all(self.known_keys(), |key| (key, self.get_data_any_json(key)))
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn get_all_data_any_ron(&self) -> Vec<(BalancingDataTypes, anyhow::Result<String>)> {
+        map_all(self.known_keys(), |key| (key, self.get_data_any_ron(key)))
+    }
+
+    /// Same as [`Self::set_data_any_json`] for many containers at once, nothing is changed if any of them fails
+    #[cfg(feature = "json")]
+    pub fn set_all_data_any_json(&mut self, containers: Vec<(BalancingDataTypes, String)>) -> anyhow::Result<()> {
+        self.set_all_parsed(containers, crate::parse_any_json)
+    }
+
+    /// Same as [`Self::set_data_any_ron`] for many containers at once, nothing is changed if any of them fails
+    #[cfg(feature = "ron")]
+    pub fn set_all_data_any_ron(&mut self, containers: Vec<(BalancingDataTypes, String)>) -> anyhow::Result<()> {
+        self.set_all_parsed(containers, crate::parse_any_ron)
+    }
+
+    #[cfg(any(feature = "json", feature = "ron"))]
+    fn set_all_parsed<P>(&mut self, containers: Vec<(BalancingDataTypes, String)>, parse: P) -> anyhow::Result<()>
+    where
+        P: Fn(BalancingDataTypes, &str) -> anyhow::Result<(AnyBalancingData, UnknownFields)> + Send + Sync,
+    {
+        let encoded = map_all(containers, |(key, contents)| {
+            parse(key, &contents)
+                .and_then(|(data, unknown)| self.encode_data_any(&data, &unknown))
+                .map(|buf| (key, buf))
+                .map_err(|e| anyhow::anyhow!("{}: {}", key, e))
+        });
+
+        for (key, buf) in encoded.into_iter().collect::<anyhow::Result<Vec<_>>>()? {
+            self.set_data_key_raw(&key.to_string(), buf);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+use std::cell::Cell;
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+
+use chrono::{DateTime, SecondsFormat};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+//DATE_FIELDS, generated from proto/date_fields.txt
+include!(concat!(env!("OUT_DIR"), "/date_fields.rs"));
+
+thread_local! {
+    //set while a ReadableDates is serialized, serializing never leaves the thread it started on
+    static READABLE: Cell<bool> = const { Cell::new(false) };
+}
+
+fn format_date(seconds: i64) -> Option<String> {
+    DateTime::from_timestamp(seconds, 0).map(|date| date.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// Serializes `value` with the fields in [`DATE_FIELDS`] written as RFC 3339 dates instead of Unix seconds
+pub struct ReadableDates<'a, T: ?Sized> {
+    value: &'a T,
+    enabled: bool,
+}
+
+impl<'a, T: ?Sized> ReadableDates<'a, T> {
+    pub fn new(value: &'a T) -> Self {
+        Self { value, enabled: true }
+    }
+
+    //archives only do it when asked to
+    pub(crate) fn with(value: &'a T, enabled: bool) -> Self {
+        Self { value, enabled }
+    }
+}
+
+//puts the previous setting back, even if serializing panics
+struct Restore(bool);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        READABLE.set(self.0);
+    }
+}
+
+impl<T> Serialize for ReadableDates<'_, T>
+where
+    T: ?Sized + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let _restore = Restore(READABLE.replace(self.enabled));
+        self.value.serialize(serializer)
+    }
+}
+
+/// `serialize_with` of the fields in [`DATE_FIELDS`], the seconds as a date inside [`ReadableDates`] and as is otherwise
+pub(crate) fn serialize<T, S>(seconds: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Copy + Into<i64> + Serialize,
+    S: Serializer,
+{
+    match READABLE.get().then(|| format_date((*seconds).into())).flatten() {
+        Some(date) => serializer.serialize_str(&date),
+        None => seconds.serialize(serializer),
+    }
+}
+
+/// `deserialize_with` of the fields in [`DATE_FIELDS`], takes Unix seconds as a number or string like pbjson
+/// and RFC 3339 dates with any offset
+pub(crate) fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: TryFrom<i64>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(DateVisitor(PhantomData))
+}
+
+//what the build script wraps the date fields of the pbjson code in, pbjson has no serialize_with of its own
+pub(crate) struct Date<T>(pub(crate) T);
+
+impl<T> Serialize for Date<&T>
+where
+    T: Copy + Into<i64> + Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize(self.0, serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Date<T>
+where
+    T: TryFrom<i64>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize(deserializer).map(Date)
+    }
+}
+
+struct DateVisitor<T>(PhantomData<T>);
+
+impl<T> DateVisitor<T>
+where
+    T: TryFrom<i64>,
+{
+    fn seconds<E>(seconds: i64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        T::try_from(seconds).map_err(|_| E::custom(format!("{} seconds are out of range", seconds)))
+    }
+}
+
+impl<T> Visitor<'_> for DateVisitor<T>
+where
+    T: TryFrom<i64>,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut Formatter<'_>) -> std::fmt::Result {
+        formatter.write_str("Unix seconds or an RFC 3339 date")
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        Self::seconds(v)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        Self::seconds(i64::try_from(v).map_err(|_| E::custom(format!("{} seconds are out of range", v)))?)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<T, E>
+    where
+        E: de::Error,
+    {
+        if let Ok(seconds) = v.parse::<i64>() {
+            return Self::seconds(seconds);
+        }
+
+        let date = DateTime::parse_from_rfc3339(v).map_err(|e| E::custom(format!("{:?} isn't an RFC 3339 date: {}", v, e)))?;
+        Self::seconds(date.timestamp())
+    }
+}
@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use epic_wire::path::quote;
+use epic_wire::WithUnknown;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::bulk::map_all;
+use crate::{BalancingDataArchive, BalancingDataTypes};
+
+const NAME_ID: &str = "nameId";
+
+/// Everything that changed between two archives, see [`BalancingDataArchive::diff`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveDiff {
+    pub old_version: String,
+    pub new_version: String,
+    pub changes: Vec<Change>,
+}
+
+/// Paths are the ones of the json/ron form of the container, entries of a top level list with a `nameId`
+/// are addressed by it (`skillData["skill_x"].damage`), list items by index and map values by their quoted key
+/// if it isn't a plain name (`offerContents["bird_red"]`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Change {
+    ContainerAdded { key: String },
+    ContainerRemoved { key: String },
+    /// Only for containers of an unknown type, their contents can't be compared
+    ContainerChanged { key: String },
+    EntryAdded { key: String, path: String },
+    EntryRemoved { key: String, path: String },
+    /// A field missing on one side is `None` there
+    FieldChanged {
+        key: String,
+        path: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+}
+
+impl BalancingDataArchive {
+    /// Semantic diff from `self` to `new`, map and field order don't matter and entries are matched by `name_id`
+    pub fn diff(&self, new: &BalancingDataArchive) -> anyhow::Result<ArchiveDiff> {
+        let mut keys = self.get_balaning_data_keys();
+        for key in new.get_balaning_data_keys() {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+
+        let changes = map_all(keys, |key| diff_container(&key, self, new));
+
+        let mut diff = ArchiveDiff {
+            old_version: self.get_version().to_string(),
+            new_version: new.get_version().to_string(),
+            changes: Vec::new(),
+        };
+        for changes in changes {
+            diff.changes.extend(changes?);
+        }
+
+        Ok(diff)
+    }
+}
+
+impl ArchiveDiff {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty() && self.old_version == self.new_version
+    }
+}
+
+fn diff_container(key: &str, old: &BalancingDataArchive, new: &BalancingDataArchive) -> anyhow::Result<Vec<Change>> {
+    let key_string = key.to_string();
+
+    match (old.get_data_key(key), new.get_data_key(key)) {
+        (None, None) => return Ok(Vec::new()),
+        (None, Some(_)) => return Ok(vec![Change::ContainerAdded { key: key_string }]),
+        (Some(_), None) => return Ok(vec![Change::ContainerRemoved { key: key_string }]),
+        (Some(old_data), Some(new_data)) if old_data == new_data => return Ok(Vec::new()),
+        _ => {}
+    }
+
+    let Ok(data_type) = BalancingDataTypes::from_str(key) else {
+        return Ok(vec![Change::ContainerChanged { key: key_string }]);
+    };
+
+    let old_value = container_value(old, data_type)?;
+    let new_value = container_value(new, data_type)?;
+
+    let mut changes = Vec::new();
+    match (old_value, new_value) {
+        (Value::Object(old_fields), Value::Object(new_fields)) => {
+            for (field, old_field, new_field) in union(&old_fields, &new_fields) {
+                match (old_field, new_field) {
+                    (Some(Value::Array(old_entries)), Some(Value::Array(new_entries)))
+                        if is_entry_list(old_entries, new_entries) =>
+                    {
+                        diff_entries(key, field, old_entries, new_entries, &mut changes)
+                    }
+                    _ => diff_value(key, field.to_string(), old_field, new_field, &mut changes),
+                }
+            }
+        }
+        (old_value, new_value) => diff_value(key, String::new(), Some(&old_value), Some(&new_value), &mut changes),
+    }
+
+    Ok(changes)
+}
+
+fn container_value(archive: &BalancingDataArchive, data_type: BalancingDataTypes) -> anyhow::Result<Value> {
+    let (data, unknown) = archive.get_data_any_with_unknown(data_type)?;
+    Ok(serde_json::to_value(WithUnknown::new(&data, &unknown))?)
+}
+
+//top level lists of entries that have a name id
+fn is_entry_list(old: &[Value], new: &[Value]) -> bool {
+    old.iter().chain(new).any(|entry| entry.get(NAME_ID).is_some_and(Value::is_string))
+}
+
+//entries without a name id, or with one that shows up more than once, are matched by the order they come in
+fn entry_ids(entries: &[Value]) -> Vec<String> {
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    entries
+        .iter()
+        .enumerate()
+        .map(|(index, entry)| match entry.get(NAME_ID).and_then(Value::as_str) {
+            Some(name_id) => {
+                let count = seen.entry(name_id.to_string()).or_default();
+                *count += 1;
+                match count {
+                    1 => quote(name_id),
+                    n => format!("{}#{}", quote(name_id), n),
+                }
+            }
+            None => index.to_string(),
+        })
+        .collect()
+}
+
+fn diff_entries(key: &str, field: &str, old: &[Value], new: &[Value], changes: &mut Vec<Change>) {
+    let old_ids = entry_ids(old);
+    let new_ids = entry_ids(new);
+    let new_entries: HashMap<&String, &Value> = new_ids.iter().zip(new).collect();
+    let path = |id: &str| format!("{}[{}]", field, id);
+
+    for (id, old_entry) in old_ids.iter().zip(old) {
+        match new_entries.get(id) {
+            Some(new_entry) => diff_value(key, path(id), Some(old_entry), Some(new_entry), changes),
+            None => changes.push(Change::EntryRemoved {
+                key: key.to_string(),
+                path: path(id),
+            }),
+        }
+    }
+
+    let old_entries: HashMap<&String, &Value> = old_ids.iter().zip(old).collect();
+    for id in new_ids.iter().filter(|id| !old_entries.contains_key(id)) {
+        changes.push(Change::EntryAdded {
+            key: key.to_string(),
+            path: path(id),
+        });
+    }
+}
+
+fn diff_value(key: &str, path: String, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<Change>) {
+    if old == new {
+        return;
+    }
+
+    match (old, new) {
+        (Some(Value::Object(old)), Some(Value::Object(new))) => {
+            for (field, old_field, new_field) in union(old, new) {
+                diff_value(key, join(&path, field), old_field, new_field, changes);
+            }
+        }
+        (Some(Value::Array(old)), Some(Value::Array(new))) => {
+            for index in 0..old.len().max(new.len()) {
+                diff_value(key, format!("{}[{}]", path, index), old.get(index), new.get(index), changes);
+            }
+        }
+        _ => changes.push(Change::FieldChanged {
+            key: key.to_string(),
+            path,
+            old: old.cloned(),
+            new: new.cloned(),
+        }),
+    }
+}
+
+//fields of both sides, in the order of the old one with the new ones last
+fn union<'a>(
+    old: &'a Map<String, Value>,
+    new: &'a Map<String, Value>,
+) -> Vec<(&'a str, Option<&'a Value>, Option<&'a Value>)> {
+    let mut fields: Vec<_> = old
+        .iter()
+        .map(|(field, value)| (field.as_str(), Some(value), new.get(field)))
+        .collect();
+
+    fields.extend(
+        new.iter()
+            .filter(|(field, _)| !old.contains_key(*field))
+            .map(|(field, value)| (field.as_str(), None, Some(value))),
+    );
+
+    fields
+}
+
+//map keys that aren't plain names are quoted so the path can be read back, see crate::patch
+fn join(path: &str, field: &str) -> String {
+    let plain = field.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+    match (path.is_empty(), plain) {
+        (_, false) => format!("{}[{}]", path, quote(field)),
+        (true, true) => field.to_string(),
+        (false, true) => format!("{}.{}", path, field),
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let value = |value: &Option<Value>| match value {
+            Some(value) => value.to_string(),
+            None => "(none)".to_string(),
+        };
+
+        match self {
+            Change::ContainerAdded { key } => write!(f, "+ {}", key),
+            Change::ContainerRemoved { key } => write!(f, "- {}", key),
+            Change::ContainerChanged { key } => write!(f, "~ {} (unknown type, contents differ)", key),
+            Change::EntryAdded { key, path } => write!(f, "+ {} {}", key, path),
+            Change::EntryRemoved { key, path } => write!(f, "- {} {}", key, path),
+            Change::FieldChanged { key, path, old, new } => {
+                write!(f, "~ {} {}: {} -> {}", key, path, value(old), value(new))
+            }
+        }
+    }
+}
+
+impl Display for ArchiveDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.old_version != self.new_version {
+            writeln!(f, "version: {} -> {}", self.old_version, self.new_version)?;
+        }
+
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+
+        Ok(())
+    }
+}
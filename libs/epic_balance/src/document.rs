@@ -0,0 +1,205 @@
+use std::fmt::Formatter;
+use std::str::FromStr;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use epic_wire::{CaptureUnknown, UnknownFields, WithUnknown, UNKNOWN_FIELD_NAME};
+use serde::de::{self, DeserializeSeed, MapAccess, Visitor};
+use serde::ser::{self, SerializeMap, SerializeStruct};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{BalancingDataArchive, BalancingDataTypes};
+
+//the whole archive as one document, every container is in its typed form under its key.
+//containers of an unknown type can't be typed so they are kept as base64 of their raw bytes
+const ARCHIVE_NAME: &str = "BalancingDataArchive";
+const FIELDS: &[&str] = &["version", "allBalancingData", UNKNOWN_FIELD_NAME];
+
+#[derive(Deserialize)]
+#[serde(field_identifier)]
+enum Field {
+    #[serde(rename = "version")]
+    Version,
+    #[serde(rename = "allBalancingData")]
+    AllBalancingData,
+    #[serde(rename = "_unknown")]
+    Unknown,
+}
+
+impl Serialize for BalancingDataArchive {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let len = if self.unknown.is_empty() { 2 } else { 3 };
+
+        let mut archive = serializer.serialize_struct(ARCHIVE_NAME, len)?;
+        archive.serialize_field("version", self.get_version())?;
+        archive.serialize_field("allBalancingData", &Containers(self))?;
+        if !self.unknown.is_empty() {
+            archive.serialize_field(UNKNOWN_FIELD_NAME, &self.unknown)?;
+        }
+        archive.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for BalancingDataArchive {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut archive = Self::default();
+        ArchiveSeed(&mut archive).deserialize(deserializer)?;
+        Ok(archive)
+    }
+}
+
+struct Containers<'a>(&'a BalancingDataArchive);
+
+impl Serialize for Containers<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let all_balancing_data = &self.0.container.all_balancing_data;
+
+        let mut map = serializer.serialize_map(Some(all_balancing_data.len()))?;
+        for (key, data) in all_balancing_data.iter() {
+            match BalancingDataTypes::from_str(key) {
+                Ok(data_type) => {
+                    let (data, unknown) = self
+                        .0
+                        .get_data_any_with_unknown(data_type)
+                        .map_err(|e| ser::Error::custom(format!("{}: {}", key, e)))?;
+                    map.serialize_entry(key, &self.0.with_dates(&WithUnknown::new(&data, &unknown)))?;
+                }
+                Err(_) => map.serialize_entry(key, &BASE64_STANDARD.encode(data))?,
+            }
+        }
+        map.end()
+    }
+}
+
+/// Deserializes a whole archive document into an existing archive, containers in the document replace the ones already there
+pub(crate) struct ArchiveSeed<'a>(pub &'a mut BalancingDataArchive);
+
+impl<'de> DeserializeSeed<'de> for ArchiveSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_struct(ARCHIVE_NAME, FIELDS, self)
+    }
+}
+
+impl<'de> Visitor<'de> for ArchiveSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a balancing data archive")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let archive = self.0;
+
+        while let Some(field) = map.next_key::<Field>()? {
+            match field {
+                Field::Version => {
+                    if let Some(version) = map.next_value::<Option<String>>()? {
+                        archive.set_version(&version);
+                    }
+                }
+                Field::AllBalancingData => map.next_value_seed(ContainersSeed(&mut *archive))?,
+                Field::Unknown => archive.set_unknown_fields(map.next_value::<UnknownFields>()?),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct ContainersSeed<'a>(&'a mut BalancingDataArchive);
+
+impl<'de> DeserializeSeed<'de> for ContainersSeed<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ContainersSeed<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a map of balancing data containers")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let archive = self.0;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match BalancingDataTypes::from_str(&key) {
+                Ok(data_type) => {
+                    let (data, unknown) = map.next_value_seed(CaptureUnknown(data_type))?;
+                    archive
+                        .set_data_any_with_unknown(&data, &unknown)
+                        .map_err(|e| de::Error::custom(format!("{}: {}", key, e)))?;
+                }
+                Err(_) => {
+                    let raw = map.next_value::<String>()?;
+                    let raw = BASE64_STANDARD
+                        .decode(raw.trim())
+                        .map_err(|e| de::Error::custom(format!("{}: {}", key, e)))?;
+                    archive.set_data_key_raw(&key, raw);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "json")]
+pub(crate) fn archive_schema() -> serde_json::Value {
+    use epic_wire::SchemaBuilder;
+    use serde_json::{json, Map};
+
+    let mut builder = SchemaBuilder::new(crate::descriptors()).date_fields(crate::DATE_FIELDS);
+
+    let mut containers = Map::new();
+    for data_type in BalancingDataTypes::all() {
+        let schema = builder
+            .message(data_type.full_message_name())
+            .expect("every container message is in the descriptors");
+        containers.insert(data_type.type_name().to_string(), builder.with_unknown(schema));
+    }
+
+    let root = json!({
+        "type": "object",
+        "properties": {
+            "version": { "type": "string" },
+            "allBalancingData": {
+                "type": "object",
+                "properties": containers,
+                //containers of types the crate doesn't know are kept as their base64 bytes
+                "additionalProperties": { "type": "string", "contentEncoding": "base64" }
+            }
+        },
+        "additionalProperties": false
+    });
+    let root = builder.with_unknown(root);
+
+    builder.build(ARCHIVE_NAME, root)
+}
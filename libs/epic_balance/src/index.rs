@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use epic_wire::wire::{records, Record};
+use epic_wire::{FieldInfo, MessageInfo};
+use prost::{encoding::WireType, DecodeError};
+
+use crate::bulk::map_all;
+use crate::proto::requirement::RequirementType;
+use crate::{descriptors, BalancingDataArchive, BalancingDataTypes};
+
+const NAME_ID: &str = "name_id";
+const MAP_KEY: u32 = 1;
+const MAP_VALUE: u32 = 2;
+const REQUIREMENT: &str = "Requirement";
+const REQUIREMENT_TYPE: u32 = 1;
+
+//where a reference field keeps its ids
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Holds {
+    /// The string itself, or every string of a repeated field
+    Value,
+    /// The keys of a map
+    Key,
+    /// The string values of a map
+    MapValue,
+}
+
+//fields meant to hold ids, messages are named without the package and nested ones with their parent.
+//`Requirement.name_id` only holds one for the requirement types in `names_id`
+const REFERENCE_FIELDS: &[(&str, &str, Holds)] = &[
+    ("LootTableEntry", "name_id", Holds::Value),
+    ("BattleParticipantTableEntry", "name_id", Holds::Value),
+    ("Requirement", "name_id", Holds::Value),
+    ("HotspotBalancingData.HotspotData", "battle_id", Holds::Value),
+    ("BirdBalancingData.BirdData", "default_inventory_name_id", Holds::Value),
+    ("PigBalancingData.PigData", "default_inventory_name_id", Holds::Value),
+    ("PigBalancingData.PigData", "skill_name_ids", Holds::Value),
+    ("PigBalancingData.PigData", "passive_skill_name_id", Holds::Value),
+    ("PigBalancingData.PigData", "loot_table_defeat_bonus", Holds::Key),
+    ("BossBalancingData.BossData", "default_inventory_name_id", Holds::Value),
+    ("BossBalancingData.BossData", "skill_name_ids", Holds::Value),
+    ("BossBalancingData.BossData", "passive_skill_name_id", Holds::Value),
+    ("BossBalancingData.BossData", "loot_table_defeat_bonus", Holds::Key),
+    ("BannerBalancingData.BannerData", "default_inventory_name_id", Holds::Value),
+    ("BannerItemBalancingData.BannerItemData", "skill_name_ids", Holds::Value),
+    ("BannerItemBalancingData.BannerItemData", "unlockable_set_skill_name_id", Holds::Value),
+    ("BannerItemBalancingData.BannerItemData", "scrap_loot", Holds::Key),
+    ("ClassItemBalancingData.ClassItemData", "skill_name_ids", Holds::Value),
+    ("ClassItemBalancingData.ClassItemData", "pvp_skill_name_ids", Holds::Value),
+    ("ClassItemBalancingData.ClassItemData", "replacement_class_name_id", Holds::Value),
+    ("ClassSkinBalancingData.ClassSkinData", "passive_skill_name_id", Holds::Value),
+    ("ConsumableItemBalancingData.ConsumableItemData", "skill_name_id", Holds::Value),
+    ("CraftingItemBalancingData.CraftingItemData", "base_item_name_id", Holds::Value),
+    ("CraftingRecipeBalancingData.CraftingRecipeData", "result_loot", Holds::Key),
+    ("BonusPerFriendBalancingData", "unlocked_class_name_id", Holds::Value),
+    ("ChronicleCaveFloorBalancingData.ChronicleCaveFloorData", "boss_name_id", Holds::Value),
+    ("ResourceCostPerLevelBalancingData.ResourceCostPerLevelData", "applied_item_name_id", Holds::Value),
+    ("ResourceCostPerLevelBalancingData.ResourceCostPerLevelData", "first_material_name_id", Holds::Value),
+    ("ResourceCostPerLevelBalancingData.ResourceCostPerLevelData", "second_material_name_id", Holds::Value),
+    ("ResourceCostPerLevelBalancingData.ResourceCostPerLevelData", "third_material_name_id", Holds::Value),
+    ("WorldBalancingData.WorldBalancingData", "first_hotspot_name_id", Holds::Value),
+    ("WorldBalancingData.WorldBalancingData", "daily_hotspot_name_id", Holds::Value),
+    ("WorldBalancingData.WorldBalancingData", "chronicle_cave_daily_treasure_loot", Holds::Key),
+    ("WorldBalancingData.WorldBalancingData", "daily_event_ad_loot", Holds::Key),
+    ("BasicShopOfferBalancingData.BasicShopOfferData", "offer_contents", Holds::Key),
+    ("ShopOfferBalancingData.ShopOfferData", "offer_contents", Holds::Key),
+    ("EventBalancingData.EventData", "event_generator_item_loot_table", Holds::Key),
+    ("EventBalancingData.EventData", "event_reward_loot_table_wheel", Holds::Key),
+    ("EventBalancingData.EventData", "event_bonus_loot_tables_per_rank", Holds::Value),
+    ("EventBalancingData.EventData", "event_collectible_generator_item_loot_table", Holds::Key),
+    ("EventBalancingData.EventData", "event_mini_campaign_item_loot_table", Holds::Key),
+    ("EventBalancingData.EventData", "event_boss_item_loot_table", Holds::Key),
+    ("PvPSeasonManagerBalancingData.PvPSeasonManagerData", "pvp_reward_loot_tables_per_league", Holds::Value),
+    ("PvPSeasonManagerBalancingData.PvPSeasonManagerData", "pvp_bonus_loot_tables_per_rank", Holds::Value),
+    ("BattleBalancingData.BattleData", "loot_table_wheel", Holds::Key),
+    ("BattleBalancingData.BattleData", "loot_table_additional", Holds::Key),
+    ("BattleBalancingData.BattleData", "loot_table_lost", Holds::Key),
+    ("BattleBalancingData.BattleData", "loot_table_wheel_after_wave", Holds::MapValue),
+    ("BattleBalancingData.BattleData", "bonus_loot", Holds::Key),
+    ("ExperienceLevelBalancingData.ExperienceLevelData", "loot_table_additional", Holds::Key),
+    ("CustomMessageBalancingData.CustomMessageData", "loot_table_reward", Holds::Key),
+    ("MasteryItemBalancingData.MasteryItemData", "fallback_loot_table", Holds::Key),
+    ("MasteryItemBalancingData.MasteryItemData", "fallback_loot_table_daily_login", Holds::Key),
+    ("CollectionGroupBalancingData.CollectionGroupData", "component_fallback_loot", Holds::Key),
+    ("CollectionGroupBalancingData.CollectionGroupData", "easy_battle_fallback_loot", Holds::Key),
+    ("CollectionGroupBalancingData.CollectionGroupData", "medium_battle_fallback_loot", Holds::Key),
+    ("CollectionGroupBalancingData.CollectionGroupData", "hard_battle_fallback_loot", Holds::Key),
+    ("CollectionGroupBalancingData.CollectionGroupData", "easy_battle_secondary_fallback_loot", Holds::Key),
+    ("CollectionGroupBalancingData.CollectionGroupData", "medium_battle_secondary_fallback_loot", Holds::Key),
+    ("CollectionGroupBalancingData.CollectionGroupData", "hard_battle_secondary_fallback_loot", Holds::Key),
+];
+
+/// Where an id shows up, the path is the one of the json/ron form of the container
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdLocation {
+    pub data_type: BalancingDataTypes,
+    /// e.g. `skillData[3]` for a definition, `pigData[2].skillNameIds[1]` or `basicShopOfferData[0].offerContents["bird_red"]` for a reference
+    pub path: String,
+    /// `name_id` of the top level entry the location is in
+    pub entry: Option<String>,
+}
+
+impl Display for IdLocation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.entry {
+            Some(entry) => write!(f, "{} {} ({})", self.data_type, self.path, entry),
+            None => write!(f, "{} {}", self.data_type, self.path),
+        }
+    }
+}
+
+/// Every `name_id` of every container and everything that refers to it.
+///
+/// An id is defined by the `name_id` of an entry in a container's top level list and referenced by the fields meant to
+/// hold one (skill and inventory name ids, loot table entries and keys, offer contents, `battle_id`, requirements that
+/// name an id...), which are also tracked when their id doesn't exist. Any other string (map keys included) with the same value as an id is only a possible reference,
+/// localization keys and sprite names often happen to match one
+#[derive(Debug, Clone, Default)]
+pub struct NameIdIndex {
+    definitions: HashMap<String, Vec<IdLocation>>,
+    references: HashMap<String, Vec<IdLocation>>,
+    possible_references: HashMap<String, Vec<IdLocation>>,
+    unresolved: Vec<(String, IdLocation)>,
+}
+
+//strings found in a container, everything is collected first since references can come before their definitions
+#[derive(Default)]
+struct Scan {
+    definitions: Vec<(String, IdLocation)>,
+    strings: Vec<Found>,
+}
+
+struct Found {
+    value: String,
+    location: IdLocation,
+    //the field is meant to hold an id
+    declared: bool,
+}
+
+impl NameIdIndex {
+    pub fn new(archive: &BalancingDataArchive) -> Result<Self, DecodeError> {
+        let scans = map_all(archive.known_keys(), |data_type| match archive.get_data_key_current(&data_type.to_string())? {
+            Some(data) => scan_container(data_type, &data),
+            None => Ok(Scan::default()),
+        });
+
+        let mut index = Self::default();
+        let mut strings = Vec::new();
+
+        for scan in scans {
+            let scan = scan?;
+
+            for (id, location) in scan.definitions {
+                index.definitions.entry(id).or_default().push(location);
+            }
+            strings.extend(scan.strings);
+        }
+
+        for found in strings {
+            match (index.definitions.contains_key(&found.value), found.declared) {
+                (true, true) => index.references.entry(found.value).or_default().push(found.location),
+                (true, false) => index.possible_references.entry(found.value).or_default().push(found.location),
+                (false, true) => index.unresolved.push((found.value, found.location)),
+                (false, false) => {}
+            }
+        }
+
+        Ok(index)
+    }
+
+    /// First definition of `id`, most ids are only defined once
+    pub fn definition(&self, id: &str) -> Option<&IdLocation> {
+        self.definitions(id).first()
+    }
+
+    pub fn definitions(&self, id: &str) -> &[IdLocation] {
+        self.definitions.get(id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Fields meant to hold an id that hold `id`
+    pub fn references(&self, id: &str) -> &[IdLocation] {
+        self.references.get(id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Other strings that happen to be `id`, these may or may not refer to it
+    pub fn possible_references(&self, id: &str) -> &[IdLocation] {
+        self.possible_references.get(id).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Ids in fields meant to hold one that no container defines, in container order
+    pub fn unresolved(&self) -> &[(String, IdLocation)] {
+        &self.unresolved
+    }
+
+    pub fn contains(&self, id: &str) -> bool {
+        self.definitions.contains_key(id)
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &str> {
+        self.definitions.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.definitions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.definitions.is_empty()
+    }
+}
+
+impl BalancingDataArchive {
+    pub fn name_id_index(&self) -> Result<NameIdIndex, DecodeError> {
+        NameIdIndex::new(self)
+    }
+}
+
+pub(crate) fn lookup(name: &str) -> Result<&'static MessageInfo, DecodeError> {
+    descriptors()
+        .message(name)
+        .ok_or_else(|| DecodeError::new(format!("unknown message type {}", name)))
+}
+
+fn reference_field(message: &MessageInfo, field: &FieldInfo) -> Option<Holds> {
+    REFERENCE_FIELDS
+        .iter()
+        .find(|(name, field_name, _)| *field_name == field.name && is_message(&message.name, name))
+        .map(|(_, _, holds)| *holds)
+}
+
+//requirement types whose name_id is the id of an item, bird, class, hotspot or offer
+fn names_id(requirement_type: RequirementType) -> bool {
+    use RequirementType::*;
+
+    matches!(
+        requirement_type,
+        PayItem
+            | HaveItem
+            | NotHaveItem
+            | HaveLessThan
+            | HaveItemWithLevel
+            | NotHaveItemWithLevel
+            | HaveBird
+            | UseBirdInBattle
+            | NotUseBirdInBattle
+            | HaveClass
+            | NotHaveClass
+            | HaveMasteryFactor
+            | NotHaveMasteryFactor
+            | HaveCurrentHotpsotState
+            | HaveUnlockedHotpsot
+            | NotHaveUnlockedHotpsot
+            | LostUnresolvedHotspot
+            | CooldownFinished
+            | DeclinedOffer
+            | AcceptedOffer
+            | EndedOffer
+    )
+}
+
+//the last requirement_type record wins, a missing one is NONE
+fn requirement_names_id(records: &[Record]) -> bool {
+    let requirement_type = records
+        .iter()
+        .rev()
+        .find(|record| record.number == REQUIREMENT_TYPE && record.wire_type == WireType::Varint)
+        .and_then(|record| prost::encoding::decode_varint(&mut &record.value[..]).ok())
+        .and_then(|value| RequirementType::try_from(value as i32).ok())
+        .unwrap_or(RequirementType::None);
+
+    names_id(requirement_type)
+}
+
+/// Messages are named without the package, nested ones with their parent, e.g. `Parent.Nested`
+pub(crate) fn is_message(full_name: &str, name: &str) -> bool {
+    full_name == name || full_name.ends_with(&format!(".{}", name))
+}
+
+pub(crate) fn join(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}.{}", path, segment)
+    }
+}
+
+//everything a container scan needs to know about where it currently is
+struct Context<'a> {
+    data_type: BalancingDataTypes,
+    entry: Option<&'a str>,
+}
+
+impl Context<'_> {
+    fn found(&self, value: &[u8], path: String, declared: bool, strings: &mut Vec<Found>) {
+        if value.is_empty() {
+            return;
+        }
+
+        strings.push(Found {
+            value: String::from_utf8_lossy(value).into_owned(),
+            location: self.location(path),
+            declared,
+        });
+    }
+
+    fn location(&self, path: String) -> IdLocation {
+        IdLocation {
+            data_type: self.data_type,
+            path,
+            entry: self.entry.map(str::to_string),
+        }
+    }
+}
+
+fn scan_container(data_type: BalancingDataTypes, buf: &[u8]) -> Result<Scan, DecodeError> {
+    let container = lookup(data_type.full_message_name())?;
+    let mut scan = Scan::default();
+    let mut occurrences: HashMap<u32, usize> = HashMap::new();
+
+    for record in records(buf)? {
+        let Some(field) = container.field(record.number) else {
+            continue;
+        };
+        let occurrence = occurrences.entry(record.number).or_default();
+        let path = format!("{}[{}]", field.json_name(), occurrence);
+        *occurrence += 1;
+
+        let Some(entry) = field.message_type().filter(|_| record.wire_type == WireType::LengthDelimited) else {
+            continue;
+        };
+        let entry = lookup(entry)?;
+
+        let name_id = entry_name_id(entry, record.value)?;
+        let context = Context {
+            data_type,
+            entry: name_id.as_deref(),
+        };
+
+        if let Some(name_id) = &name_id {
+            scan.definitions.push((name_id.clone(), context.location(path.clone())));
+        }
+
+        scan_message(&context, entry, record.value, &path, true, &mut scan.strings)?;
+    }
+
+    Ok(scan)
+}
+
+//the last name_id record wins, same as decoding would
+pub(crate) fn entry_name_id(entry: &MessageInfo, buf: &[u8]) -> Result<Option<String>, DecodeError> {
+    let Some(name_id) = entry.field_by_name(NAME_ID).filter(|field| field.is_string()) else {
+        return Ok(None);
+    };
+
+    Ok(records(buf)?
+        .iter()
+        .rev()
+        .find(|record| record.number == name_id.number)
+        .map(|record| String::from_utf8_lossy(record.value).into_owned()))
+}
+
+fn scan_message(
+    context: &Context,
+    message: &MessageInfo,
+    buf: &[u8],
+    path: &str,
+    entry_level: bool,
+    strings: &mut Vec<Found>,
+) -> Result<(), DecodeError> {
+    let mut occurrences: HashMap<u32, usize> = HashMap::new();
+    let records = records(buf)?;
+    let requirement = is_message(&message.name, REQUIREMENT);
+
+    for record in &records {
+        let Some(field) = message.field(record.number) else {
+            continue;
+        };
+        let occurrence = occurrences.entry(record.number).or_default();
+        let field_path = if field.repeated {
+            join(path, &format!("{}[{}]", field.json_name(), occurrence))
+        } else {
+            join(path, &field.json_name())
+        };
+        *occurrence += 1;
+
+        if record.wire_type != WireType::LengthDelimited || (entry_level && field.name == NAME_ID) {
+            continue;
+        }
+
+        let holds = reference_field(message, field);
+        match field.message_type().map(lookup).transpose()? {
+            Some(nested) if nested.map_entry => {
+                let path = join(path, &field.json_name());
+                scan_map_entry(context, nested, record.value, &path, holds, strings)?
+            }
+            Some(nested) => scan_message(context, nested, record.value, &field_path, false, strings)?,
+            None if field.is_string() => {
+                let declared = holds == Some(Holds::Value) && (!requirement || requirement_names_id(&records));
+                context.found(record.value, field_path, declared, strings)
+            }
+            None => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn scan_map_entry(
+    context: &Context,
+    entry: &MessageInfo,
+    buf: &[u8],
+    path: &str,
+    holds: Option<Holds>,
+    strings: &mut Vec<Found>,
+) -> Result<(), DecodeError> {
+    let records = records(buf)?;
+    let key = records.iter().rev().find(|record| record.number == MAP_KEY);
+    let key_is_string = entry.field(MAP_KEY).is_some_and(|field| field.is_string());
+
+    let path = match key {
+        Some(key) if key_is_string => {
+            let path = format!("{}[{:?}]", path, String::from_utf8_lossy(key.value));
+            context.found(key.value, path.clone(), holds == Some(Holds::Key), strings);
+            path
+        }
+        Some(key) => format!("{}[{}]", path, prost::encoding::decode_varint(&mut &key.value[..]).unwrap_or_default()),
+        None => format!("{}[]", path),
+    };
+
+    //only string and message values can hold an id
+    let value = records.iter().rev().find(|record| record.number == MAP_VALUE);
+    match (value, entry.field(MAP_VALUE)) {
+        (Some(value), Some(field)) if value.wire_type == WireType::LengthDelimited => match field.message_type() {
+            Some(nested) => scan_message(context, lookup(nested)?, value.value, &path, false, strings),
+            None if field.is_string() => {
+                context.found(value.value, path, holds == Some(Holds::MapValue), strings);
+                Ok(())
+            }
+            None => Ok(()),
+        },
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,208 @@
+use std::borrow::Cow;
+use std::io::Read;
+use std::str::FromStr;
+
+use bytes::Bytes;
+use epic_wire::wire::records;
+use epic_wire::UnknownFields;
+use indexmap::IndexMap;
+use prost::{encoding::WireType, DecodeError, Message};
+
+#[cfg(any(feature = "json", feature = "ron"))]
+use epic_wire::WithUnknown;
+
+use crate::reading::ReadSettings;
+use crate::{descriptors, AnyBalancingData, BalancingDataArchive, BalancingDataTypes};
+#[cfg(feature = "reflect")]
+use crate::SchemaRevision;
+
+//field numbers of SerializedBalancingDataContainer and its map entries
+const ALL_BALANCING_DATA: u32 = 1;
+const VERSION: u32 = 2;
+const ENTRY_KEY: u32 = 1;
+const ENTRY_VALUE: u32 = 2;
+
+/// Read only view of a balancing data container, the outer message is scanned once and every container
+/// stays a slice of the input buffer until it's asked for.
+/// Use [`BalancingDataArchive`] to change anything
+pub struct LazyBalancingDataArchive {
+    buf: Bytes,
+    version: Option<String>,
+    entries: IndexMap<String, Bytes>,
+    read: ReadSettings,
+}
+
+impl LazyBalancingDataArchive {
+    /// Doesn't copy anything, containers are slices of `buf`
+    pub fn new<B>(buf: B) -> anyhow::Result<Self>
+    where
+        B: Into<Bytes>,
+    {
+        let buf = buf.into();
+        let mut version = None;
+        let mut entries = IndexMap::new();
+
+        for record in records(&buf)? {
+            match (record.number, record.wire_type) {
+                (ALL_BALANCING_DATA, WireType::LengthDelimited) => {
+                    let (key, value) = scan_entry(record.value)?;
+                    entries.insert(key, buf.slice_ref(value));
+                }
+                (VERSION, WireType::LengthDelimited) => {
+                    version = Some(String::from_utf8(record.value.to_vec())?);
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            buf,
+            version,
+            entries,
+            read: ReadSettings::default(),
+        })
+    }
+
+    /// The container has to be inflated first, that is the only copy made
+    pub fn new_gzipped<R>(reader: R) -> anyhow::Result<Self>
+    where
+        R: Read,
+    {
+        let mut decoder = flate2::read::GzDecoder::new(reader);
+        let mut decompressed = Vec::new();
+        decoder.read_to_end(&mut decompressed)?;
+
+        Self::new(decompressed)
+    }
+
+    /// Memory maps an uncompressed container, gzipped ones have to go through [`Self::new_gzipped`]
+    #[cfg(feature = "mmap")]
+    pub fn open<P>(path: P) -> anyhow::Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(path)?;
+        //the file changing under the map is undefined behaviour, same caveat as every other mmap user
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+
+        Self::new(Bytes::from_owner(map))
+    }
+
+    pub fn get_balaning_data_keys(&self) -> Vec<String> {
+        self.entries.keys().cloned().collect()
+    }
+
+    pub fn get_data_key(&self, key: &str) -> Option<&Bytes> {
+        self.entries.get(key)
+    }
+
+    //same as BalancingDataArchive::get_data_key_current, containers already in it stay slices of the input
+    fn get_data_key_current(&self, key: &str) -> Result<Option<Cow<'_, [u8]>>, DecodeError> {
+        self.get_data_key(key)
+            .map(|data| self.read.current(self.get_version(), key, data))
+            .transpose()
+    }
+
+    pub fn get_data_key_decoded<T>(&self, key: &str) -> Result<T, DecodeError>
+    where
+        T: Message + Default,
+    {
+        match self.get_data_key_current(key)? {
+            Some(data) => T::decode(data.as_ref()),
+            None => Err(DecodeError::new("Key not found")),
+        }
+    }
+
+    pub fn get_data_enum_decoded<T>(&self, key: BalancingDataTypes) -> Result<T, DecodeError>
+    where
+        T: Message + Default,
+    {
+        self.get_data_key_decoded::<T>(&key.to_string())
+    }
+
+    pub fn get_data_any(&self, key: BalancingDataTypes) -> Result<AnyBalancingData, DecodeError> {
+        match self.get_data_key_current(&key.to_string())? {
+            Some(data) => AnyBalancingData::decode(key, data.as_ref()),
+            None => Err(DecodeError::new("Key not found")),
+        }
+    }
+
+    pub fn get_data_key_any(&self, key: &str) -> anyhow::Result<AnyBalancingData> {
+        Ok(self.get_data_any(BalancingDataTypes::from_str(key)?)?)
+    }
+
+    pub fn get_data_unknown_fields(&self, key: BalancingDataTypes) -> Result<UnknownFields, DecodeError> {
+        match self.get_data_key_current(&key.to_string())? {
+            Some(data) => UnknownFields::extract(descriptors(), key.full_message_name(), &data),
+            None => Ok(UnknownFields::default()),
+        }
+    }
+
+    pub fn get_data_any_with_unknown(
+        &self,
+        key: BalancingDataTypes,
+    ) -> Result<(AnyBalancingData, UnknownFields), DecodeError> {
+        Ok((self.get_data_any(key)?, self.get_data_unknown_fields(key)?))
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn get_data_any_ron(&self, key: BalancingDataTypes) -> anyhow::Result<String> {
+        let (data, unknown) = self.get_data_any_with_unknown(key)?;
+        Ok(self.read.pretty_ron(&WithUnknown::new(&data, &unknown))?)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn get_data_any_json(&self, key: BalancingDataTypes) -> anyhow::Result<String> {
+        let (data, unknown) = self.get_data_any_with_unknown(key)?;
+        Ok(self.read.pretty_json(&WithUnknown::new(&data, &unknown))?)
+    }
+
+    pub fn get_version(&self) -> &str {
+        self.version.as_deref().unwrap_or_default()
+    }
+
+    /// Same as [`BalancingDataArchive::set_readable_dates`]
+    pub fn set_readable_dates(&mut self, readable_dates: bool) {
+        self.read.readable_dates = readable_dates;
+    }
+
+    pub fn readable_dates(&self) -> bool {
+        self.read.readable_dates
+    }
+
+    /// Same as [`BalancingDataArchive::schema_revision`], typed reads convert from it on the fly
+    #[cfg(feature = "reflect")]
+    pub fn schema_revision(&self) -> &'static SchemaRevision {
+        self.read.schema_revision(self.get_version())
+    }
+
+    /// Overrides the revision picked from the version, `None` goes back to picking it
+    #[cfg(feature = "reflect")]
+    pub fn set_schema_revision(&mut self, revision: Option<&'static SchemaRevision>) {
+        self.read.revision = revision;
+    }
+
+    /// Fully decodes the container into an editable archive, readable dates and the schema revision are kept
+    pub fn to_archive(&self) -> anyhow::Result<BalancingDataArchive> {
+        let mut archive = BalancingDataArchive::new(self.buf.clone())?;
+        archive.read = self.read;
+        Ok(archive)
+    }
+}
+
+//a map entry is a message of its own, missing fields mean default values
+fn scan_entry(buf: &[u8]) -> anyhow::Result<(String, &[u8])> {
+    let mut key = None;
+    let mut value: &[u8] = &[];
+
+    for record in records(buf)? {
+        match (record.number, record.wire_type) {
+            (ENTRY_KEY, WireType::LengthDelimited) => key = Some(record.value),
+            (ENTRY_VALUE, WireType::LengthDelimited) => value = record.value,
+            _ => {}
+        }
+    }
+
+    let key = String::from_utf8(key.unwrap_or_default().to_vec())?;
+    Ok((key, value))
+}
@@ -1,11 +1,34 @@
-use std::fmt::Display;
+//BalancingDataTypes, its FromStr/Display impls and for_each_balancing_data_type!, generated from proto/balancing_types.txt
+include!(concat!(env!("OUT_DIR"), "/balancing_types.rs"));
+
+mod any;
+mod bulk;
+#[cfg(feature = "serde")]
+mod dates;
+#[cfg(feature = "json")]
+mod diff;
+#[cfg(feature = "serde")]
+mod document;
+mod index;
+mod lazy;
+mod lossless;
+#[cfg(feature = "json")]
+mod patch;
+mod reading;
+mod registry;
+mod requirements;
+#[cfg(feature = "reflect")]
+mod revision;
+mod stats;
+mod validate;
+
+use std::borrow::Cow;
 use std::str::FromStr;
 use std::{
     fs::File,
     io::{Read, Write},
 };
 
-use anyhow::bail;
 use prost::{
     bytes::{Buf, BufMut},
     DecodeError, Message,
@@ -20,7 +43,38 @@ use ron::{
 #[cfg(feature = "serde")]
 use serde::Serialize;
 
+#[cfg(any(feature = "json", feature = "ron"))]
+use epic_wire::{capture_unknown, CaptureUnknown, WithUnknown};
+
+#[cfg(any(feature = "json", feature = "ron"))]
+use document::ArchiveSeed;
+
+#[cfg(feature = "json")]
+use serde::de::DeserializeSeed;
+
+#[cfg(feature = "reflect")]
+use epic_wire::Descriptors;
+use epic_wire::UnknownFields;
+use epic_wire::wire::{records, Record};
+use lossless::{encode_lossless, LosslessSource, Reencode};
 use proto::SerializedBalancingDataContainer;
+use reading::ReadSettings;
+
+pub use any::AnyBalancingData;
+pub use bulk::DecodedBalancingData;
+#[cfg(feature = "serde")]
+pub use dates::{ReadableDates, DATE_FIELDS};
+#[cfg(feature = "json")]
+pub use diff::{ArchiveDiff, Change};
+pub use index::{IdLocation, NameIdIndex};
+pub use lazy::LazyBalancingDataArchive;
+#[cfg(feature = "json")]
+pub use patch::{paths_overlap, Patch, PatchOperation};
+pub use registry::{BalancingDataGroup, BalancingDataTypeInfo, BALANCING_DATA_TYPES};
+#[cfg(feature = "reflect")]
+pub use revision::{SchemaChange, SchemaRevision};
+pub use stats::{CharacterKind, CharacterStats, EquipmentStats, StatCalculator};
+pub use validate::ValidationIssue;
 
 pub mod proto {
     include!(concat!(env!("OUT_DIR"), "/abepic.balancing.rs"));
@@ -28,14 +82,26 @@ pub mod proto {
     include!(concat!(env!("OUT_DIR"), "/abepic.balancing.serde.rs"));
 }
 
+const CONTAINER_MESSAGE: &str = "abepic.balancing.SerializedBalancingDataContainer";
+const ENTRY_MESSAGE: &str = "abepic.balancing.SerializedBalancingDataContainer.AllBalancingDataEntry";
+const ENTRIES_FIELD: u32 = 1;
+
+epic_wire::include_descriptors!(pub(crate) fn descriptors);
+
 pub struct BalancingDataArchive {
     container: SerializedBalancingDataContainer,
+    unknown: UnknownFields,
+    lossless: Option<LosslessSource>,
+    read: ReadSettings,
 }
 
 impl BalancingDataArchive {
     pub fn default() -> Self {
         Self {
             container: SerializedBalancingDataContainer::default(),
+            unknown: UnknownFields::default(),
+            lossless: None,
+            read: ReadSettings::default(),
         }
     }
 
@@ -50,12 +116,111 @@ impl BalancingDataArchive {
         Self::new(decompressed.as_slice())
     }
 
-    pub fn new<B>(buf: B) -> anyhow::Result<Self>
+    pub fn new<B>(mut buf: B) -> anyhow::Result<Self>
     where
         B: Buf,
     {
+        //slices and Bytes are read in place, only buffers made of several chunks are copied
+        let copied;
+        let buf = if buf.chunk().len() == buf.remaining() {
+            buf.chunk()
+        } else {
+            copied = buf.copy_to_bytes(buf.remaining());
+            copied.as_ref()
+        };
+
         let container = SerializedBalancingDataContainer::decode(buf)?;
-        Ok(Self { container })
+        let unknown = container_unknown_fields(buf)?;
+        Ok(Self {
+            container,
+            unknown,
+            lossless: None,
+            read: ReadSettings::default(),
+        })
+    }
+
+    /// Same as [`Self::new_gzipped`], but keeps the original bytes so untouched data is written back unchanged
+    pub fn new_gzipped_lossless<B>(mut buf: B) -> anyhow::Result<Self>
+    where
+        B: Buf + Read,
+    {
+        let compressed = buf.copy_to_bytes(buf.remaining()).to_vec();
+
+        let mut decoder = flate2::read::GzDecoder::new(compressed.as_slice());
+        let mut decompressed = Vec::new();
+        decoder.read_to_end(&mut decompressed)?;
+
+        let mut archive = Self::new_lossless(decompressed.as_slice())?;
+        if let Some(source) = archive.lossless.as_mut() {
+            source.gzipped = Some(compressed);
+        }
+
+        Ok(archive)
+    }
+
+    /// Same as [`Self::new`], but keeps the original bytes so untouched data is written back unchanged
+    pub fn new_lossless<B>(mut buf: B) -> anyhow::Result<Self>
+    where
+        B: Buf,
+    {
+        let raw = buf.copy_to_bytes(buf.remaining()).to_vec();
+
+        let mut archive = Self::new(raw.as_slice())?;
+        archive.lossless = Some(LosslessSource { raw, gzipped: None });
+
+        Ok(archive)
+    }
+
+    pub fn is_lossless(&self) -> bool {
+        self.lossless.is_some()
+    }
+
+    /// Turning lossless mode on takes the current state of the archive as the original
+    pub fn set_lossless(&mut self, lossless: bool) -> anyhow::Result<()> {
+        if !lossless {
+            self.lossless = None;
+        } else if self.lossless.is_none() {
+            self.lossless = Some(LosslessSource {
+                raw: self.encode_container()?,
+                gzipped: None,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn encode_container(&self) -> anyhow::Result<Vec<u8>> {
+        if let Some(source) = &self.lossless {
+            if let Ok(buf) = encode_lossless(&self.container, &self.unknown, Some(CONTAINER_MESSAGE), &source.raw) {
+                return Ok(buf);
+            }
+        }
+
+        Ok(self
+            .unknown
+            .merge(descriptors(), CONTAINER_MESSAGE, &self.container.encode_to_vec())?)
+    }
+
+    /// Unknown fields of the container itself, the ones of each entry are kept in its raw bytes
+    pub fn get_unknown_fields(&self) -> &UnknownFields {
+        &self.unknown
+    }
+
+    pub fn set_unknown_fields(&mut self, unknown: UnknownFields) {
+        self.unknown = unknown;
+    }
+
+    fn get_key_unknown_fields(&self, key: &str) -> Result<UnknownFields, DecodeError> {
+        match (BalancingDataTypes::from_str(key), self.get_data_key_current(key)?) {
+            (Ok(data_type), Some(data)) => {
+                UnknownFields::extract(descriptors(), data_type.full_message_name(), &data)
+            }
+            _ => Ok(UnknownFields::default()),
+        }
+    }
+
+    pub fn get_data_unknown_fields(&self, key: BalancingDataTypes) -> Result<UnknownFields, DecodeError> {
+        self.get_key_unknown_fields(&key.to_string())
     }
 
     pub fn get_balaning_data_keys(&self) -> Vec<String> {
@@ -74,16 +239,23 @@ impl BalancingDataArchive {
             .map(|balancing_data| balancing_data.1)
     }
 
+    //the entry in the revision the generated types are made from, converted if the archive is in another one
+    pub(crate) fn get_data_key_current(&self, key: &str) -> Result<Option<Cow<'_, [u8]>>, DecodeError> {
+        self.get_data_key(key)
+            .map(|data| self.read.current(self.get_version(), key, data))
+            .transpose()
+    }
+
     pub fn get_data_key_decoded<T>(&self, key: &str) -> Result<T, DecodeError>
     where
         T: Message + Default,
     {
-        let data = match self.get_data_key(key) {
+        let data = match self.get_data_key_current(key)? {
             Some(data) => data,
             None => return Err(DecodeError::new("Key not found")),
         };
 
-        T::decode(data.as_slice())
+        T::decode(data.as_ref())
     }
 
     pub fn get_data_enum_decoded<T>(&self, key: BalancingDataTypes) -> Result<T, DecodeError>
@@ -99,7 +271,8 @@ impl BalancingDataArchive {
         T: Message + Serialize + Default,
     {
         let data = self.get_data_key_decoded::<T>(key)?;
-        Ok(ron::ser::to_string_pretty(&data, Default::default())?)
+        let unknown = self.get_key_unknown_fields(key)?;
+        Ok(self.read.pretty_ron(&WithUnknown::new(&data, &unknown))?)
     }
     
     #[cfg(feature = "json")]
@@ -108,7 +281,8 @@ impl BalancingDataArchive {
         T: Message + Serialize + Default,
     {
         let data = self.get_data_key_decoded::<T>(key)?;
-        Ok(serde_json::to_string_pretty(&data)?)
+        let unknown = self.get_key_unknown_fields(key)?;
+        Ok(self.read.pretty_json(&WithUnknown::new(&data, &unknown))?)
     }
 
     #[cfg(feature = "ron")]
@@ -127,12 +301,85 @@ impl BalancingDataArchive {
         self.get_data_key_decoded_json::<T>(&key.to_string())
     }
 
+    pub fn get_data_any(&self, key: BalancingDataTypes) -> Result<AnyBalancingData, DecodeError> {
+        let data = match self.get_data_key_current(&key.to_string())? {
+            Some(data) => data,
+            None => return Err(DecodeError::new("Key not found")),
+        };
+
+        AnyBalancingData::decode(key, data.as_ref())
+    }
+
+    pub fn get_data_key_any(&self, key: &str) -> anyhow::Result<AnyBalancingData> {
+        Ok(self.get_data_any(BalancingDataTypes::from_str(key)?)?)
+    }
+
+    pub fn get_data_any_with_unknown(
+        &self,
+        key: BalancingDataTypes,
+    ) -> Result<(AnyBalancingData, UnknownFields), DecodeError> {
+        Ok((self.get_data_any(key)?, self.get_data_unknown_fields(key)?))
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn get_data_any_ron(&self, key: BalancingDataTypes) -> anyhow::Result<String> {
+        let (data, unknown) = self.get_data_any_with_unknown(key)?;
+        Ok(self.read.pretty_ron(&WithUnknown::new(&data, &unknown))?)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn get_data_any_json(&self, key: BalancingDataTypes) -> anyhow::Result<String> {
+        let (data, unknown) = self.get_data_any_with_unknown(key)?;
+        Ok(self.read.pretty_json(&WithUnknown::new(&data, &unknown))?)
+    }
+
+    /// Decodes a container against descriptors loaded at runtime instead of the compiled in `balancing.proto`,
+    /// `message` defaults to the one the container type uses, or the one named like the container for unknown types
+    #[cfg(feature = "reflect")]
+    pub fn get_data_key_reflect(
+        &self,
+        key: &str,
+        descriptors: &Descriptors,
+        message: Option<&str>,
+    ) -> anyhow::Result<serde_json::Value> {
+        let data = self
+            .get_data_key(key)
+            .ok_or_else(|| anyhow::anyhow!("No container named {}", key))?;
+
+        descriptors.decode_dynamic(message.unwrap_or_else(|| key_message(descriptors, key)), data)
+    }
+
+    /// Encodes the json form [`BalancingDataArchive::get_data_key_reflect`] gives back into the container
+    #[cfg(feature = "reflect")]
+    pub fn set_data_key_reflect(
+        &mut self,
+        key: &str,
+        descriptors: &Descriptors,
+        message: Option<&str>,
+        value: &serde_json::Value,
+    ) -> anyhow::Result<()> {
+        let data = descriptors.encode_dynamic(message.unwrap_or_else(|| key_message(descriptors, key)), value)?;
+        self.set_data_key_raw(key, data);
+
+        Ok(())
+    }
+
     pub fn set_data_key_raw(&mut self, key: &str, data: Vec<u8>) {
         self.container
             .all_balancing_data
             .insert(key.to_string(), data);
     }
 
+    /// Returns if the key was there
+    pub fn remove_data_key(&mut self, key: &str) -> bool {
+        let len = self.container.all_balancing_data.len();
+        self.container
+            .all_balancing_data
+            .retain(|balancing_key, _| balancing_key != key);
+
+        self.container.all_balancing_data.len() != len
+    }
+
     pub fn set_data_key<T>(&mut self, key: &str, data: T) -> anyhow::Result<()>
     where
         T: Message,
@@ -145,31 +392,56 @@ impl BalancingDataArchive {
         Ok(())
     }
 
+    fn set_data_key_with_unknown<T>(&mut self, key: &str, data: T, unknown: UnknownFields) -> anyhow::Result<()>
+    where
+        T: Message + Default + PartialEq,
+    {
+        //known types take the set_data_any path, their message is where unknown fields go
+        if let Ok(data_type) = BalancingDataTypes::from_str(key) {
+            let buf = self.encode_typed(data_type, &data, &unknown)?;
+            self.set_data_key_raw(key, buf);
+
+            return Ok(());
+        }
+
+        let buf = match (&self.lossless, self.get_data_key(key)) {
+            (Some(_), Some(original)) => encode_lossless(&data, &UnknownFields::default(), None, original)
+                .unwrap_or_else(|_| data.encode_to_vec()),
+            _ => data.encode_to_vec(),
+        };
+
+        self.set_data_key_raw(key, buf);
+
+        Ok(())
+    }
+
     #[cfg(feature = "ron")]
     pub fn set_data_key_ron<T>(&mut self, key: &str, ron: &str) -> anyhow::Result<()>
     where
-        T: Message + Default + serde::de::DeserializeOwned,
+        T: Message + Default + PartialEq + serde::de::DeserializeOwned,
     {
-        //todo: there is def a less stupid way of doing this
-        let options = Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
-        let data: T = options.from_str(ron)?;
-        self.set_data_key(key, data)
+        let (data, unknown) = ron_options().from_str_seed(ron, capture_unknown::<T>())?;
+        self.set_data_key_with_unknown(key, data, unknown)
     }
     
     #[cfg(feature = "json")]
     pub fn set_data_key_json<T>(&mut self, key: &str, json: &str) -> anyhow::Result<()>
     where
-        T: Message + Default + serde::de::DeserializeOwned,
+        T: Message + Default + PartialEq + serde::de::DeserializeOwned,
     {
-        let data: T = serde_json::from_str(json)?;
-        self.set_data_key(key, data)
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        let (data, unknown) = capture_unknown::<T>().deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        self.set_data_key_with_unknown(key, data, unknown)
     }
 
+    /// Unknown fields of the entry being replaced are kept
     pub fn set_data_enum<T>(&mut self, key: BalancingDataTypes, data: T) -> anyhow::Result<()>
     where
-        T: Message,
+        T: Message + Default + PartialEq,
     {
-        self.set_data_key(key.to_string().as_str(), data)
+        let unknown = self.get_data_unknown_fields(key)?;
+        self.set_data_key_with_unknown(key.to_string().as_str(), data, unknown)
     }
 
     #[cfg(feature = "ron")]
@@ -179,7 +451,7 @@ impl BalancingDataArchive {
         ron: &str,
     ) -> anyhow::Result<()>
     where
-        T: Message + Default + serde::de::DeserializeOwned,
+        T: Message + Default + PartialEq + serde::de::DeserializeOwned,
     {
         self.set_data_key_ron::<T>(key.to_string().as_str(), ron)
     }
@@ -191,19 +463,91 @@ impl BalancingDataArchive {
         json: &str,
     ) -> anyhow::Result<()>
     where
-        T: Message + Default + serde::de::DeserializeOwned,
+        T: Message + Default + PartialEq + serde::de::DeserializeOwned,
     {
         self.set_data_key_json::<T>(key.to_string().as_str(), json)
     }
 
+    /// Unknown fields of the entry being replaced are kept
+    pub fn set_data_any(&mut self, data: &AnyBalancingData) -> anyhow::Result<()> {
+        let unknown = self.get_data_unknown_fields(data.data_type())?;
+        self.set_data_any_with_unknown(data, &unknown)
+    }
+
+    pub fn set_data_any_with_unknown(
+        &mut self,
+        data: &AnyBalancingData,
+        unknown: &UnknownFields,
+    ) -> anyhow::Result<()> {
+        let buf = self.encode_data_any(data, unknown)?;
+        self.set_data_key_raw(&data.data_type().to_string(), buf);
+
+        Ok(())
+    }
+
+    fn encode_data_any(&self, data: &AnyBalancingData, unknown: &UnknownFields) -> anyhow::Result<Vec<u8>> {
+        self.encode_typed(data.data_type(), data, unknown)
+    }
+
+    //`data` has to be the message of `data_type`
+    fn encode_typed<T>(&self, data_type: BalancingDataTypes, data: &T, unknown: &UnknownFields) -> anyhow::Result<Vec<u8>>
+    where
+        T: Reencode,
+    {
+        let message_name = data_type.full_message_name();
+        let original = self.get_data_key_current(&data_type.to_string())?;
+
+        let lossless = match (&self.lossless, &original) {
+            (Some(_), Some(original)) => encode_lossless(data, unknown, Some(message_name), original).ok(),
+            _ => None,
+        };
+
+        let buf = match lossless {
+            Some(buf) => buf,
+            None => unknown.merge(descriptors(), message_name, &data.encode_plain())?,
+        };
+
+        //back to the revision of the archive, entries that didn't change keep their bytes
+        #[cfg(feature = "reflect")]
+        if !self.schema_revision().is_current() {
+            if let (Some(_), Some(original)) = (&self.lossless, &original) {
+                if original.as_ref() == buf.as_slice() {
+                    return Ok(self.get_data_key(&data_type.to_string()).cloned().unwrap_or_default());
+                }
+            }
+
+            return SchemaRevision::current().convert(self.schema_revision(), message_name, &buf);
+        }
+
+        Ok(buf)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn set_data_any_ron(&mut self, key: BalancingDataTypes, ron: &str) -> anyhow::Result<()> {
+        let (data, unknown) = parse_any_ron(key, ron)?;
+        self.set_data_any_with_unknown(&data, &unknown)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn set_data_any_json(&mut self, key: BalancingDataTypes, json: &str) -> anyhow::Result<()> {
+        let (data, unknown) = parse_any_json(key, json)?;
+        self.set_data_any_with_unknown(&data, &unknown)
+    }
+
     pub fn write_gzipped<W>(&self, writer: &mut W) -> anyhow::Result<()>
     where
         W: BufMut + Write,
     {
-        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let buf = self.encode_container()?;
 
-        let mut buf = Vec::new();
-        self.container.encode(&mut buf)?;
+        //the compressed stream is only identical if the compressor settings match, reuse it when nothing changed
+        if let Some(LosslessSource { raw, gzipped: Some(gzipped) }) = &self.lossless {
+            if raw == &buf {
+                return writer.write_all(gzipped).map_err(anyhow::Error::new);
+            }
+        }
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
         encoder.write_all(&buf)?;
 
         writer
@@ -215,7 +559,9 @@ impl BalancingDataArchive {
     where
         W: BufMut + Write,
     {
-        self.container.encode(writer).map_err(anyhow::Error::new)
+        writer
+            .write_all(&self.encode_container()?)
+            .map_err(anyhow::Error::new)
     }
 
     pub fn save(&self, mut file: File) -> anyhow::Result<()> {
@@ -239,196 +585,173 @@ impl BalancingDataArchive {
     pub fn get_version(&self) -> &str {
         self.container.version()
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum BalancingDataTypes {
-    //NORMAL BALANCING
-    BattleBalancingData,
-    ChronicleCaveFloorBalancingData,
-    CustomMessageBalancingData,
-    HotspotBalancingData,
-    ExperienceScalingBalancingData,
-    CollectionGroupBalancingData,
-    ExperienceLevelBalancingData,
-    InventoryBalancingData,
-    ShopBalancingData,
-    BattleParticipantTableBalancingData,
-    ClassSkinBalancingData,
-    ConditionalInventoryBalancingData,
-    ChronicleCaveBattleBalancingData,
-    ExperienceMasteryBalancingData,
-    BirdBalancingData,
-    CraftingRecipeBalancingData,
-    MiniCampaignBalancingData,
-    LootTableBalancingData,
-    SocialEnvironmentBalancingData,
-    BannerItemBalancingData,
-    PremiumShopOfferBalancingData,
-    GachaShopOfferBalancingData,
-    PowerLevelBalancingData,
-    PigBalancingData,
-    EnchantingBalancingData,
-    BuyableShopOfferBalancingData,
-    ClientConfigBalancingData,
-    ConsumableItemBalancingData,
-    ResourceCostPerLevelBalancingData,
-    SkillBalancingData,
-    EventItemBalancingData,
-    GameConstantsBalancingData,
-    CraftingItemBalancingData,
-    DailyLoginGiftsBalancingData,
-    LoadingHintBalancingData,
-    SetFusionBalancingData,
-    EquipmentBalancingData,
-    ChronicleCaveHotspotBalancingData,
-    MasteryItemBalancingData,
-    SplashScreenBalancingData,
-    PigTypePowerLevelBalancingData,
-    PvPObjectivesBalancingData,
-    ThirdPartyIdBalancingData,
-    BasicItemBalancingData,
-    BattleHintBalancingData,
-    BossBalancingData,
-    ScoreBalancingData,
-    SalesManagerBalancingData,
-    BannerBalancingData,
-    ChronicleCaveBattleParticipantTableBalancingData,
-    ClassItemBalancingData,
-    //EVENT STUFF
-    EventBalancingData,
-    BonusEventBalancingData,
-    PvPSeasonManagerBalancingData,
-    EventPlacementBalancingData,
-    EventManagerBalancingData,
+    /// Write the fields in [`DATE_FIELDS`] as RFC 3339 dates instead of Unix seconds in json/ron,
+    /// both are read back either way
+    pub fn set_readable_dates(&mut self, readable_dates: bool) {
+        self.read.readable_dates = readable_dates;
+    }
+
+    pub fn readable_dates(&self) -> bool {
+        self.read.readable_dates
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_dates<'a, T: ?Sized>(&self, value: &'a T) -> ReadableDates<'a, T> {
+        self.read.with_dates(value)
+    }
+
+    /// Revision of `balancing.proto` the containers are in, picked from the version unless set with
+    /// [`Self::set_schema_revision`]. Typed reads and writes convert from and to it on the fly
+    #[cfg(feature = "reflect")]
+    pub fn schema_revision(&self) -> &'static SchemaRevision {
+        self.read.schema_revision(self.get_version())
+    }
+
+    /// Overrides the revision picked from the version, `None` goes back to picking it
+    #[cfg(feature = "reflect")]
+    pub fn set_schema_revision(&mut self, revision: Option<&'static SchemaRevision>) {
+        self.read.revision = revision;
+    }
+
+    /// Converts every container of a known type from the revision `from` (the one of the archive if `None`) to `to`,
+    /// returns how many were converted. The archive is in `to` afterwards, the version is left as is
+    #[cfg(feature = "reflect")]
+    pub fn convert_schema(
+        &mut self,
+        from: Option<&'static SchemaRevision>,
+        to: &'static SchemaRevision,
+    ) -> anyhow::Result<usize> {
+        let from = from.unwrap_or_else(|| self.schema_revision());
+        if std::ptr::eq(from, to) {
+            self.read.revision = Some(to);
+            return Ok(0);
+        }
+
+        let mut count = 0;
+        for key in self.get_balaning_data_keys() {
+            let Ok(data_type) = BalancingDataTypes::from_str(&key) else {
+                continue;
+            };
+
+            let data = self.get_data_key(&key).map(Vec::as_slice).unwrap_or_default();
+            let converted = from
+                .convert(to, data_type.full_message_name(), data)
+                .map_err(|e| anyhow::anyhow!("{}: {}", key, e))?;
+            self.set_data_key_raw(&key, converted);
+            count += 1;
+        }
+        self.read.revision = Some(to);
+
+        Ok(count)
+    }
+
+    /// Whole archive as one json document, `version` plus every container in its typed form keyed by container name
+    #[cfg(feature = "json")]
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Replaces the containers (and version) that are in the document, everything else is kept
+    #[cfg(feature = "json")]
+    pub fn update_from_json(&mut self, json: &str) -> anyhow::Result<()> {
+        let mut deserializer = serde_json::Deserializer::from_str(json);
+        ArchiveSeed(self).deserialize(&mut deserializer)?;
+        Ok(deserializer.end()?)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// JSON Schema of the document `to_json` writes, every known container type is in it
+    #[cfg(feature = "json")]
+    pub fn json_schema() -> serde_json::Value {
+        document::archive_schema()
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn from_ron(ron: &str) -> anyhow::Result<Self> {
+        let mut archive = Self::default();
+        archive.update_from_ron(ron)?;
+        Ok(archive)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn update_from_ron(&mut self, ron: &str) -> anyhow::Result<()> {
+        Ok(ron_options().from_str_seed(ron, ArchiveSeed(self))?)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn to_ron(&self) -> ron::Result<String> {
+        ron::ser::to_string(self)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn to_ron_pretty(&self) -> ron::Result<String> {
+        ron::ser::to_string_pretty(self, Default::default())
+    }
 }
 
-impl FromStr for BalancingDataTypes {
-    type Err = anyhow::Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "ABH.Shared.BalancingData.BattleBalancingData" => Ok(BalancingDataTypes::BattleBalancingData),
-            "ABH.Shared.BalancingData.ChronicleCaveFloorBalancingData" => Ok(BalancingDataTypes::ChronicleCaveFloorBalancingData),
-            "ABH.Shared.BalancingData.CustomMessageBalancingData" => Ok(BalancingDataTypes::CustomMessageBalancingData),
-            "ABH.Shared.BalancingData.HotspotBalancingData" => Ok(BalancingDataTypes::HotspotBalancingData),
-            "ABH.Shared.BalancingData.ExperienceScalingBalancingData" => Ok(BalancingDataTypes::ExperienceScalingBalancingData),
-            "ABH.Shared.BalancingData.CollectionGroupBalancingData" => Ok(BalancingDataTypes::CollectionGroupBalancingData),
-            "ABH.Shared.BalancingData.ExperienceLevelBalancingData" => Ok(BalancingDataTypes::ExperienceLevelBalancingData),
-            "ABH.Shared.BalancingData.InventoryBalancingData" => Ok(BalancingDataTypes::InventoryBalancingData),
-            "ABH.Shared.BalancingData.ShopBalancingData" => Ok(BalancingDataTypes::ShopBalancingData),
-            "ABH.Shared.BalancingData.BattleParticipantTableBalancingData" => Ok(BalancingDataTypes::BattleParticipantTableBalancingData),
-            "ABH.Shared.BalancingData.ClassSkinBalancingData" => Ok(BalancingDataTypes::ClassSkinBalancingData),
-            "ABH.Shared.BalancingData.ConditionalInventoryBalancingData" => Ok(BalancingDataTypes::ConditionalInventoryBalancingData),
-            "ABH.Shared.BalancingData.ChronicleCaveBattleBalancingData" => Ok(BalancingDataTypes::ChronicleCaveBattleBalancingData),
-            "ABH.Shared.BalancingData.ExperienceMasteryBalancingData" => Ok(BalancingDataTypes::ExperienceMasteryBalancingData),
-            "ABH.Shared.BalancingData.BirdBalancingData" => Ok(BalancingDataTypes::BirdBalancingData),
-            "ABH.Shared.BalancingData.CraftingRecipeBalancingData" => Ok(BalancingDataTypes::CraftingRecipeBalancingData),
-            "ABH.Shared.BalancingData.MiniCampaignBalancingData" => Ok(BalancingDataTypes::MiniCampaignBalancingData),
-            "ABH.Shared.BalancingData.LootTableBalancingData" => Ok(BalancingDataTypes::LootTableBalancingData),
-            "ABH.Shared.BalancingData.SocialEnvironmentBalancingData" => Ok(BalancingDataTypes::SocialEnvironmentBalancingData),
-            "ABH.Shared.BalancingData.BannerItemBalancingData" => Ok(BalancingDataTypes::BannerItemBalancingData),
-            "ABH.Shared.BalancingData.PremiumShopOfferBalancingData" => Ok(BalancingDataTypes::PremiumShopOfferBalancingData),
-            "ABH.Shared.BalancingData.GachaShopOfferBalancingData" => Ok(BalancingDataTypes::GachaShopOfferBalancingData),
-            "ABH.Shared.BalancingData.PowerLevelBalancingData" => Ok(BalancingDataTypes::PowerLevelBalancingData),
-            "ABH.Shared.BalancingData.PigBalancingData" => Ok(BalancingDataTypes::PigBalancingData),
-            "ABH.Shared.BalancingData.EnchantingBalancingData" => Ok(BalancingDataTypes::EnchantingBalancingData),
-            "ABH.Shared.BalancingData.BuyableShopOfferBalancingData" => Ok(BalancingDataTypes::BuyableShopOfferBalancingData),
-            "ABH.Shared.BalancingData.ClientConfigBalancingData" => Ok(BalancingDataTypes::ClientConfigBalancingData),
-            "ABH.Shared.BalancingData.ConsumableItemBalancingData" => Ok(BalancingDataTypes::ConsumableItemBalancingData),
-            "ABH.Shared.BalancingData.ResourceCostPerLevelBalancingData" => Ok(BalancingDataTypes::ResourceCostPerLevelBalancingData),
-            "ABH.Shared.BalancingData.SkillBalancingData" => Ok(BalancingDataTypes::SkillBalancingData),
-            "ABH.Shared.BalancingData.EventItemBalancingData" => Ok(BalancingDataTypes::EventItemBalancingData),
-            "ABH.Shared.BalancingData.GameConstantsBalancingData" => Ok(BalancingDataTypes::GameConstantsBalancingData),
-            "ABH.Shared.BalancingData.CraftingItemBalancingData" => Ok(BalancingDataTypes::CraftingItemBalancingData),
-            "ABH.Shared.BalancingData.DailyLoginGiftsBalancingData" => Ok(BalancingDataTypes::DailyLoginGiftsBalancingData),
-            "ABH.Shared.BalancingData.LoadingHintBalancingData" => Ok(BalancingDataTypes::LoadingHintBalancingData),
-            "ABH.Shared.BalancingData.SetFusionBalancingData" => Ok(BalancingDataTypes::SetFusionBalancingData),
-            "ABH.Shared.BalancingData.EquipmentBalancingData" => Ok(BalancingDataTypes::EquipmentBalancingData),
-            "ABH.Shared.BalancingData.ChronicleCaveHotspotBalancingData" => Ok(BalancingDataTypes::ChronicleCaveHotspotBalancingData),
-            "ABH.Shared.BalancingData.MasteryItemBalancingData" => Ok(BalancingDataTypes::MasteryItemBalancingData),
-            "ABH.Shared.BalancingData.SplashScreenBalancingData" => Ok(BalancingDataTypes::SplashScreenBalancingData),
-            "ABH.Shared.BalancingData.PigTypePowerLevelBalancingData" => Ok(BalancingDataTypes::PigTypePowerLevelBalancingData),
-            "ABH.Shared.BalancingData.PvPObjectivesBalancingData" => Ok(BalancingDataTypes::PvPObjectivesBalancingData),
-            "ABH.Shared.BalancingData.ThirdPartyIdBalancingData" => Ok(BalancingDataTypes::ThirdPartyIdBalancingData),
-            "ABH.Shared.BalancingData.BasicItemBalancingData" => Ok(BalancingDataTypes::BasicItemBalancingData),
-            "ABH.Shared.BalancingData.BattleHintBalancingData" => Ok(BalancingDataTypes::BattleHintBalancingData),
-            "ABH.Shared.BalancingData.BossBalancingData" => Ok(BalancingDataTypes::BossBalancingData),
-            "ABH.Shared.BalancingData.ScoreBalancingData" => Ok(BalancingDataTypes::ScoreBalancingData),
-            "ABH.Shared.BalancingData.SalesManagerBalancingData" => Ok(BalancingDataTypes::SalesManagerBalancingData),
-            "ABH.Shared.BalancingData.BannerBalancingData" => Ok(BalancingDataTypes::BannerBalancingData),
-            "ABH.Shared.BalancingData.ChronicleCaveBattleParticipantTableBalancingData" => Ok(BalancingDataTypes::ChronicleCaveBattleParticipantTableBalancingData),
-            "ABH.Shared.BalancingData.ClassItemBalancingData" => Ok(BalancingDataTypes::ClassItemBalancingData),
-            "ABH.Shared.Events.BalancingData.EventBalancingData" => Ok(BalancingDataTypes::EventBalancingData),
-            "ABH.Shared.Events.BalancingData.BonusEventBalancingData" => Ok(BalancingDataTypes::BonusEventBalancingData),
-            "ABH.Shared.Events.BalancingData.PvPSeasonManagerBalancingData" => Ok(BalancingDataTypes::PvPSeasonManagerBalancingData),
-            "ABH.Shared.Events.BalancingData.EventPlacementBalancingData" => Ok(BalancingDataTypes::EventPlacementBalancingData),
-            "ABH.Shared.Events.BalancingData.EventManagerBalancingData" => Ok(BalancingDataTypes::EventManagerBalancingData),
-            _ => bail!("Unknown BalancingData type: {}", s),
+//entries are plain bytes, so the full extraction only has to run if a record or entry field isn't one the proto has,
+//the unknown fields of each entry are only extracted once they're asked for
+fn container_unknown_fields(buf: &[u8]) -> Result<UnknownFields, DecodeError> {
+    let has_unknown = |message: &str, records: &[Record]| {
+        let info = descriptors().message(message).expect("the container messages are in balancing.proto");
+        records.iter().any(|record| info.field(record.number).is_none())
+    };
+
+    let container = records(buf)?;
+    let mut unknown = has_unknown(CONTAINER_MESSAGE, &container);
+    for entry in container.iter().filter(|record| record.number == ENTRIES_FIELD) {
+        if unknown {
+            break;
         }
+        unknown = has_unknown(ENTRY_MESSAGE, &records(entry.value)?);
+    }
+
+    if !unknown {
+        return Ok(UnknownFields::default());
     }
+
+    UnknownFields::extract(descriptors(), CONTAINER_MESSAGE, buf)
 }
 
-impl Display for BalancingDataTypes {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let str = match self {
-            BalancingDataTypes::BattleBalancingData => "ABH.Shared.BalancingData.BattleBalancingData",
-            BalancingDataTypes::ChronicleCaveFloorBalancingData => "ABH.Shared.BalancingData.ChronicleCaveFloorBalancingData",
-            BalancingDataTypes::CustomMessageBalancingData => "ABH.Shared.BalancingData.CustomMessageBalancingData",
-            BalancingDataTypes::HotspotBalancingData => "ABH.Shared.BalancingData.HotspotBalancingData",
-            BalancingDataTypes::ExperienceScalingBalancingData => "ABH.Shared.BalancingData.ExperienceScalingBalancingData",
-            BalancingDataTypes::CollectionGroupBalancingData => "ABH.Shared.BalancingData.CollectionGroupBalancingData",
-            BalancingDataTypes::ExperienceLevelBalancingData => "ABH.Shared.BalancingData.ExperienceLevelBalancingData",
-            BalancingDataTypes::InventoryBalancingData => "ABH.Shared.BalancingData.InventoryBalancingData",
-            BalancingDataTypes::ShopBalancingData => "ABH.Shared.BalancingData.ShopBalancingData",
-            BalancingDataTypes::BattleParticipantTableBalancingData => "ABH.Shared.BalancingData.BattleParticipantTableBalancingData",
-            BalancingDataTypes::ClassSkinBalancingData => "ABH.Shared.BalancingData.ClassSkinBalancingData",
-            BalancingDataTypes::ConditionalInventoryBalancingData => "ABH.Shared.BalancingData.ConditionalInventoryBalancingData",
-            BalancingDataTypes::ChronicleCaveBattleBalancingData => "ABH.Shared.BalancingData.ChronicleCaveBattleBalancingData",
-            BalancingDataTypes::ExperienceMasteryBalancingData => "ABH.Shared.BalancingData.ExperienceMasteryBalancingData",
-            BalancingDataTypes::BirdBalancingData => "ABH.Shared.BalancingData.BirdBalancingData",
-            BalancingDataTypes::CraftingRecipeBalancingData => "ABH.Shared.BalancingData.CraftingRecipeBalancingData",
-            BalancingDataTypes::MiniCampaignBalancingData => "ABH.Shared.BalancingData.MiniCampaignBalancingData",
-            BalancingDataTypes::LootTableBalancingData => "ABH.Shared.BalancingData.LootTableBalancingData",
-            BalancingDataTypes::SocialEnvironmentBalancingData => "ABH.Shared.BalancingData.SocialEnvironmentBalancingData",
-            BalancingDataTypes::BannerItemBalancingData => "ABH.Shared.BalancingData.BannerItemBalancingData",
-            BalancingDataTypes::PremiumShopOfferBalancingData => "ABH.Shared.BalancingData.PremiumShopOfferBalancingData",
-            BalancingDataTypes::GachaShopOfferBalancingData => "ABH.Shared.BalancingData.GachaShopOfferBalancingData",
-            BalancingDataTypes::PowerLevelBalancingData => "ABH.Shared.BalancingData.PowerLevelBalancingData",
-            BalancingDataTypes::PigBalancingData => "ABH.Shared.BalancingData.PigBalancingData",
-            BalancingDataTypes::EnchantingBalancingData => "ABH.Shared.BalancingData.EnchantingBalancingData",
-            BalancingDataTypes::BuyableShopOfferBalancingData => "ABH.Shared.BalancingData.BuyableShopOfferBalancingData",
-            BalancingDataTypes::ClientConfigBalancingData => "ABH.Shared.BalancingData.ClientConfigBalancingData",
-            BalancingDataTypes::ConsumableItemBalancingData => "ABH.Shared.BalancingData.ConsumableItemBalancingData",
-            BalancingDataTypes::ResourceCostPerLevelBalancingData => "ABH.Shared.BalancingData.ResourceCostPerLevelBalancingData",
-            BalancingDataTypes::SkillBalancingData => "ABH.Shared.BalancingData.SkillBalancingData",
-            BalancingDataTypes::EventItemBalancingData => "ABH.Shared.BalancingData.EventItemBalancingData",
-            BalancingDataTypes::GameConstantsBalancingData => "ABH.Shared.BalancingData.GameConstantsBalancingData",
-            BalancingDataTypes::CraftingItemBalancingData => "ABH.Shared.BalancingData.CraftingItemBalancingData",
-            BalancingDataTypes::DailyLoginGiftsBalancingData => "ABH.Shared.BalancingData.DailyLoginGiftsBalancingData",
-            BalancingDataTypes::LoadingHintBalancingData => "ABH.Shared.BalancingData.LoadingHintBalancingData",
-            BalancingDataTypes::SetFusionBalancingData => "ABH.Shared.BalancingData.SetFusionBalancingData",
-            BalancingDataTypes::EquipmentBalancingData => "ABH.Shared.BalancingData.EquipmentBalancingData",
-            BalancingDataTypes::ChronicleCaveHotspotBalancingData => "ABH.Shared.BalancingData.ChronicleCaveHotspotBalancingData",
-            BalancingDataTypes::MasteryItemBalancingData => "ABH.Shared.BalancingData.MasteryItemBalancingData",
-            BalancingDataTypes::SplashScreenBalancingData => "ABH.Shared.BalancingData.SplashScreenBalancingData",
-            BalancingDataTypes::PigTypePowerLevelBalancingData => "ABH.Shared.BalancingData.PigTypePowerLevelBalancingData",
-            BalancingDataTypes::PvPObjectivesBalancingData => "ABH.Shared.BalancingData.PvPObjectivesBalancingData",
-            BalancingDataTypes::ThirdPartyIdBalancingData => "ABH.Shared.BalancingData.ThirdPartyIdBalancingData",
-            BalancingDataTypes::BasicItemBalancingData => "ABH.Shared.BalancingData.BasicItemBalancingData",
-            BalancingDataTypes::BattleHintBalancingData => "ABH.Shared.BalancingData.BattleHintBalancingData",
-            BalancingDataTypes::BossBalancingData => "ABH.Shared.BalancingData.BossBalancingData",
-            BalancingDataTypes::ScoreBalancingData => "ABH.Shared.BalancingData.ScoreBalancingData",
-            BalancingDataTypes::SalesManagerBalancingData => "ABH.Shared.BalancingData.SalesManagerBalancingData",
-            BalancingDataTypes::BannerBalancingData => "ABH.Shared.BalancingData.BannerBalancingData",
-            BalancingDataTypes::ChronicleCaveBattleParticipantTableBalancingData => "ABH.Shared.BalancingData.ChronicleCaveBattleParticipantTableBalancingData",
-            BalancingDataTypes::ClassItemBalancingData => "ABH.Shared.BalancingData.ClassItemBalancingData",
-            BalancingDataTypes::EventBalancingData => "ABH.Shared.Events.BalancingData.EventBalancingData",
-            BalancingDataTypes::BonusEventBalancingData => "ABH.Shared.Events.BalancingData.BonusEventBalancingData",
-            BalancingDataTypes::PvPSeasonManagerBalancingData => "ABH.Shared.Events.BalancingData.PvPSeasonManagerBalancingData",
-            BalancingDataTypes::EventPlacementBalancingData => "ABH.Shared.Events.BalancingData.EventPlacementBalancingData",
-            BalancingDataTypes::EventManagerBalancingData => "ABH.Shared.Events.BalancingData.EventManagerBalancingData"
-        };
-        write!(f, "{}", str)
+/// Options every ron document of the crate is read with, `Some(...)` can be left out around optional fields
+#[cfg(feature = "ron")]
+pub fn ron_options() -> Options {
+    Options::default().with_default_extension(Extensions::IMPLICIT_SOME)
+}
+
+#[cfg(feature = "ron")]
+fn parse_any_ron(key: BalancingDataTypes, ron: &str) -> anyhow::Result<(AnyBalancingData, UnknownFields)> {
+    Ok(ron_options().from_str_seed(ron, CaptureUnknown(key))?)
+}
+
+#[cfg(feature = "json")]
+fn parse_any_json(key: BalancingDataTypes, json: &str) -> anyhow::Result<(AnyBalancingData, UnknownFields)> {
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    let parsed = CaptureUnknown(key).deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(parsed)
+}
+
+//known containers decode as the message the registry has for them, some aren't named like the container
+//(`ChronicleCaveBattleBalancingData` is a `BattleBalancingData`), unknown ones as the last segment of the key
+#[cfg(feature = "reflect")]
+fn key_message<'a>(descriptors: &Descriptors, key: &'a str) -> &'a str {
+    match BalancingDataTypes::from_str(key) {
+        //descriptors with another package still find it by name
+        Ok(data_type) if descriptors.message(data_type.full_message_name()).is_none() => {
+            data_type.message_name()
+        }
+        Ok(data_type) => data_type.full_message_name(),
+        Err(_) => key.rsplit('.').next().unwrap_or(key),
     }
 }
@@ -0,0 +1,153 @@
+use std::collections::{HashMap, VecDeque};
+
+use epic_wire::wire::{parse_record, record_len, split_records};
+use epic_wire::UnknownFields;
+use prost::{DecodeError, Message};
+
+use crate::descriptors;
+
+/// Something that can be re-encoded losslessly, decoding has to know the exact type so it goes through an existing value
+pub(crate) trait Reencode: PartialEq + Sized {
+    fn decode_as(&self, buf: &[u8]) -> Result<Self, DecodeError>;
+    fn encode_plain(&self) -> Vec<u8>;
+}
+
+impl Reencode for crate::AnyBalancingData {
+    fn decode_as(&self, buf: &[u8]) -> Result<Self, DecodeError> {
+        Self::decode(self.data_type(), buf)
+    }
+
+    fn encode_plain(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+}
+
+impl<T> Reencode for T
+where
+    T: Message + Default + PartialEq,
+{
+    fn decode_as(&self, buf: &[u8]) -> Result<Self, DecodeError> {
+        T::decode(buf)
+    }
+
+    fn encode_plain(&self) -> Vec<u8> {
+        self.encode_to_vec()
+    }
+}
+
+fn encode_with_unknown<T>(
+    data: &T,
+    unknown: &UnknownFields,
+    message_name: Option<&str>,
+) -> Result<Vec<u8>, DecodeError>
+where
+    T: Reencode,
+{
+    match message_name {
+        Some(message_name) => unknown.merge(descriptors(), message_name, &data.encode_plain()),
+        None => Ok(data.encode_plain()),
+    }
+}
+
+fn extract_unknown(message_name: Option<&str>, buf: &[u8]) -> Result<UnknownFields, DecodeError> {
+    match message_name {
+        Some(message_name) => UnknownFields::extract(descriptors(), message_name, buf),
+        None => Ok(UnknownFields::default()),
+    }
+}
+
+/// Encodes `data` and its unknown fields, reusing the bytes and places of every top level field record of `original` that did not change.
+///
+/// Each original record is decoded on its own and re-encoded to get the bytes prost would write for it,
+/// wherever those bytes show up in the new encoding the original record is written instead.
+/// Records then go back into the places the original had for their field number, in the order of the new encoding,
+/// so untouched records keep their position, a changed one takes the place of the one it replaces and extra ones
+/// follow the last record of their field (or go at the end for fields the original didn't have).
+/// This keeps the original layout (record order, map entry contents, packed fields, explicit defaults) of everything
+/// that was not touched, if nothing changed at all the original bytes are returned as is.
+pub(crate) fn encode_lossless<T>(
+    data: &T,
+    unknown: &UnknownFields,
+    message_name: Option<&str>,
+    original: &[u8],
+) -> Result<Vec<u8>, DecodeError>
+where
+    T: Reencode,
+{
+    if data.decode_as(original)? == *data && extract_unknown(message_name, original)? == *unknown {
+        return Ok(original.to_vec());
+    }
+
+    let encoded = encode_with_unknown(data, unknown, message_name)?;
+    let records = split_records(original)?;
+
+    //canonical encoding -> original records, keyed by the first canonical record so lookups stay cheap
+    let mut candidates: HashMap<Vec<u8>, Vec<_>> = HashMap::new();
+    //places of each field number in the original, in order
+    let mut places: HashMap<u32, VecDeque<usize>> = HashMap::new();
+    for (position, record) in records.iter().enumerate() {
+        places.entry(parse_record(record)?.number).or_default().push_back(position);
+
+        let canonical = encode_with_unknown(
+            &data.decode_as(record)?,
+            &extract_unknown(message_name, record)?,
+            message_name,
+        )?;
+        if canonical.is_empty() {
+            continue;
+        }
+
+        let first_len = record_len(&canonical)?;
+        candidates
+            .entry(canonical[..first_len].to_vec())
+            .or_default()
+            .push((canonical, *record));
+    }
+
+    let mut placed: Vec<Vec<&[u8]>> = vec![Vec::new(); records.len()];
+    let mut last_place: HashMap<u32, usize> = HashMap::new();
+    let mut appended = Vec::new();
+    let mut remaining = encoded.as_slice();
+
+    while !remaining.is_empty() {
+        let len = record_len(remaining)?;
+
+        let matched = candidates.get_mut(&remaining[..len]).and_then(|candidates| {
+            let index = candidates
+                .iter()
+                .position(|(canonical, _)| remaining.starts_with(canonical))?;
+            Some(candidates.remove(index))
+        });
+
+        let (record, consumed) = match matched {
+            Some((canonical, record)) => (record, canonical.len()),
+            None => (&remaining[..len], len),
+        };
+        remaining = &remaining[consumed..];
+
+        let number = parse_record(record)?.number;
+        match places.get_mut(&number).and_then(VecDeque::pop_front) {
+            Some(position) => {
+                placed[position].push(record);
+                last_place.insert(number, position);
+            }
+            None => match last_place.get(&number) {
+                Some(&position) => placed[position].push(record),
+                None => appended.push(record),
+            },
+        }
+    }
+
+    let mut output = Vec::with_capacity(original.len().max(encoded.len()));
+    for record in placed.into_iter().flatten().chain(appended) {
+        output.extend_from_slice(record);
+    }
+
+    Ok(output)
+}
+
+/// Original bytes of a container kept around to re-encode it losslessly
+pub(crate) struct LosslessSource {
+    pub raw: Vec<u8>,
+    pub gzipped: Option<Vec<u8>>,
+}
@@ -0,0 +1,468 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use epic_wire::path::{quote, unquote};
+use epic_wire::WithUnknown;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::diff::{ArchiveDiff, Change};
+use crate::{parse_any_json, BalancingDataArchive, BalancingDataTypes};
+
+const NAME_ID: &str = "nameId";
+
+/// Edits to an archive that don't depend on where things are in it.
+///
+/// Operations work like RFC 6902 JSON Patch on the json form of a container, with paths written the way
+/// [`ArchiveDiff`] writes them: `pigData["pig_king"].baseHealth` is the `baseHealth` of the `pigData` entry with the
+/// `nameId` `pig_king` wherever it ends up in the list, `["x"#2]` is the second entry named `x`, `[3]` and `[-]`
+/// (end of the list, only for `add`) are plain list indices and `["key"]` on a map is the value of that key.
+/// An empty path is the whole container, containers of an unknown type are base64 of their raw bytes.
+/// Containers of a known type can be given by their short name (`SkillBalancingData`)
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Patch {
+    /// Version of the archive the patch was made against, only informational
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub operations: Vec<PatchOperation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    /// Adds the value, an entry selected by name id that already exists is replaced
+    Add {
+        container: String,
+        #[serde(default)]
+        path: String,
+        value: Value,
+    },
+    Remove {
+        container: String,
+        #[serde(default)]
+        path: String,
+    },
+    /// Like `add` but the value has to be there already
+    Replace {
+        container: String,
+        #[serde(default)]
+        path: String,
+        value: Value,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Field(String),
+    Index(usize),
+    Append,
+    //name id (or map key) and which one of the entries with it, starting at 1
+    Select(String, usize),
+}
+
+//what a container turns into once every operation on it went through
+enum Staged {
+    Typed(Value),
+    Raw(Vec<u8>),
+    Removed,
+}
+
+impl Patch {
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json_pretty(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Turns a diff into the patch that makes its old archive into `new`, entry and container values are taken from `new`
+    pub fn from_diff(diff: &ArchiveDiff, new: &BalancingDataArchive) -> anyhow::Result<Self> {
+        let mut containers: HashMap<BalancingDataTypes, Value> = HashMap::new();
+        let mut new_value = |container: &str, path: &str| -> anyhow::Result<Value> {
+            let Ok(data_type) = BalancingDataTypes::from_str(container) else {
+                let raw = new.get_data_key(container).ok_or_else(|| anyhow!("{} is not in the new archive", container))?;
+                return Ok(Value::String(BASE64_STANDARD.encode(raw)));
+            };
+
+            let container = match containers.entry(data_type) {
+                Entry::Occupied(container) => container.into_mut(),
+                Entry::Vacant(container) => container.insert(container_value(new, data_type)?),
+            };
+            resolve(container, &parse_path(path)?)
+                .cloned()
+                .ok_or_else(|| anyhow!("{} {} is not in the new archive", container, path))
+        };
+
+        let mut operations = Vec::new();
+        for change in &diff.changes {
+            operations.push(match change {
+                Change::ContainerAdded { key } => PatchOperation::Add {
+                    container: key.clone(),
+                    path: String::new(),
+                    value: new_value(key, "")?,
+                },
+                Change::ContainerRemoved { key } => PatchOperation::Remove {
+                    container: key.clone(),
+                    path: String::new(),
+                },
+                Change::ContainerChanged { key } => PatchOperation::Replace {
+                    container: key.clone(),
+                    path: String::new(),
+                    value: new_value(key, "")?,
+                },
+                Change::EntryAdded { key, path } => PatchOperation::Add {
+                    container: key.clone(),
+                    path: path.clone(),
+                    value: new_value(key, path)?,
+                },
+                Change::EntryRemoved { key, path } | Change::FieldChanged { key, path, new: None, .. } => {
+                    PatchOperation::Remove {
+                        container: key.clone(),
+                        path: path.clone(),
+                    }
+                }
+                Change::FieldChanged { key, path, old: None, new: Some(value) } => PatchOperation::Add {
+                    container: key.clone(),
+                    path: path.clone(),
+                    value: value.clone(),
+                },
+                Change::FieldChanged { key, path, new: Some(value), .. } => PatchOperation::Replace {
+                    container: key.clone(),
+                    path: path.clone(),
+                    value: value.clone(),
+                },
+            });
+        }
+
+        //the diff lists removed list items front to back, removing them that way would shift the ones after
+        let mut start = 0;
+        while start < operations.len() {
+            let mut end = start;
+            while end < operations.len() && is_index_removal(&operations[end], &operations[start]) {
+                end += 1;
+            }
+            operations[start..end.max(start + 1)].reverse();
+            start = end.max(start + 1);
+        }
+
+        Ok(Self {
+            version: Some(diff.old_version.clone()),
+            operations,
+        })
+    }
+}
+
+impl BalancingDataArchive {
+    /// Applies every operation in order, nothing is changed if any of them fails
+    pub fn apply_patch(&mut self, patch: &Patch) -> anyhow::Result<()> {
+        let mut staged: Vec<(String, Staged)> = Vec::new();
+
+        for (index, operation) in patch.operations.iter().enumerate() {
+            self.stage(operation, &mut staged)
+                .map_err(|e| anyhow!("operation {} ({}): {}", index, operation.describe(), e))?;
+        }
+
+        let mut encoded = Vec::with_capacity(staged.len());
+        for (key, container) in staged {
+            encoded.push(match container {
+                Staged::Typed(value) => {
+                    let data_type = BalancingDataTypes::from_str(&key)?;
+                    let (data, unknown) = parse_any_json(data_type, &value.to_string())
+                        .map_err(|e| anyhow!("{} doesn't fit its container after patching: {}", key, e))?;
+                    (key, Some(self.encode_data_any(&data, &unknown)?))
+                }
+                Staged::Raw(raw) => (key, Some(raw)),
+                Staged::Removed => (key, None),
+            });
+        }
+
+        for (key, buf) in encoded {
+            match buf {
+                Some(buf) => self.set_data_key_raw(&key, buf),
+                None => {
+                    self.remove_data_key(&key);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Patch that turns `self` into `new`
+    pub fn make_patch(&self, new: &BalancingDataArchive) -> anyhow::Result<Patch> {
+        Patch::from_diff(&self.diff(new)?, new)
+    }
+
+    fn stage(&self, operation: &PatchOperation, staged: &mut Vec<(String, Staged)>) -> anyhow::Result<()> {
+        let (key, data_type) = container_key(operation.container());
+        let path = parse_path(operation.path())?;
+
+        let position = match staged.iter().position(|(staged_key, _)| *staged_key == key) {
+            Some(position) => position,
+            None => {
+                let current = match (data_type, self.get_data_key(&key)) {
+                    (_, None) => Staged::Removed,
+                    (Some(data_type), Some(_)) => Staged::Typed(container_value(self, data_type)?),
+                    (None, Some(raw)) => Staged::Raw(raw.clone()),
+                };
+                staged.push((key.clone(), current));
+                staged.len() - 1
+            }
+        };
+        let container = &mut staged[position].1;
+
+        if path.is_empty() {
+            *container = match (operation, &container) {
+                (PatchOperation::Remove { .. }, Staged::Removed) | (PatchOperation::Replace { .. }, Staged::Removed) => {
+                    bail!("the container doesn't exist")
+                }
+                (PatchOperation::Remove { .. }, _) => Staged::Removed,
+                (PatchOperation::Add { value, .. } | PatchOperation::Replace { value, .. }, _) => {
+                    match data_type {
+                        Some(_) => Staged::Typed(value.clone()),
+                        None => Staged::Raw(
+                            BASE64_STANDARD.decode(value.as_str().ok_or_else(|| {
+                                anyhow!("containers of an unknown type have to be base64 strings")
+                            })?)?,
+                        ),
+                    }
+                }
+            };
+
+            return Ok(());
+        }
+
+        let root = match container {
+            Staged::Typed(root) => root,
+            Staged::Raw(_) => bail!("containers of an unknown type can only be patched as a whole"),
+            Staged::Removed => bail!("the container doesn't exist"),
+        };
+
+        let (last, parents) = path.split_last().expect("path is not empty");
+        let parent = resolve_mut(root, parents).ok_or_else(|| anyhow!("nothing is at the path"))?;
+
+        match operation {
+            PatchOperation::Add { value, .. } => add(parent, last, value.clone()),
+            PatchOperation::Remove { .. } => remove(parent, last),
+            PatchOperation::Replace { value, .. } => {
+                let target = child_mut(parent, last).ok_or_else(|| anyhow!("nothing is at the path"))?;
+                *target = value.clone();
+                Ok(())
+            }
+        }
+    }
+}
+
+impl PatchOperation {
+    pub fn container(&self) -> &str {
+        match self {
+            PatchOperation::Add { container, .. }
+            | PatchOperation::Remove { container, .. }
+            | PatchOperation::Replace { container, .. } => container,
+        }
+    }
+
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOperation::Add { path, .. } | PatchOperation::Remove { path, .. } | PatchOperation::Replace { path, .. } => {
+                path
+            }
+        }
+    }
+
+    /// Full key of the container, the same for a known type given by its short name
+    pub fn container_key(&self) -> String {
+        container_key(self.container()).0
+    }
+
+    fn describe(&self) -> String {
+        let op = match self {
+            PatchOperation::Add { .. } => "add",
+            PatchOperation::Remove { .. } => "remove",
+            PatchOperation::Replace { .. } => "replace",
+        };
+
+        format!("{} {} {}", op, self.container(), self.path())
+    }
+}
+
+/// If two paths in the same container point at the same value or one is inside the other,
+/// appends to a list never overlap and neither do paths that can't be parsed
+pub fn paths_overlap(a: &str, b: &str) -> bool {
+    let (Ok(a), Ok(b)) = (parse_path(a), parse_path(b)) else {
+        return false;
+    };
+
+    a.iter().zip(&b).all(|segments| match segments {
+        (Segment::Append, _) | (_, Segment::Append) => false,
+        (Segment::Field(a) | Segment::Select(a, 1), Segment::Field(b) | Segment::Select(b, 1)) => a == b,
+        (a, b) => a == b,
+    })
+}
+
+//full key of the container, known types can also be given by their short name
+fn container_key(container: &str) -> (String, Option<BalancingDataTypes>) {
+    let data_type = match BalancingDataTypes::from_str(container) {
+        Ok(data_type) => Some(data_type),
+        Err(_) if !container.contains('.') => BalancingDataTypes::from_any_name(container),
+        Err(_) => None,
+    };
+
+    match data_type {
+        Some(data_type) => (data_type.to_string(), Some(data_type)),
+        None => (container.to_string(), None),
+    }
+}
+
+fn container_value(archive: &BalancingDataArchive, data_type: BalancingDataTypes) -> anyhow::Result<Value> {
+    let (data, unknown) = archive.get_data_any_with_unknown(data_type)?;
+    Ok(serde_json::to_value(WithUnknown::new(&data, &unknown))?)
+}
+
+//removals of list items that directly follow each other in the same list
+fn is_index_removal(operation: &PatchOperation, first: &PatchOperation) -> bool {
+    let list = |operation: &PatchOperation| match operation {
+        PatchOperation::Remove { container, path } if path.ends_with(']') => {
+            let (list, index) = path.rsplit_once('[')?;
+            index.trim_end_matches(']').parse::<usize>().ok()?;
+            Some((container.clone(), list.to_string()))
+        }
+        _ => None,
+    };
+
+    list(operation).is_some() && list(operation) == list(first)
+}
+
+fn parse_path(path: &str) -> anyhow::Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+
+    while !rest.is_empty() {
+        if let Some(inner) = rest.strip_prefix('[') {
+            let (segment, after) = parse_bracket(inner).ok_or_else(|| anyhow!("invalid path {}", path))?;
+            segments.push(segment);
+            rest = after;
+        } else {
+            let field = rest.strip_prefix('.').unwrap_or(rest);
+            let end = field.find(['.', '[']).unwrap_or(field.len());
+            if end == 0 {
+                bail!("invalid path {}", path);
+            }
+
+            segments.push(Segment::Field(field[..end].to_string()));
+            rest = &field[end..];
+        }
+    }
+
+    Ok(segments)
+}
+
+//everything after a `[`, returns the segment and what's after the `]`
+fn parse_bracket(inner: &str) -> Option<(Segment, &str)> {
+    if let Some(after) = inner.strip_prefix("-]") {
+        return Some((Segment::Append, after));
+    }
+
+    if !inner.starts_with('"') {
+        let (index, after) = inner.split_once(']')?;
+        return Some((Segment::Index(index.parse().ok()?), after));
+    }
+
+    let (name, after) = unquote(inner)?;
+    let (occurrence, after) = after.split_once(']')?;
+    let occurrence = match occurrence.strip_prefix('#') {
+        Some(occurrence) => occurrence.parse().ok()?,
+        None if occurrence.is_empty() => 1,
+        None => return None,
+    };
+
+    Some((Segment::Select(name, occurrence), after))
+}
+
+fn resolve<'a>(value: &'a Value, path: &[Segment]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, segment| match (value, segment) {
+        (Value::Object(fields), Segment::Field(name) | Segment::Select(name, 1)) => fields.get(name),
+        (Value::Array(items), Segment::Index(index)) => items.get(*index),
+        (Value::Array(items), Segment::Select(name, occurrence)) => {
+            selected(items, name, *occurrence).map(|index| &items[index])
+        }
+        _ => None,
+    })
+}
+
+fn resolve_mut<'a>(value: &'a mut Value, path: &[Segment]) -> Option<&'a mut Value> {
+    path.iter().try_fold(value, child_mut)
+}
+
+fn child_mut<'a>(value: &'a mut Value, segment: &Segment) -> Option<&'a mut Value> {
+    match (value, segment) {
+        (Value::Object(fields), Segment::Field(name) | Segment::Select(name, 1)) => fields.get_mut(name),
+        (Value::Array(items), Segment::Index(index)) => items.get_mut(*index),
+        (Value::Array(items), Segment::Select(name, occurrence)) => {
+            let index = selected(items, name, *occurrence)?;
+            items.get_mut(index)
+        }
+        _ => None,
+    }
+}
+
+//index of the nth entry with the name id
+fn selected(items: &[Value], name_id: &str, occurrence: usize) -> Option<usize> {
+    items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| item.get(NAME_ID).and_then(Value::as_str) == Some(name_id))
+        .nth(occurrence.checked_sub(1)?)
+        .map(|(index, _)| index)
+}
+
+fn add(parent: &mut Value, segment: &Segment, value: Value) -> anyhow::Result<()> {
+    match (parent, segment) {
+        (Value::Object(fields), Segment::Field(name) | Segment::Select(name, 1)) => {
+            fields.insert(name.clone(), value);
+        }
+        (Value::Array(items), Segment::Index(index)) if *index <= items.len() => items.insert(*index, value),
+        (Value::Array(items), Segment::Append) => items.push(value),
+        (Value::Array(items), Segment::Select(name, occurrence)) => match selected(items, name, *occurrence) {
+            Some(index) => items[index] = value,
+            None => {
+                if value.get(NAME_ID).and_then(Value::as_str) != Some(name.as_str()) {
+                    bail!("the added entry doesn't have the name id {}", quote(name));
+                }
+                items.push(value)
+            }
+        },
+        _ => bail!("nothing can be added at the path"),
+    }
+
+    Ok(())
+}
+
+fn remove(parent: &mut Value, segment: &Segment) -> anyhow::Result<()> {
+    let removed = match (parent, segment) {
+        (Value::Object(fields), Segment::Field(name) | Segment::Select(name, 1)) => fields.shift_remove(name).is_some(),
+        (Value::Array(items), Segment::Index(index)) if *index < items.len() => {
+            items.remove(*index);
+            true
+        }
+        (Value::Array(items), Segment::Select(name, occurrence)) => match selected(items, name, *occurrence) {
+            Some(index) => {
+                items.remove(index);
+                true
+            }
+            None => false,
+        },
+        _ => false,
+    };
+
+    if !removed {
+        bail!("nothing is at the path");
+    }
+
+    Ok(())
+}
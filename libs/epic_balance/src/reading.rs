@@ -0,0 +1,60 @@
+use std::borrow::Cow;
+#[cfg(feature = "reflect")]
+use std::str::FromStr;
+
+use prost::DecodeError;
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+#[cfg(feature = "serde")]
+use crate::ReadableDates;
+#[cfg(feature = "reflect")]
+use crate::{BalancingDataTypes, SchemaRevision};
+
+//how typed reads see the stored containers, the same for BalancingDataArchive and LazyBalancingDataArchive
+#[derive(Clone, Copy, Default)]
+pub(crate) struct ReadSettings {
+    pub(crate) readable_dates: bool,
+    #[cfg(feature = "reflect")]
+    pub(crate) revision: Option<&'static SchemaRevision>,
+}
+
+impl ReadSettings {
+    #[cfg(feature = "reflect")]
+    pub(crate) fn schema_revision(&self, version: &str) -> &'static SchemaRevision {
+        self.revision.unwrap_or_else(|| SchemaRevision::for_version(version))
+    }
+
+    //`data` of the container `key` in the revision the generated types are made from, converted if the archive is in another one
+    #[cfg_attr(not(feature = "reflect"), allow(unused_variables))]
+    pub(crate) fn current<'a>(&self, version: &str, key: &str, data: &'a [u8]) -> Result<Cow<'a, [u8]>, DecodeError> {
+        #[cfg(feature = "reflect")]
+        {
+            let revision = self.schema_revision(version);
+            if let (false, Ok(data_type)) = (revision.is_current(), BalancingDataTypes::from_str(key)) {
+                let converted = revision
+                    .convert(SchemaRevision::current(), data_type.full_message_name(), data)
+                    .map_err(|e| DecodeError::new(format!("{} from schema revision {}: {}", key, revision.name, e)))?;
+                return Ok(Cow::Owned(converted));
+            }
+        }
+
+        Ok(Cow::Borrowed(data))
+    }
+
+    #[cfg(feature = "serde")]
+    pub(crate) fn with_dates<'a, T: ?Sized>(&self, value: &'a T) -> ReadableDates<'a, T> {
+        ReadableDates::with(value, self.readable_dates)
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn pretty_json<T: Serialize + ?Sized>(&self, value: &T) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.with_dates(value))
+    }
+
+    #[cfg(feature = "ron")]
+    pub(crate) fn pretty_ron<T: Serialize + ?Sized>(&self, value: &T) -> ron::Result<String> {
+        ron::ser::to_string_pretty(&self.with_dates(value), Default::default())
+    }
+}
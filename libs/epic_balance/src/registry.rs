@@ -0,0 +1,101 @@
+use crate::BalancingDataTypes;
+
+/// Which part of the game a balancing data container belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BalancingDataGroup {
+    Normal,
+    Event,
+}
+
+/// Everything the crate knows about a single balancing data container type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BalancingDataTypeInfo {
+    pub data_type: BalancingDataTypes,
+    /// Full C# type name, used as the key inside `SerializedBalancingDataContainer`
+    pub type_name: &'static str,
+    /// C# type name without the namespace
+    pub short_name: &'static str,
+    /// Name of the message in `balancing.proto` the container decodes as
+    pub message_name: &'static str,
+    /// Same as `message_name`, but with the proto package in front
+    pub full_message_name: &'static str,
+    pub group: BalancingDataGroup,
+}
+
+macro_rules! generate_registry {
+    ($({ $group:ident, $type_name:literal, $message_name:literal, $variant:ident => $proto_type:ty }),+ $(,)?) => {
+        /// Every balancing data container type, in the same order as [`BalancingDataTypes`]
+        pub static BALANCING_DATA_TYPES: &[BalancingDataTypeInfo] = &[
+            $(
+                BalancingDataTypeInfo {
+                    data_type: BalancingDataTypes::$variant,
+                    type_name: $type_name,
+                    short_name: stringify!($variant),
+                    message_name: $message_name,
+                    full_message_name: concat!("abepic.balancing.", $message_name),
+                    group: BalancingDataGroup::$group,
+                },
+            )+
+        ];
+
+        impl BalancingDataTypes {
+            pub fn info(&self) -> &'static BalancingDataTypeInfo {
+                BALANCING_DATA_TYPES
+                    .iter()
+                    .find(|info| info.data_type == *self)
+                    .expect("every BalancingDataTypes variant is registered")
+            }
+        }
+    };
+}
+
+for_each_balancing_data_type!(generate_registry!());
+
+impl BalancingDataTypes {
+    pub fn all() -> impl Iterator<Item = BalancingDataTypes> {
+        BALANCING_DATA_TYPES.iter().map(|info| info.data_type)
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        self.info().type_name
+    }
+
+    pub fn short_name(&self) -> &'static str {
+        self.info().short_name
+    }
+
+    pub fn message_name(&self) -> &'static str {
+        self.info().message_name
+    }
+
+    pub fn full_message_name(&self) -> &'static str {
+        self.info().full_message_name
+    }
+
+    pub fn group(&self) -> BalancingDataGroup {
+        self.info().group
+    }
+
+    /// Looks a type up by its full C# type name, the short name or the last segment of a dotted name
+    pub fn from_any_name(name: &str) -> Option<BalancingDataTypes> {
+        let short_name = name.rsplit('.').next().unwrap_or(name);
+
+        BALANCING_DATA_TYPES
+            .iter()
+            .find(|info| info.type_name == name)
+            .or_else(|| BALANCING_DATA_TYPES.iter().find(|info| info.short_name == short_name))
+            .map(|info| info.data_type)
+    }
+
+    /// JSON Schema of the container the way `to_json` writes it
+    #[cfg(feature = "json")]
+    pub fn json_schema(&self) -> serde_json::Value {
+        let mut builder = epic_wire::SchemaBuilder::new(crate::descriptors()).date_fields(crate::DATE_FIELDS);
+        let root = builder
+            .message(self.full_message_name())
+            .expect("every container message is in the descriptors");
+        let root = builder.with_unknown(root);
+
+        builder.build(self.full_message_name(), root)
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+
+use epic_wire::wire::records;
+use epic_wire::MessageInfo;
+use prost::{encoding::WireType, DecodeError, Message};
+
+use crate::bulk::map_all;
+use crate::index::{entry_name_id, join, lookup};
+use crate::proto::Requirement;
+use crate::{BalancingDataArchive, BalancingDataTypes, IdLocation};
+
+const REQUIREMENT_MESSAGE: &str = "abepic.balancing.Requirement";
+
+impl BalancingDataArchive {
+    /// Every requirement of every entry with the name id (buy and show requirements of a shop offer, battle requirements...),
+    /// in container order
+    pub fn requirements_of(&self, name_id: &str) -> Result<Vec<(IdLocation, Requirement)>, DecodeError> {
+        let found = map_all(self.known_keys(), |data_type| match self.get_data_key_current(&data_type.to_string())? {
+            Some(data) => container_requirements(data_type, &data, name_id),
+            None => Ok(Vec::new()),
+        });
+
+        let mut requirements = Vec::new();
+        for found in found {
+            requirements.extend(found?);
+        }
+
+        Ok(requirements)
+    }
+}
+
+fn container_requirements(
+    data_type: BalancingDataTypes,
+    buf: &[u8],
+    name_id: &str,
+) -> Result<Vec<(IdLocation, Requirement)>, DecodeError> {
+    let container = lookup(data_type.full_message_name())?;
+    let mut requirements = Vec::new();
+    let mut occurrences: HashMap<u32, usize> = HashMap::new();
+
+    for record in records(buf)? {
+        let Some(field) = container.field(record.number) else {
+            continue;
+        };
+        let occurrence = occurrences.entry(record.number).or_default();
+        let path = format!("{}[{}]", field.json_name(), occurrence);
+        *occurrence += 1;
+
+        let Some(entry) = field.message_type().filter(|_| record.wire_type == WireType::LengthDelimited) else {
+            continue;
+        };
+        let entry = lookup(entry)?;
+
+        if entry_name_id(entry, record.value)?.as_deref() != Some(name_id) {
+            continue;
+        }
+
+        let location = |path: String| IdLocation {
+            data_type,
+            path,
+            entry: Some(name_id.to_string()),
+        };
+        for (path, requirement) in message_requirements(entry, record.value, &path)? {
+            requirements.push((location(path), requirement));
+        }
+    }
+
+    Ok(requirements)
+}
+
+fn message_requirements(
+    message: &MessageInfo,
+    buf: &[u8],
+    path: &str,
+) -> Result<Vec<(String, Requirement)>, DecodeError> {
+    let mut requirements = Vec::new();
+    let mut occurrences: HashMap<u32, usize> = HashMap::new();
+
+    for record in records(buf)? {
+        let Some(field) = message.field(record.number) else {
+            continue;
+        };
+        let occurrence = occurrences.entry(record.number).or_default();
+        let field_path = if field.repeated {
+            join(path, &format!("{}[{}]", field.json_name(), occurrence))
+        } else {
+            join(path, &field.json_name())
+        };
+        *occurrence += 1;
+
+        if record.wire_type != WireType::LengthDelimited {
+            continue;
+        }
+
+        //requirements don't hold other requirements, anything else can
+        match field.message_type() {
+            Some(REQUIREMENT_MESSAGE) => requirements.push((field_path, Requirement::decode(record.value)?)),
+            Some(nested) => {
+                let nested = lookup(nested)?;
+                if !nested.map_entry {
+                    requirements.extend(message_requirements(nested, record.value, &field_path)?);
+                }
+            }
+            None => {}
+        }
+    }
+
+    Ok(requirements)
+}
@@ -0,0 +1,293 @@
+use std::cmp::Ordering;
+use std::sync::OnceLock;
+
+use epic_wire::{Descriptors, MessageInfo};
+use serde_json::{Map, Value};
+
+use crate::index::is_message;
+
+/// A revision of `balancing.proto`, listed in `proto/revisions.txt`. Containers written by older game builds
+/// are read with the revision of their version and converted to the current one
+pub struct SchemaRevision {
+    pub name: &'static str,
+    /// First game version written with the revision
+    pub since: &'static str,
+    /// What changed since the revision before it
+    pub changes: &'static [SchemaChange],
+    descriptor_set: &'static [u8],
+    descriptors: OnceLock<Descriptors>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaChange {
+    /// New field, converting a value of it to an older revision is an error
+    Added { message: &'static str, field: &'static str },
+    Renamed {
+        message: &'static str,
+        from: &'static str,
+        to: &'static str,
+    },
+    /// Field that's gone, converting a value of it to a newer revision is an error
+    Retired { message: &'static str, field: &'static str },
+}
+
+include!(concat!(env!("OUT_DIR"), "/schema_revisions.rs"));
+
+//what happens to a field in one conversion step, `to: None` means the other revision doesn't have it
+struct FieldChange {
+    message: &'static str,
+    field: &'static str,
+    to: Option<&'static str>,
+}
+
+impl SchemaRevision {
+    //the ones generated from the manifest, descriptors are decoded on first use
+    const fn compiled(
+        name: &'static str,
+        since: &'static str,
+        descriptor_set: &'static [u8],
+        changes: &'static [SchemaChange],
+    ) -> Self {
+        Self {
+            name,
+            since,
+            changes,
+            descriptor_set,
+            descriptors: OnceLock::new(),
+        }
+    }
+
+    /// Revision that isn't in the manifest, e.g. one loaded with [`Descriptors::load`].
+    /// It can be stepped to and from with [`Self::upgrade`] and [`Self::downgrade`], [`Self::convert`] only knows the listed ones
+    pub fn new(name: &'static str, since: &'static str, descriptors: Descriptors, changes: &'static [SchemaChange]) -> Self {
+        Self {
+            name,
+            since,
+            changes,
+            descriptor_set: &[],
+            descriptors: OnceLock::from(descriptors),
+        }
+    }
+
+    /// Every revision the crate knows, oldest first
+    pub fn all() -> &'static [SchemaRevision] {
+        &SCHEMA_REVISIONS
+    }
+
+    /// The revision the generated types are made from
+    pub fn current() -> &'static SchemaRevision {
+        &SCHEMA_REVISIONS[SCHEMA_REVISIONS.len() - 1]
+    }
+
+    pub fn from_name(name: &str) -> Option<&'static SchemaRevision> {
+        SCHEMA_REVISIONS.iter().find(|revision| revision.name == name)
+    }
+
+    /// Newest revision that isn't newer than the game version, the oldest one for versions from before all of them.
+    /// Archives without a version are new ones and versions that aren't numbers can't be placed, both get the current revision
+    pub fn for_version(version: &str) -> &'static SchemaRevision {
+        let Some(version) = parse_version(version) else {
+            return Self::current();
+        };
+
+        SCHEMA_REVISIONS
+            .iter()
+            .rev()
+            .find(|revision| {
+                let since = parse_version(revision.since).expect("build script checks the first versions of revisions");
+                compare_versions(&version, &since) != Ordering::Less
+            })
+            .unwrap_or(&SCHEMA_REVISIONS[0])
+    }
+
+    pub fn descriptors(&self) -> &Descriptors {
+        self.descriptors.get_or_init(|| {
+            Descriptors::decode(self.descriptor_set).expect("build script wrote an invalid revision descriptor set")
+        })
+    }
+
+    pub fn is_current(&self) -> bool {
+        std::ptr::eq(self, Self::current())
+    }
+
+    /// Converts an encoded `message` from this revision to `to`, one revision at a time.
+    /// Fails if a field with a value isn't in the revision it's converted to, nothing is dropped
+    pub fn convert(&self, to: &SchemaRevision, message: &str, buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let (Some(from_index), Some(to_index)) = (self.index(), to.index()) else {
+            anyhow::bail!("schema revision {} or {} isn't in the manifest, step between them with upgrade/downgrade", self.name, to.name);
+        };
+        if from_index == to_index {
+            return Ok(buf.to_vec());
+        }
+
+        let mut value = self.descriptors().decode_dynamic(message, buf)?;
+
+        if from_index < to_index {
+            for step in from_index + 1..=to_index {
+                SCHEMA_REVISIONS[step - 1].step_up(&SCHEMA_REVISIONS[step], message, &mut value)?;
+            }
+        } else {
+            for step in (to_index + 1..=from_index).rev() {
+                SCHEMA_REVISIONS[step].step_down(&SCHEMA_REVISIONS[step - 1], message, &mut value)?;
+            }
+        }
+
+        to.descriptors().encode_dynamic(message, &value)
+    }
+
+    /// Converts an encoded `message` to `newer`, the revision right after this one, with the changes of `newer`
+    pub fn upgrade(&self, newer: &SchemaRevision, message: &str, buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut value = self.descriptors().decode_dynamic(message, buf)?;
+        self.step_up(newer, message, &mut value)?;
+        newer.descriptors().encode_dynamic(message, &value)
+    }
+
+    /// Converts an encoded `message` to `older`, the revision right before this one, with the changes of this one
+    pub fn downgrade(&self, older: &SchemaRevision, message: &str, buf: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let mut value = self.descriptors().decode_dynamic(message, buf)?;
+        self.step_down(older, message, &mut value)?;
+        older.descriptors().encode_dynamic(message, &value)
+    }
+
+    fn step_up(&self, newer: &SchemaRevision, message: &str, value: &mut Value) -> anyhow::Result<()> {
+        let changes = newer.changes.iter().filter_map(SchemaChange::forward).collect::<Vec<_>>();
+        convert_value(self, newer, message, value, &changes)
+    }
+
+    fn step_down(&self, older: &SchemaRevision, message: &str, value: &mut Value) -> anyhow::Result<()> {
+        let changes = self.changes.iter().filter_map(SchemaChange::backward).collect::<Vec<_>>();
+        convert_value(self, older, message, value, &changes)
+    }
+
+    fn index(&self) -> Option<usize> {
+        SCHEMA_REVISIONS.iter().position(|revision| std::ptr::eq(self, revision))
+    }
+}
+
+impl SchemaChange {
+    fn forward(&self) -> Option<FieldChange> {
+        match *self {
+            SchemaChange::Added { .. } => None,
+            SchemaChange::Renamed { message, from, to } => Some(FieldChange { message, field: from, to: Some(to) }),
+            SchemaChange::Retired { message, field } => Some(FieldChange { message, field, to: None }),
+        }
+    }
+
+    fn backward(&self) -> Option<FieldChange> {
+        match *self {
+            SchemaChange::Added { message, field } => Some(FieldChange { message, field, to: None }),
+            SchemaChange::Renamed { message, from, to } => Some(FieldChange { message, field: to, to: Some(from) }),
+            SchemaChange::Retired { .. } => None,
+        }
+    }
+}
+
+fn convert_value(
+    from: &SchemaRevision,
+    to: &SchemaRevision,
+    message: &str,
+    value: &mut Value,
+    changes: &[FieldChange],
+) -> anyhow::Result<()> {
+    let Some(info) = from.descriptors().find_message(message) else {
+        anyhow::bail!("no message named {} in schema revision {}", message, from.name);
+    };
+
+    convert_nested(from, to, info, value, changes)
+}
+
+fn convert_nested(
+    from: &SchemaRevision,
+    to: &SchemaRevision,
+    info: &MessageInfo,
+    value: &mut Value,
+    changes: &[FieldChange],
+) -> anyhow::Result<()> {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                convert_nested(from, to, info, item, changes)?;
+            }
+        }
+        //map keys stay, only message values are converted
+        Value::Object(entries) if info.map_entry => {
+            let Some(value_info) = info
+                .field_by_name("value")
+                .and_then(|field| field.message_type())
+                .and_then(|name| from.descriptors().message(name))
+            else {
+                return Ok(());
+            };
+
+            for entry in entries.values_mut() {
+                convert_nested(from, to, value_info, entry, changes)?;
+            }
+        }
+        Value::Object(fields) => convert_fields(from, to, info, fields, changes)?,
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn convert_fields(
+    from: &SchemaRevision,
+    to: &SchemaRevision,
+    info: &MessageInfo,
+    fields: &mut Map<String, Value>,
+    changes: &[FieldChange],
+) -> anyhow::Result<()> {
+    let target_info = to.descriptors().message(&info.name);
+
+    for (key, mut value) in std::mem::take(fields) {
+        //`_unknown` and typos, encoding complains about the latter
+        let Some(field) = info.fields.iter().find(|field| field.json_name() == key || field.name == key) else {
+            fields.insert(key, value);
+            continue;
+        };
+
+        if let Some(nested) = field.message_type().and_then(|name| from.descriptors().message(name)) {
+            convert_nested(from, to, nested, &mut value, changes)?;
+        }
+
+        let change = changes
+            .iter()
+            .find(|change| change.field == field.name && is_message(&info.name, change.message));
+
+        let name = match change {
+            Some(FieldChange { to: None, .. }) => {
+                anyhow::bail!("{}.{} has a value but isn't in schema revision {}", info.name, field.name, to.name)
+            }
+            Some(FieldChange { to: Some(name), .. }) => name,
+            None => field.name.as_str(),
+        };
+
+        let key = target_info
+            .and_then(|target| target.field_by_name(name))
+            .map(|target| target.json_name())
+            .unwrap_or(key);
+        fields.insert(key, value);
+    }
+
+    Ok(())
+}
+
+//`3.1.10` is newer than `3.1.9`, every part has to start with a number, `2.x` or `dev` can't be placed
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    version
+        .trim()
+        .split('.')
+        .map(|part| {
+            let digits = part.trim().chars().take_while(char::is_ascii_digit).collect::<String>();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+//missing parts count as 0
+fn compare_versions(left: &[u64], right: &[u64]) -> Ordering {
+    (0..left.len().max(right.len()))
+        .map(|i| left.get(i).unwrap_or(&0).cmp(right.get(i).unwrap_or(&0)))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use prost::DecodeError;
+
+use crate::proto::bird_balancing_data::BirdData;
+use crate::proto::equipment_balancing_data::EquipmentData;
+use crate::proto::equipment_perk::PerkType;
+use crate::proto::pig_balancing_data::PigData;
+use crate::proto::{
+    BirdBalancingData, EquipmentBalancingData, EquipmentPerk, InventoryItemType, PigBalancingData,
+    PigTypePowerLevelBalancingData,
+};
+use crate::{BalancingDataArchive, BalancingDataTypes};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CharacterKind {
+    Bird,
+    Pig,
+}
+
+/// Stats of a bird or pig at one level, worked out the way the client does: `base + per_level * (level - 1)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CharacterStats {
+    pub name_id: String,
+    pub kind: CharacterKind,
+    pub level: i32,
+    pub health: i64,
+    pub attack: i64,
+    /// Pigs only
+    pub pig_strength: Option<i32>,
+    /// Pigs only
+    pub size_scale: Option<f32>,
+    /// `pig_strength` times the weight `PigTypePowerLevelBalancingData` has for the pig, pigs without one have a weight of 1
+    pub power_level: Option<f32>,
+}
+
+/// Main stat of a piece of equipment at one level and quality along with its perk and set.
+///
+/// The stat is `base_stat + stat_per_level * (level - 1)`, quality adds `stat_per_quality` for every quality above 1
+/// and then the `stat_per_quality_percent` of that quality (1 is the first value) in percent of the whole stat
+#[derive(Debug, Clone, PartialEq)]
+pub struct EquipmentStats {
+    pub name_id: String,
+    pub item_type: InventoryItemType,
+    pub level: i32,
+    pub quality: i32,
+    pub stat: f64,
+    /// `None` if the equipment has no perk
+    pub perk: Option<EquipmentPerk>,
+    /// `corresponding_set_item_id`, the other half of the set
+    pub set_item: Option<String>,
+    pub set_item_skill: Option<String>,
+    pub pvp_set_item_skill: Option<String>,
+}
+
+/// Bird, pig and equipment stats of an archive, everything is looked up by name id
+#[derive(Debug, Clone, Default)]
+pub struct StatCalculator {
+    birds: HashMap<String, BirdData>,
+    pigs: HashMap<String, PigData>,
+    power_level_weights: HashMap<String, f32>,
+    equipment: HashMap<String, EquipmentData>,
+}
+
+impl StatCalculator {
+    pub fn new(archive: &BalancingDataArchive) -> Result<Self, DecodeError> {
+        let birds = decode_if_present::<BirdBalancingData>(archive, BalancingDataTypes::BirdBalancingData)?;
+        let pigs = decode_if_present::<PigBalancingData>(archive, BalancingDataTypes::PigBalancingData)?;
+        let power_levels = decode_if_present::<PigTypePowerLevelBalancingData>(
+            archive,
+            BalancingDataTypes::PigTypePowerLevelBalancingData,
+        )?;
+        let equipment =
+            decode_if_present::<EquipmentBalancingData>(archive, BalancingDataTypes::EquipmentBalancingData)?;
+
+        Ok(Self {
+            birds: birds
+                .bird_data
+                .into_iter()
+                .map(|bird| (bird.name_id().to_string(), bird))
+                .collect(),
+            pigs: pigs
+                .pig_data
+                .into_iter()
+                .map(|pig| (pig.name_id().to_string(), pig))
+                .collect(),
+            power_level_weights: power_levels
+                .pig_type_power_level_data
+                .into_iter()
+                .map(|power_level| (power_level.name_id().to_string(), power_level.power_level_weight()))
+                .collect(),
+            equipment: equipment
+                .equipment_data
+                .into_iter()
+                .map(|equipment| (equipment.name_id().to_string(), equipment))
+                .collect(),
+        })
+    }
+
+    pub fn kind(&self, name_id: &str) -> Option<CharacterKind> {
+        if self.birds.contains_key(name_id) {
+            Some(CharacterKind::Bird)
+        } else if self.pigs.contains_key(name_id) {
+            Some(CharacterKind::Pig)
+        } else {
+            None
+        }
+    }
+
+    pub fn birds(&self) -> impl Iterator<Item = &str> {
+        self.birds.keys().map(String::as_str)
+    }
+
+    pub fn pigs(&self) -> impl Iterator<Item = &str> {
+        self.pigs.keys().map(String::as_str)
+    }
+
+    pub fn equipment(&self) -> impl Iterator<Item = &str> {
+        self.equipment.keys().map(String::as_str)
+    }
+
+    /// `None` if no bird or pig has the name id, birds win if both do
+    pub fn stats(&self, name_id: &str, level: i32) -> Option<CharacterStats> {
+        if let Some(bird) = self.birds.get(name_id) {
+            return Some(CharacterStats {
+                name_id: name_id.to_string(),
+                kind: CharacterKind::Bird,
+                level,
+                health: at_level(bird.base_health(), bird.per_level_health(), level),
+                attack: at_level(bird.base_attack(), bird.per_level_attack(), level),
+                pig_strength: None,
+                size_scale: None,
+                power_level: None,
+            });
+        }
+
+        let pig = self.pigs.get(name_id)?;
+        let weight = self.power_level_weights.get(name_id).copied().unwrap_or(1.0);
+
+        Some(CharacterStats {
+            name_id: name_id.to_string(),
+            kind: CharacterKind::Pig,
+            level,
+            health: at_level(pig.base_health(), pig.per_level_health(), level),
+            attack: at_level(pig.base_attack(), pig.per_level_attack(), level),
+            pig_strength: Some(pig.pig_strength()),
+            size_scale: Some(pig.size_scale()),
+            power_level: Some(pig.pig_strength() as f32 * weight),
+        })
+    }
+
+    pub fn stats_range(&self, name_id: &str, levels: RangeInclusive<i32>) -> Option<Vec<CharacterStats>> {
+        self.kind(name_id)?;
+
+        levels.map(|level| self.stats(name_id, level)).collect()
+    }
+
+    /// `None` if no equipment has the name id, quality starts at 1
+    pub fn equipment_stats(&self, name_id: &str, level: i32, quality: i32) -> Option<EquipmentStats> {
+        let equipment = self.equipment.get(name_id)?;
+
+        let mut stat = at_level(equipment.base_stat(), equipment.stat_per_level(), level) as f64;
+        if quality > 1 {
+            stat += equipment.stat_per_quality() as f64 * (quality - 1) as f64;
+        }
+
+        let percent = usize::try_from(quality - 1)
+            .ok()
+            .and_then(|index| equipment.stat_per_quality_percent.get(index))
+            .copied()
+            .unwrap_or_default();
+        stat += stat * percent as f64 / 100.0;
+
+        Some(EquipmentStats {
+            name_id: name_id.to_string(),
+            item_type: equipment.item_type(),
+            level,
+            quality,
+            stat,
+            perk: equipment.perk.as_ref().filter(|perk| perk.r#type() != PerkType::None).cloned(),
+            set_item: non_empty(&equipment.corresponding_set_item_id),
+            set_item_skill: non_empty(&equipment.set_item_skill),
+            pvp_set_item_skill: non_empty(&equipment.pvp_set_item_skill),
+        })
+    }
+}
+
+impl BalancingDataArchive {
+    pub fn stat_calculator(&self) -> Result<StatCalculator, DecodeError> {
+        StatCalculator::new(self)
+    }
+}
+
+//archives without a container just have nothing of it
+fn decode_if_present<T>(archive: &BalancingDataArchive, key: BalancingDataTypes) -> Result<T, DecodeError>
+where
+    T: prost::Message + Default,
+{
+    match archive.get_data_key(&key.to_string()) {
+        Some(_) => archive.get_data_enum_decoded(key),
+        None => Ok(T::default()),
+    }
+}
+
+fn non_empty(value: &Option<String>) -> Option<String> {
+    value.clone().filter(|value| !value.is_empty())
+}
+
+//i64 so huge per level values at silly levels don't overflow
+fn at_level(base: i32, per_level: i32, level: i32) -> i64 {
+    base as i64 + per_level as i64 * (level as i64 - 1)
+}
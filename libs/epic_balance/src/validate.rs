@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use prost::DecodeError;
+
+use crate::bulk::map_all;
+use crate::{BalancingDataArchive, BalancingDataTypes, IdLocation, NameIdIndex};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+    /// A field meant to hold an id names one no container defines
+    DanglingReference { id: String, location: IdLocation },
+    /// The same `name_id` is defined by more than one entry of a container
+    DuplicateNameId {
+        id: String,
+        data_type: BalancingDataTypes,
+        paths: Vec<String>,
+    },
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationIssue::DanglingReference { id, location } => {
+                write!(f, "{}: references missing id {:?}", location, id)
+            }
+            ValidationIssue::DuplicateNameId { id, data_type, paths } => {
+                write!(f, "{}: name_id {:?} is defined more than once ({})", data_type, id, paths.join(", "))
+            }
+        }
+    }
+}
+
+impl NameIdIndex {
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues: Vec<ValidationIssue> = self
+            .unresolved()
+            .iter()
+            .map(|(id, location)| ValidationIssue::DanglingReference {
+                id: id.clone(),
+                location: location.clone(),
+            })
+            .collect();
+
+        //ids are checked on the rayon pool with the parallel feature, like the containers are scanned
+        let ids: Vec<&str> = self.ids().collect();
+        let mut duplicates: Vec<ValidationIssue> = map_all(ids, |id| self.duplicates(id)).into_iter().flatten().collect();
+
+        //the index is a hash map, sort so the report is the same every run
+        duplicates.sort_by_cached_key(|issue| issue.to_string());
+        issues.extend(duplicates);
+
+        issues
+    }
+
+    //entries of the same container sharing `id`
+    fn duplicates(&self, id: &str) -> Vec<ValidationIssue> {
+        let mut per_container: HashMap<BalancingDataTypes, Vec<String>> = HashMap::new();
+        for definition in self.definitions(id) {
+            per_container
+                .entry(definition.data_type)
+                .or_default()
+                .push(definition.path.clone());
+        }
+
+        per_container
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(data_type, paths)| ValidationIssue::DuplicateNameId {
+                id: id.to_string(),
+                data_type,
+                paths,
+            })
+            .collect()
+    }
+}
+
+impl BalancingDataArchive {
+    /// Checks every reference of the archive against the ids it defines, see [`NameIdIndex`] for what counts as one
+    pub fn validate(&self) -> Result<Vec<ValidationIssue>, DecodeError> {
+        Ok(NameIdIndex::new(self)?.validate())
+    }
+}
@@ -0,0 +1,126 @@
+#![cfg(all(feature = "json", feature = "ron"))]
+
+use epic_balance::proto::basic_shop_offer_balancing_data::BasicShopOfferData;
+use epic_balance::proto::splash_screen_balancing_data::SplashScreenData;
+use epic_balance::proto::{BasicShopOfferBalancingData, SplashScreenBalancingData};
+use epic_balance::{BalancingDataArchive, BalancingDataTypes, LazyBalancingDataArchive, DATE_FIELDS};
+use serde_json::json;
+
+const SPLASH: BalancingDataTypes = BalancingDataTypes::SplashScreenBalancingData;
+const SHOP: BalancingDataTypes = BalancingDataTypes::PremiumShopOfferBalancingData;
+
+fn splash_screens() -> SplashScreenBalancingData {
+    SplashScreenBalancingData {
+        splash_screen_data: vec![SplashScreenData {
+            name_id: Some("splash_1".to_string()),
+            start_timestamp: Some(1462708800),
+            end_timestamp: Some(1462795200),
+        }],
+    }
+}
+
+fn offers() -> BasicShopOfferBalancingData {
+    BasicShopOfferBalancingData {
+        basic_shop_offer_data: vec![BasicShopOfferData {
+            name_id: Some("offer_1".to_string()),
+            start_date: Some(1462708800),
+            ..Default::default()
+        }],
+    }
+}
+
+fn archive() -> BalancingDataArchive {
+    let mut archive = BalancingDataArchive::default();
+    archive.set_data_enum(SPLASH, splash_screens()).unwrap();
+    archive.set_data_enum(SHOP, offers()).unwrap();
+    archive.set_readable_dates(true);
+    archive
+}
+
+fn decoded(archive: &BalancingDataArchive) -> (SplashScreenBalancingData, BasicShopOfferBalancingData) {
+    (archive.get_data_enum_decoded(SPLASH).unwrap(), archive.get_data_enum_decoded(SHOP).unwrap())
+}
+
+#[test]
+fn dates_are_only_readable_when_asked_for() {
+    let mut archive = archive();
+    archive.set_readable_dates(false);
+    assert!(archive.get_data_any_json(SPLASH).unwrap().contains("\"startTimestamp\": 1462708800"));
+
+    archive.set_readable_dates(true);
+    let json = archive.get_data_any_json(SPLASH).unwrap();
+    assert!(json.contains("\"startTimestamp\": \"2016-05-08T12:00:00Z\""));
+    assert!(json.contains("\"endTimestamp\": \"2016-05-09T12:00:00Z\""));
+}
+
+#[test]
+fn json_round_trip() {
+    let mut archive = archive();
+    for key in [SPLASH, SHOP] {
+        let json = archive.get_data_any_json(key).unwrap();
+        assert!(json.contains("2016-05-08T12:00:00Z"));
+        archive.set_data_any_json(key, &json).unwrap();
+    }
+
+    assert_eq!(decoded(&archive), (splash_screens(), offers()));
+}
+
+#[test]
+fn ron_round_trip() {
+    let mut archive = archive();
+    for key in [SPLASH, SHOP] {
+        let ron = archive.get_data_any_ron(key).unwrap();
+        assert!(ron.contains("2016-05-08T12:00:00Z"));
+        archive.set_data_any_ron(key, &ron).unwrap();
+    }
+
+    assert_eq!(decoded(&archive), (splash_screens(), offers()));
+}
+
+#[test]
+fn proto_names_take_dates_too() {
+    let mut archive = archive();
+    let json = r#"{
+        "splash_screen_data": [
+            { "name_id": "splash_1", "start_timestamp": "2016-05-08T14:00:00+02:00", "end_timestamp": 1462795200 }
+        ]
+    }"#;
+    archive.set_data_any_json(SPLASH, json).unwrap();
+
+    assert_eq!(decoded(&archive).0, splash_screens());
+}
+
+#[test]
+fn lazy_archives_write_the_same_dates() {
+    let archive = archive();
+    let mut buf = Vec::new();
+    archive.write(&mut buf).unwrap();
+
+    let mut lazy = LazyBalancingDataArchive::new(buf).unwrap();
+    lazy.set_readable_dates(true);
+    for key in [SPLASH, SHOP] {
+        assert_eq!(lazy.get_data_any_json(key).unwrap(), archive.get_data_any_json(key).unwrap());
+        assert_eq!(lazy.get_data_any_ron(key).unwrap(), archive.get_data_any_ron(key).unwrap());
+    }
+    assert!(lazy.to_archive().unwrap().readable_dates());
+}
+
+#[test]
+fn schemas_take_both_forms() {
+    let schema = SPLASH.json_schema();
+    let splash = &schema["$defs"]["abepic.balancing.SplashScreenBalancingData.SplashScreenData"]["properties"];
+    assert_eq!(
+        splash["startTimestamp"]["anyOf"],
+        json!([{ "type": "integer", "minimum": 0 }, { "type": "string", "format": "date-time" }])
+    );
+    assert_eq!(splash["start_timestamp"]["anyOf"], splash["startTimestamp"]["anyOf"]);
+    assert_eq!(splash["nameId"].get("anyOf"), None);
+
+    //EventPopupBalancingData isn't a container type, nothing refers to it
+    let schema = BalancingDataArchive::json_schema();
+    let in_schema: Vec<_> = DATE_FIELDS.iter().filter(|(message, _)| !schema["$defs"][message].is_null()).collect();
+    assert_eq!(in_schema.len(), DATE_FIELDS.len() - 2);
+    for (message, field) in in_schema {
+        assert!(schema["$defs"][message]["properties"][field]["anyOf"].is_array(), "{}.{}", message, field);
+    }
+}
@@ -0,0 +1,43 @@
+use epic_balance::proto::pig_balancing_data::PigData;
+use epic_balance::proto::skill_balancing_data::SkillData;
+use epic_balance::proto::{PigBalancingData, SkillBalancingData};
+use epic_balance::{BalancingDataArchive, BalancingDataTypes};
+
+fn archive() -> BalancingDataArchive {
+    let mut archive = BalancingDataArchive::default();
+
+    let skills = SkillBalancingData {
+        skill_data: vec![SkillData {
+            name_id: Some("skill_a".to_string()),
+            //a localization key that happens to be an id
+            loca_id: Some("pig_1".to_string()),
+            ..Default::default()
+        }],
+    };
+    archive.set_data_enum(BalancingDataTypes::SkillBalancingData, skills).unwrap();
+
+    let pigs = PigBalancingData {
+        pig_data: vec![PigData {
+            name_id: Some("pig_1".to_string()),
+            skill_name_ids: vec!["skill_a".to_string()],
+            ..Default::default()
+        }],
+    };
+    archive.set_data_enum(BalancingDataTypes::PigBalancingData, pigs).unwrap();
+
+    archive
+}
+
+#[test]
+fn references_only_come_from_id_fields() {
+    let index = archive().name_id_index().unwrap();
+
+    let references = index.references("skill_a");
+    assert_eq!(references.len(), 1);
+    assert_eq!(references[0].path, "pigData[0].skillNameIds[0]");
+
+    assert!(index.references("pig_1").is_empty());
+    let possible = index.possible_references("pig_1");
+    assert_eq!(possible.len(), 1);
+    assert_eq!(possible[0].path, "skillData[0].locaId");
+}
@@ -0,0 +1,122 @@
+use std::str::FromStr;
+
+use epic_balance::proto::SkillBalancingData;
+use epic_balance::{BalancingDataArchive, BalancingDataTypes};
+
+fn record(field: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![(field << 3) | 2, body.len() as u8];
+    out.extend_from_slice(body);
+    out
+}
+
+//map entry with the value written before the key, prost always writes the key first
+fn entry(key: &str, value: &[u8]) -> Vec<u8> {
+    record(1, &[record(2, value), record(1, key.as_bytes())].concat())
+}
+
+//skill with its fields out of order, an explicit default and a map<string, float> entry with its value first
+fn skill_a() -> Vec<u8> {
+    let mut skill = record(3, b"loca_skill");
+    skill.extend(record(1, b"skill_a"));
+    skill.extend([0x30, 0x00]); //effect_duration = 0
+    skill.extend(record(4, &[[0x15, 0x00, 0x00, 0x80, 0x3f].as_slice(), &record(1, b"damage")].concat()));
+    record(1, &skill)
+}
+
+fn skill_records() -> Vec<u8> {
+    [skill_a(), record(1, &record(1, b"skill_b"))].concat()
+}
+
+//a container laid out the way prost never would: version first and values before keys
+fn fixture() -> Vec<u8> {
+    let mut raw = record(2, b"3.0.1");
+    raw.extend(entry("ABH.Shared.BalancingData.SkillBalancingData", &skill_records()));
+    raw.extend(entry("ABH.Shared.BalancingData.CustomMessageBalancingData", b""));
+    raw
+}
+
+fn reencode_every_entry(archive: &mut BalancingDataArchive) {
+    for key in archive.get_balaning_data_keys() {
+        let data = archive.get_data_any(BalancingDataTypes::from_str(&key).unwrap()).unwrap();
+        archive.set_data_any(&data).unwrap();
+    }
+}
+
+#[test]
+fn untouched_container_is_byte_identical() {
+    let raw = fixture();
+
+    let mut archive = BalancingDataArchive::new_lossless(raw.as_slice()).unwrap();
+    reencode_every_entry(&mut archive);
+
+    let mut out = Vec::new();
+    archive.write(&mut out).unwrap();
+    assert_eq!(out, raw);
+
+    //without lossless mode the same steps give prost's layout, otherwise this test proves nothing
+    let mut plain = BalancingDataArchive::new(raw.as_slice()).unwrap();
+    reencode_every_entry(&mut plain);
+    let mut plain_out = Vec::new();
+    plain.write(&mut plain_out).unwrap();
+    assert_ne!(plain_out, raw);
+}
+
+#[test]
+fn untouched_gzipped_container_is_byte_identical() {
+    let mut gzipped = Vec::new();
+    BalancingDataArchive::new_lossless(fixture().as_slice())
+        .unwrap()
+        .write_gzipped(&mut gzipped)
+        .unwrap();
+
+    let mut archive = BalancingDataArchive::new_gzipped_lossless(gzipped.as_slice()).unwrap();
+    reencode_every_entry(&mut archive);
+
+    let mut out = Vec::new();
+    archive.write_gzipped(&mut out).unwrap();
+    assert_eq!(out, gzipped);
+}
+
+#[test]
+fn touched_entry_leaves_the_others_alone() {
+    let raw = fixture();
+    let version = record(2, b"3.0.1");
+    let untouched = entry("ABH.Shared.BalancingData.CustomMessageBalancingData", b"");
+
+    let mut archive = BalancingDataArchive::new_lossless(raw.as_slice()).unwrap();
+    let mut skills: SkillBalancingData = archive
+        .get_data_enum_decoded(BalancingDataTypes::SkillBalancingData)
+        .unwrap();
+    skills.skill_data[1].loca_id = Some("loca_skill_b".to_string());
+    archive.set_data_enum(BalancingDataTypes::SkillBalancingData, skills).unwrap();
+
+    //the untouched skill keeps its bytes at its offset, the changed one is in the place of the old one
+    let encoded = archive.get_data_key(&BalancingDataTypes::SkillBalancingData.to_string()).unwrap();
+    let skill_a = skill_a();
+    assert_eq!(encoded[..skill_a.len()], skill_a[..]);
+    assert_ne!(encoded[skill_a.len()..], skill_records()[skill_a.len()..]);
+
+    //same for the container, the version stays in front and the other entry behind the changed one
+    let mut out = Vec::new();
+    archive.write(&mut out).unwrap();
+    assert_ne!(out, raw);
+    assert_eq!(out[..version.len()], version[..]);
+    assert_eq!(out[out.len() - untouched.len()..], untouched[..]);
+    //the changed entry itself is new, prost writes the key first
+    let changed = record(1, &[record(1, b"ABH.Shared.BalancingData.SkillBalancingData"), record(2, encoded)].concat());
+    assert_eq!(out[version.len()..out.len() - untouched.len()], changed[..]);
+}
+
+#[test]
+fn reordered_entries_keep_their_new_order() {
+    let mut archive = BalancingDataArchive::new_lossless(fixture().as_slice()).unwrap();
+    let mut skills: SkillBalancingData = archive
+        .get_data_enum_decoded(BalancingDataTypes::SkillBalancingData)
+        .unwrap();
+    skills.skill_data.reverse();
+    archive.set_data_enum(BalancingDataTypes::SkillBalancingData, skills).unwrap();
+
+    //both skills are untouched, so both keep their bytes, in the order they have now
+    let encoded = archive.get_data_key(&BalancingDataTypes::SkillBalancingData.to_string()).unwrap();
+    assert_eq!(encoded, &[record(1, &record(1, b"skill_b")), skill_a()].concat());
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "reflect")]
+
+use epic_balance::proto::battle_balancing_data::battle_data::ScoringStrategy;
+use epic_balance::proto::battle_balancing_data::BattleData;
+use epic_balance::proto::requirement::RequirementType;
+use epic_balance::proto::{BattleBalancingData, Requirement};
+use epic_balance::{BalancingDataArchive, BalancingDataTypes};
+
+epic_wire::include_descriptors!(fn descriptors);
+
+const KEY: &str = "ABH.Shared.BalancingData.ChronicleCaveBattleBalancingData";
+
+#[test]
+fn container_uses_the_registry_message() {
+    let mut archive = BalancingDataArchive::default();
+    let battles = BattleBalancingData {
+        battle_data: vec![BattleData {
+            name_id: Some("cave_battle".to_string()),
+            ..Default::default()
+        }],
+    };
+    archive.set_data_enum(BalancingDataTypes::ChronicleCaveBattleBalancingData, battles.clone()).unwrap();
+
+    //there is no ChronicleCaveBattleBalancingData message, the container is a BattleBalancingData
+    let value = archive.get_data_key_reflect(KEY, descriptors(), None).unwrap();
+    assert_eq!(value["battleData"][0]["nameId"], "cave_battle");
+
+    let mut copy = BalancingDataArchive::default();
+    copy.set_data_key_reflect(KEY, descriptors(), None, &value).unwrap();
+    let decoded: BattleBalancingData = copy
+        .get_data_enum_decoded(BalancingDataTypes::ChronicleCaveBattleBalancingData)
+        .unwrap();
+    assert_eq!(decoded, battles);
+}
+
+#[test]
+fn unknown_container_uses_the_last_segment() {
+    let mut archive = BalancingDataArchive::default();
+    archive.set_data_key_raw("ABH.Shared.BalancingData.Future.SkillBalancingData", Vec::new());
+
+    let value = archive
+        .get_data_key_reflect("ABH.Shared.BalancingData.Future.SkillBalancingData", descriptors(), None)
+        .unwrap();
+    assert!(value.is_object());
+}
+
+#[test]
+fn reflected_json_matches_the_typed_json() {
+    let mut archive = BalancingDataArchive::default();
+    let battles = BattleBalancingData {
+        battle_data: vec![BattleData {
+            name_id: Some("battle_000".to_string()),
+            battle_participants_ids: vec!["pig_a".to_string(), "pig_b".to_string()],
+            loot_table_wheel: [("wheel_1".to_string(), 1)].into_iter().collect(),
+            battle_requirements: vec![Requirement {
+                requirement_type: Some(RequirementType::HaveItem as i32),
+                name_id: Some("key".to_string()),
+                value: Some(2.5),
+            }],
+            scoring_strategy: Some(ScoringStrategy::Pvp as i32),
+            additional_attack_in_percent: Some(0.25),
+            loot_table_wheel_after_wave: [(3, "wheel_3".to_string())].into_iter().collect(),
+            apply_power_level_balancing: Some(true),
+            ..Default::default()
+        }],
+    };
+    archive.set_data_enum(BalancingDataTypes::BattleBalancingData, battles).unwrap();
+
+    let key = BalancingDataTypes::BattleBalancingData.to_string();
+    let reflected = archive.get_data_key_reflect(&key, descriptors(), None).unwrap();
+    let typed: serde_json::Value =
+        serde_json::from_str(&archive.get_data_any_json(BalancingDataTypes::BattleBalancingData).unwrap()).unwrap();
+    assert_eq!(reflected, typed);
+}
@@ -0,0 +1,156 @@
+#![cfg(feature = "reflect")]
+
+use epic_balance::proto::basic_shop_offer_balancing_data::BasicShopOfferData;
+use epic_balance::proto::BasicShopOfferBalancingData;
+use epic_balance::{BalancingDataArchive, BalancingDataTypes, SchemaChange, SchemaRevision};
+use epic_wire::Descriptors;
+use prost::Message;
+use prost_types::{DescriptorProto, FileDescriptorSet};
+use serde_json::json;
+
+const KEY: BalancingDataTypes = BalancingDataTypes::PremiumShopOfferBalancingData;
+
+const CHANGES: &[SchemaChange] = &[
+    SchemaChange::Renamed {
+        message: "BasicShopOfferData",
+        from: "popup_loca",
+        to: "obsolete_popup_loca",
+    },
+    SchemaChange::Renamed {
+        message: "BasicShopOfferData",
+        from: "discount_start_date",
+        to: "obsolete_discount_start_date",
+    },
+    SchemaChange::Added { message: "BasicShopOfferData", field: "start_date" },
+];
+
+fn find_message<'a>(messages: &'a mut [DescriptorProto], name: &str) -> Option<&'a mut DescriptorProto> {
+    for message in messages {
+        if message.name() == name {
+            return Some(message);
+        }
+        if let Some(nested) = find_message(&mut message.nested_type, name) {
+            return Some(nested);
+        }
+    }
+    None
+}
+
+//made up from the current one, the fields that got renamed have their old names and start_date doesn't exist yet
+fn legacy_revision() -> SchemaRevision {
+    let mut set = FileDescriptorSet::decode(&include_bytes!(concat!(env!("OUT_DIR"), "/proto_descriptor.bin"))[..]).unwrap();
+    let offer = find_message(&mut set.file[0].message_type, "BasicShopOfferData").unwrap();
+
+    offer.field.retain(|field| field.name() != "start_date");
+    for field in &mut offer.field {
+        if let Some(old) = field.name().strip_prefix("obsolete_").filter(|old| *old == "popup_loca" || *old == "discount_start_date") {
+            let old = old.to_string();
+            field.json_name = None;
+            field.name = Some(old);
+        }
+    }
+
+    let descriptors = Descriptors::decode(&set.encode_to_vec()).unwrap();
+    SchemaRevision::new("legacy", "1.0.0", descriptors, &[])
+}
+
+fn current_revision() -> SchemaRevision {
+    let descriptors = SchemaRevision::current().descriptors().clone();
+    SchemaRevision::new("next", "3.0.0", descriptors, CHANGES)
+}
+
+fn offers(offer: BasicShopOfferData) -> BasicShopOfferBalancingData {
+    BasicShopOfferBalancingData {
+        basic_shop_offer_data: vec![offer],
+    }
+}
+
+fn legacy_offer() -> BasicShopOfferData {
+    BasicShopOfferData {
+        name_id: Some("offer_1".to_string()),
+        obsolete_popup_loca: Some("popup_offer_1".to_string()),
+        obsolete_discount_start_date: Some(1462708800),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn revision_comes_from_the_version() {
+    assert!(SchemaRevision::for_version("3.0.1").is_current());
+    //new archives have no version yet
+    assert!(BalancingDataArchive::default().schema_revision().is_current());
+}
+
+#[test]
+fn unparsable_versions_get_the_current_revision() {
+    for version in ["2.x", "dev", "beta.3", " "] {
+        assert!(SchemaRevision::for_version(version).is_current(), "{}", version);
+    }
+
+    let mut archive = BalancingDataArchive::default();
+    archive.set_version("nightly");
+    assert!(archive.schema_revision().is_current());
+}
+
+#[test]
+fn upgrade_renames_fields() {
+    let (legacy, current) = (legacy_revision(), current_revision());
+    let message = KEY.full_message_name();
+    let value = json!({
+        "basicShopOfferData": [{ "nameId": "offer_1", "popupLoca": "popup_offer_1", "discountStartDate": 1462708800 }]
+    });
+    let buf = legacy.descriptors().encode_dynamic(message, &value).unwrap();
+
+    let upgraded = legacy.upgrade(&current, message, &buf).unwrap();
+    assert_eq!(BasicShopOfferBalancingData::decode(upgraded.as_slice()).unwrap(), offers(legacy_offer()));
+}
+
+#[test]
+fn downgrade_round_trip() {
+    let (legacy, current) = (legacy_revision(), current_revision());
+    let message = KEY.full_message_name();
+    let buf = offers(legacy_offer()).encode_to_vec();
+
+    let downgraded = current.downgrade(&legacy, message, &buf).unwrap();
+    let value = legacy.descriptors().decode_dynamic(message, &downgraded).unwrap();
+    assert_eq!(value["basicShopOfferData"][0]["popupLoca"], "popup_offer_1");
+    assert_eq!(value["basicShopOfferData"][0]["discountStartDate"], 1462708800);
+    assert_eq!(value["basicShopOfferData"][0].get("_unknown"), None);
+
+    let back = legacy.upgrade(&current, message, &downgraded).unwrap();
+    assert_eq!(back, buf);
+}
+
+#[test]
+fn downgrade_refuses_to_drop_fields() {
+    let (legacy, current) = (legacy_revision(), current_revision());
+    let offer = BasicShopOfferData {
+        start_date: Some(1462708800),
+        ..legacy_offer()
+    };
+
+    let err = current
+        .downgrade(&legacy, KEY.full_message_name(), &offers(offer).encode_to_vec())
+        .unwrap_err();
+    assert!(err.to_string().contains("start_date"), "{}", err);
+}
+
+#[test]
+fn convert_only_knows_listed_revisions() {
+    let legacy = legacy_revision();
+    let buf = offers(legacy_offer()).encode_to_vec();
+
+    assert!(SchemaRevision::current().convert(&legacy, KEY.full_message_name(), &buf).is_err());
+    assert_eq!(SchemaRevision::current().convert(SchemaRevision::current(), KEY.full_message_name(), &buf).unwrap(), buf);
+}
+
+#[test]
+fn archive_in_the_current_revision_is_not_converted() {
+    let mut archive = BalancingDataArchive::default();
+    archive.set_version("3.0.1");
+    archive.set_data_enum(KEY, offers(legacy_offer())).unwrap();
+
+    assert_eq!(archive.convert_schema(None, SchemaRevision::current()).unwrap(), 0);
+    let decoded: BasicShopOfferBalancingData = archive.get_data_enum_decoded(KEY).unwrap();
+    assert_eq!(decoded, offers(legacy_offer()));
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "json")]
+
+use epic_balance::proto::SkillBalancingData;
+use epic_balance::{BalancingDataArchive, BalancingDataTypes};
+use prost::Message;
+use serde_json::Value;
+
+fn record(field: u8, body: &[u8]) -> Vec<u8> {
+    let mut out = vec![(field << 3) | 2, body.len() as u8];
+    out.extend_from_slice(body);
+    out
+}
+
+//two skills, the second one with a field 99 the proto doesn't have
+fn skills() -> Vec<u8> {
+    let mut second = record(1, b"skill_b");
+    second.extend([0x98, 0x06, 0x07]);
+    [record(1, &record(1, b"skill_a")), record(1, &second)].concat()
+}
+
+#[test]
+fn unknown_fields_follow_their_entry() {
+    let key = BalancingDataTypes::SkillBalancingData;
+    let mut archive = BalancingDataArchive::default();
+    archive.set_data_key_raw(&key.to_string(), skills());
+
+    let mut json: Value = serde_json::from_str(&archive.get_data_any_json(key).unwrap()).unwrap();
+    assert_eq!(json["_unknown"][0]["path"], r#"1["skill_b"]"#);
+
+    //swap the entries and put a new one in front
+    let entries = json["skillData"].as_array_mut().unwrap();
+    entries.reverse();
+    entries.insert(0, serde_json::json!({ "nameId": "skill_new" }));
+
+    archive.set_data_any_json(key, &json.to_string()).unwrap();
+
+    let encoded = archive.get_data_key(&key.to_string()).unwrap();
+    let mut moved = record(1, b"skill_b");
+    moved.extend([0x98, 0x06, 0x07]);
+    assert!(encoded.windows(moved.len()).any(|window| window == moved));
+
+    let decoded: SkillBalancingData = archive.get_data_enum_decoded(key).unwrap();
+    let names = decoded.skill_data.iter().map(|skill| skill.name_id()).collect::<Vec<_>>();
+    assert_eq!(names, ["skill_new", "skill_b", "skill_a"]);
+}
+
+#[test]
+fn index_paths_still_load() {
+    let key = BalancingDataTypes::SkillBalancingData;
+    let mut archive = BalancingDataArchive::default();
+    archive.set_data_key_raw(&key.to_string(), skills());
+
+    let json = archive
+        .get_data_any_json(key)
+        .unwrap()
+        .replace(r#"1[\"skill_b\"]"#, "1[1]");
+    archive.set_data_any_json(key, &json).unwrap();
+
+    assert_eq!(archive.get_data_key(&key.to_string()).unwrap(), &skills());
+}
+
+#[test]
+fn typed_writes_keep_unknown_fields_raw_writes_dont() {
+    let key = BalancingDataTypes::SkillBalancingData;
+    let mut archive = BalancingDataArchive::default();
+    archive.set_data_key_raw(&key.to_string(), skills());
+
+    let mut decoded: SkillBalancingData = archive.get_data_enum_decoded(key).unwrap();
+    decoded.skill_data.reverse();
+
+    archive.set_data_enum(key, decoded.clone()).unwrap();
+    let encoded = archive.get_data_key(&key.to_string()).unwrap();
+    assert!(encoded.windows(3).any(|window| window == [0x98, 0x06, 0x07]));
+
+    //set_data_key stores what it's given
+    archive.set_data_key(&key.to_string(), decoded.clone()).unwrap();
+    assert_eq!(archive.get_data_key(&key.to_string()).unwrap(), &decoded.encode_to_vec());
+}
+
+#[test]
+fn container_unknown_fields_are_kept() {
+    let key = BalancingDataTypes::SkillBalancingData.to_string();
+    let mut entry = [record(1, key.as_bytes()), record(2, &skills())].concat();
+    let plain = [record(1, &entry), record(2, b"3.0.1")].concat();
+    assert!(BalancingDataArchive::new(plain.as_slice()).unwrap().get_unknown_fields().is_empty());
+
+    //an entry field and a container field the proto doesn't have
+    entry.extend(record(3, b"x"));
+    let raw = [record(1, &entry), record(2, b"3.0.1"), record(9, b"y")].concat();
+    let archive = BalancingDataArchive::new(raw.as_slice()).unwrap();
+    assert_eq!(archive.get_unknown_fields().len(), 2);
+
+    let mut out = Vec::new();
+    archive.write(&mut out).unwrap();
+    assert_eq!(out, raw);
+}
@@ -0,0 +1,136 @@
+use epic_balance::proto::battle_balancing_data::BattleData;
+use epic_balance::proto::custom_message_balancing_data::CustomMessageData;
+use epic_balance::proto::loot_table_balancing_data::LootTableData;
+use epic_balance::proto::pv_p_season_manager_balancing_data::PvPSeasonManagerData;
+use epic_balance::proto::requirement::RequirementType;
+use epic_balance::proto::{
+    BattleBalancingData, CustomMessageBalancingData, LootTableBalancingData, LootTableEntry,
+    PvPSeasonManagerBalancingData, Requirement,
+};
+use epic_balance::{BalancingDataArchive, BalancingDataTypes, ValidationIssue};
+
+fn requirement(requirement_type: RequirementType, name_id: &str) -> Requirement {
+    Requirement {
+        requirement_type: Some(requirement_type as i32),
+        name_id: Some(name_id.to_string()),
+        value: None,
+    }
+}
+
+fn loot_table(name_id: &str, entries: &[&str]) -> LootTableData {
+    LootTableData {
+        name_id: Some(name_id.to_string()),
+        loot_table_entries: entries
+            .iter()
+            .map(|entry| LootTableEntry {
+                name_id: Some(entry.to_string()),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    }
+}
+
+//a small container that is valid, but full of strings in id-like fields that aren't ids
+fn fixture() -> BalancingDataArchive {
+    let mut archive = BalancingDataArchive::default();
+
+    let loot = LootTableBalancingData {
+        loot_table_data: vec![
+            loot_table("loot_wheel", &["gold"]),
+            loot_table("loot_wave_2", &["gold"]),
+            loot_table("loot_league_1", &["gold"]),
+            loot_table("gold", &[]),
+        ],
+    };
+    archive.set_data_enum(BalancingDataTypes::LootTableBalancingData, loot).unwrap();
+
+    let battles = BattleBalancingData {
+        battle_data: vec![BattleData {
+            name_id: Some("battle_1".to_string()),
+            loot_table_wheel: [("loot_wheel".to_string(), 1)].into(),
+            loot_table_wheel_after_wave: [(2, "loot_wave_2".to_string())].into(),
+            battle_requirements: vec![
+                requirement(RequirementType::HaveItem, "gold"),
+                //tutorial tracks and levels aren't ids
+                requirement(RequirementType::TutorialCompleted, "tutorial_battle"),
+                requirement(RequirementType::Level, "3"),
+            ],
+            ..Default::default()
+        }],
+    };
+    archive.set_data_enum(BalancingDataTypes::BattleBalancingData, battles).unwrap();
+
+    let messages = CustomMessageBalancingData {
+        custom_message_data: vec![CustomMessageData {
+            name_id: Some("message_1".to_string()),
+            button_sprite_name_id: Some("button_green".to_string()),
+            ..Default::default()
+        }],
+    };
+    archive.set_data_enum(BalancingDataTypes::CustomMessageBalancingData, messages).unwrap();
+
+    let seasons = PvPSeasonManagerBalancingData {
+        pvp_season_manager_data: vec![PvPSeasonManagerData {
+            name_id: Some("season_1".to_string()),
+            pvp_reward_loot_tables_per_league: vec!["loot_league_1".to_string()],
+            ..Default::default()
+        }],
+    };
+    archive.set_data_enum(BalancingDataTypes::PvPSeasonManagerBalancingData, seasons).unwrap();
+
+    archive
+}
+
+fn dangling(issues: &[ValidationIssue]) -> Vec<(&str, &str)> {
+    issues
+        .iter()
+        .filter_map(|issue| match issue {
+            ValidationIssue::DanglingReference { id, location } => Some((id.as_str(), location.path.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn valid_container_has_no_issues() {
+    assert_eq!(fixture().validate().unwrap(), Vec::new());
+}
+
+#[test]
+fn loot_table_values_are_references() {
+    let index = fixture().name_id_index().unwrap();
+
+    assert_eq!(index.references("loot_wave_2")[0].path, "battleData[0].lootTableWheelAfterWave[2]");
+    assert_eq!(index.references("loot_league_1")[0].path, "pvpSeasonManagerData[0].pvpRewardLootTablesPerLeague[0]");
+    assert_eq!(index.references("loot_wheel")[0].path, "battleData[0].lootTableWheel[\"loot_wheel\"]");
+}
+
+#[test]
+fn missing_ids_are_reported() {
+    let mut archive = fixture();
+    let loot = LootTableBalancingData {
+        loot_table_data: vec![loot_table("gold", &[])],
+    };
+    archive.set_data_enum(BalancingDataTypes::LootTableBalancingData, loot).unwrap();
+
+    let mut battles: BattleBalancingData = archive
+        .get_data_enum_decoded(BalancingDataTypes::BattleBalancingData)
+        .unwrap();
+    battles.battle_data[0].battle_requirements[0].name_id = Some("silver".to_string());
+    archive.set_data_enum(BalancingDataTypes::BattleBalancingData, battles).unwrap();
+
+    //containers come in whatever order the archive keeps them in
+    let issues = archive.validate().unwrap();
+    let mut missing = dangling(&issues);
+    missing.sort();
+    assert_eq!(
+        missing,
+        [
+            ("loot_league_1", "pvpSeasonManagerData[0].pvpRewardLootTablesPerLeague[0]"),
+            ("loot_wave_2", "battleData[0].lootTableWheelAfterWave[2]"),
+            ("loot_wheel", "battleData[0].lootTableWheel[\"loot_wheel\"]"),
+            ("silver", "battleData[0].battleRequirements[0].nameId"),
+        ]
+    );
+}
@@ -1,8 +1,14 @@
 use crate::proto::{LocaleBalancingDataBase, SerializedLocalizedTexts};
+use epic_wire::UnknownFields;
 use prost::bytes::{Buf, BufMut};
 use prost::Message;
 use std::io::{Read, Write};
 
+#[cfg(any(feature = "json", feature = "ron"))]
+use epic_wire::{capture_unknown, WithUnknown};
+#[cfg(feature = "json")]
+use serde::de::DeserializeSeed;
+
 #[cfg(feature = "ron")]
 use ron::{
     extensions::Extensions,
@@ -15,14 +21,20 @@ pub mod proto {
     include!(concat!(env!("OUT_DIR"), "/abepic.locale.serde.rs"));
 }
 
+const LOCALE_MESSAGE: &str = "abepic.locale.SerializedLocalizedTexts";
+
+epic_wire::include_descriptors!(fn descriptors);
+
 pub struct LocaleDataContainer {
     locale: SerializedLocalizedTexts,
+    unknown: UnknownFields,
 }
 
 impl LocaleDataContainer {
     pub fn default() -> Self {
         Self {
             locale: SerializedLocalizedTexts::default(),
+            unknown: UnknownFields::default(),
         }
     }
 
@@ -37,12 +49,15 @@ impl LocaleDataContainer {
         Self::new(decompressed.as_slice())
     }
 
-    pub fn new<B>(buf: B) -> anyhow::Result<Self>
+    pub fn new<B>(mut buf: B) -> anyhow::Result<Self>
     where
         B: Buf,
     {
-        let locale = SerializedLocalizedTexts::decode(buf)?;
-        Ok(Self { locale })
+        let buf = buf.copy_to_bytes(buf.remaining());
+
+        let locale = SerializedLocalizedTexts::decode(buf.as_ref())?;
+        let unknown = UnknownFields::extract(descriptors(), LOCALE_MESSAGE, &buf)?;
+        Ok(Self { locale, unknown })
     }
 
     pub fn get_language_id(self) -> Option<String> {
@@ -77,38 +92,65 @@ impl LocaleDataContainer {
         Ok(())
     }
 
+    /// Sets the text or adds it if the key isn't there yet, new texts get the language subtype of the other ones
+    pub fn insert_translated_text(&mut self, key: &str, text: &str) {
+        let texts = &mut self.locale.texts;
+
+        match texts.iter_mut().find(|locale| locale.name_id.as_deref() == Some(key)) {
+            Some(locale) => locale.translated_text = Some(text.to_string()),
+            None => {
+                let subtype = texts.iter().find_map(|locale| locale.subtype.as_ref().cloned());
+                texts.push(LocaleBalancingDataBase {
+                    name_id: Some(key.to_string()),
+                    translated_text: Some(text.to_string()),
+                    subtype,
+                })
+            }
+        }
+    }
+
     #[cfg(feature = "json")]
     pub fn from_json(contents: &str) -> anyhow::Result<Self> {
-        let locale = serde_json::from_str::<SerializedLocalizedTexts>(contents)?;
-        Ok(Self { locale })
+        let mut deserializer = serde_json::Deserializer::from_str(contents);
+        let (locale, unknown) = capture_unknown::<SerializedLocalizedTexts>().deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(Self { locale, unknown })
     }
 
     #[cfg(feature = "json")]
     pub fn to_json_pretty(&self) -> serde_json::Result<String> {
-        serde_json::to_string_pretty::<SerializedLocalizedTexts>(&self.locale)
+        serde_json::to_string_pretty(&WithUnknown::new(&self.locale, &self.unknown))
     }
 
     #[cfg(feature = "json")]
     pub fn to_json(&self) -> serde_json::Result<String> {
-        serde_json::to_string::<SerializedLocalizedTexts>(&self.locale)
+        serde_json::to_string(&WithUnknown::new(&self.locale, &self.unknown))
+    }
+
+    /// JSON Schema of the json form of the locale
+    #[cfg(feature = "json")]
+    pub fn json_schema() -> serde_json::Value {
+        descriptors()
+            .json_schema(LOCALE_MESSAGE)
+            .expect("the locale message is in the descriptors")
     }
 
     #[cfg(feature = "ron")]
     pub fn from_ron(contents: &str) -> anyhow::Result<Self> {
         //todo: there is def a less stupid way of doing this
         let options = Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
-        let locale = options.from_str::<SerializedLocalizedTexts>(contents)?;
-        Ok(Self { locale })
+        let (locale, unknown) = options.from_str_seed(contents, capture_unknown::<SerializedLocalizedTexts>())?;
+        Ok(Self { locale, unknown })
     }
 
     #[cfg(feature = "ron")]
     pub fn to_ron(&self) -> ron::Result<String> {
-        ron::ser::to_string::<SerializedLocalizedTexts>(&self.locale)
+        ron::ser::to_string(&WithUnknown::new(&self.locale, &self.unknown))
     }
 
     #[cfg(feature = "ron")]
     pub fn to_ron_pretty(&self) -> ron::Result<String> {
-        ron::ser::to_string_pretty::<SerializedLocalizedTexts>(&self.locale, Default::default())
+        ron::ser::to_string_pretty(&WithUnknown::new(&self.locale, &self.unknown), Default::default())
     }
     
     #[cfg(feature = "csv")]
@@ -119,7 +161,10 @@ impl LocaleDataContainer {
             let record: LocaleBalancingDataBase = result?;
             locale.texts.push(record);
         }
-        Ok(Self { locale })
+        Ok(Self {
+            locale,
+            unknown: UnknownFields::default(),
+        })
     }
     
     #[cfg(feature = "csv")]
@@ -142,6 +187,20 @@ impl LocaleDataContainer {
     pub fn set_locale(&mut self, data: SerializedLocalizedTexts) {
         self.locale = data;
     }
+
+    pub fn get_unknown_fields(&self) -> &UnknownFields {
+        &self.unknown
+    }
+
+    pub fn set_unknown_fields(&mut self, unknown: UnknownFields) {
+        self.unknown = unknown;
+    }
+
+    fn encode_locale(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self
+            .unknown
+            .merge(descriptors(), LOCALE_MESSAGE, &self.locale.encode_to_vec())?)
+    }
     pub fn sort(&mut self) {
         self.locale.texts.sort_by(|a, b| a.name_id.cmp(&b.name_id));
     }
@@ -152,9 +211,7 @@ impl LocaleDataContainer {
     {
         let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
 
-        let mut buf = Vec::new();
-        self.locale.encode(&mut buf)?;
-        encoder.write_all(&buf)?;
+        encoder.write_all(&self.encode_locale()?)?;
 
         writer
             .write_all(&encoder.finish()?)
@@ -165,6 +222,8 @@ impl LocaleDataContainer {
     where
         W: BufMut + Write,
     {
-        self.locale.encode(writer).map_err(anyhow::Error::new)
+        writer
+            .write_all(&self.encode_locale()?)
+            .map_err(anyhow::Error::new)
     }
 }
@@ -0,0 +1,3 @@
+//game rules worked out from balancing data and player saves, everything here is a best guess of what the client does
+pub mod loot;
+pub mod requirements;
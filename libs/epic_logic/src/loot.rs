@@ -0,0 +1,396 @@
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, bail};
+
+use epic_balance::proto::loot_table_balancing_data::loot_table_data::LootTableType;
+use epic_balance::proto::loot_table_balancing_data::LootTableData;
+use epic_balance::proto::{LootTableBalancingData, LootTableEntry};
+use epic_balance::{BalancingDataArchive, BalancingDataTypes};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+
+//loot tables naming each other in a loop would never end
+const MAX_DEPTH: usize = 32;
+
+/// Anything a loot table hands out that isn't another loot table, at the level it drops with
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct LootItem {
+    pub name_id: String,
+    pub level: i32,
+}
+
+/// Items and how many of them dropped
+pub type Loot = BTreeMap<LootItem, u32>;
+
+/// Chance of every total amount of one item, amounts that can't happen are left out
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AmountDistribution(BTreeMap<u32, f64>);
+
+/// Every loot table of an archive by name id.
+///
+/// How a table is rolled, per [`LootTableType`]:
+/// - `PROBABILITY`: every entry drops on its own with its `probability` as the chance
+/// - `WEIGHTED` and `WHEEL`: exactly one entry drops, picked with `probability` as its weight
+/// - `INVENTORY`: every entry drops
+/// - `WHEEL_FORCED`: not supported, nothing in the table says which entry the wheel is forced to, rolling one is an error
+///
+/// Only entries with `level_min_incl <= level < level_max_excl` take part. `balancing.proto` declares both with
+/// `[default = 0]`, an entry without a window would never drop if 0 was a real upper bound, so a `level_max_excl` of 0
+/// has none. Negative ones are not supported and are an error.
+/// An entry drops `base_value` plus up to `span` more, every amount equally likely.
+/// Entries naming another loot table roll it that many times at `level + current_player_level_delta`,
+/// anything else is an item at that level
+#[derive(Debug, Clone, Default)]
+pub struct LootTables {
+    tables: HashMap<String, LootTableData>,
+}
+
+impl LootTables {
+    pub fn new(archive: &BalancingDataArchive) -> anyhow::Result<Self> {
+        let key = BalancingDataTypes::LootTableBalancingData;
+        if archive.get_data_key(&key.to_string()).is_none() {
+            return Ok(Self::default());
+        }
+
+        Ok(Self::from_data(archive.get_data_enum_decoded::<LootTableBalancingData>(key)?))
+    }
+
+    pub fn from_data(data: LootTableBalancingData) -> Self {
+        let tables = data
+            .loot_table_data
+            .into_iter()
+            .map(|table| (table.name_id().to_string(), table))
+            .collect();
+
+        Self { tables }
+    }
+
+    pub fn get(&self, name_id: &str) -> Option<&LootTableData> {
+        self.tables.get(name_id)
+    }
+
+    pub fn contains(&self, name_id: &str) -> bool {
+        self.tables.contains_key(name_id)
+    }
+
+    pub fn name_ids(&self) -> impl Iterator<Item = &str> {
+        self.tables.keys().map(String::as_str)
+    }
+
+    /// Rolls `name_id` once at the given player level
+    pub fn roll<R>(&self, name_id: &str, level: i32, rng: &mut R) -> anyhow::Result<Loot>
+    where
+        R: Rng + ?Sized,
+    {
+        let mut loot = Loot::new();
+        self.roll_into(name_id, level, rng, &mut loot, 0)?;
+        Ok(loot)
+    }
+
+    /// Rolls every table of a loot map (table or item name id -> amount), the way rewards and shop offers reference loot
+    pub fn roll_map<'a, I, R>(&self, loot_map: I, level: i32, rng: &mut R) -> anyhow::Result<Loot>
+    where
+        I: IntoIterator<Item = (&'a String, &'a i32)>,
+        R: Rng + ?Sized,
+    {
+        let mut loot = Loot::new();
+        for (name_id, amount) in loot_map {
+            for _ in 0..(*amount).max(0) {
+                self.drop_into(name_id, level, rng, &mut loot, 0)?;
+            }
+        }
+        Ok(loot)
+    }
+
+    fn roll_into<R>(&self, name_id: &str, level: i32, rng: &mut R, loot: &mut Loot, depth: usize) -> anyhow::Result<()>
+    where
+        R: Rng + ?Sized,
+    {
+        let table = self.table(name_id, depth)?;
+        let entries = entries_in_range(table, level)?;
+
+        let picked: Vec<&LootTableEntry> = match table.r#type() {
+            LootTableType::Probability => entries
+                .into_iter()
+                .filter(|entry| rng.gen_bool(chance(entry)))
+                .collect(),
+            LootTableType::Weighted | LootTableType::Wheel => {
+                //fails when nothing has a weight, then nothing drops
+                match WeightedIndex::new(entries.iter().map(|entry| weight(entry))) {
+                    Ok(index) => vec![entries[index.sample(rng)]],
+                    Err(_) => Vec::new(),
+                }
+            }
+            LootTableType::Inventory => entries,
+            LootTableType::WheelForced => bail!(wheel_forced(name_id)),
+        };
+
+        for entry in picked {
+            let amount = entry.base_value() + rng.gen_range(0..=entry.span().max(0));
+            for _ in 0..amount.max(0) {
+                self.drop_into(entry.name_id(), entry_level(entry, level), rng, loot, depth)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn drop_into<R>(&self, name_id: &str, level: i32, rng: &mut R, loot: &mut Loot, depth: usize) -> anyhow::Result<()>
+    where
+        R: Rng + ?Sized,
+    {
+        if self.contains(name_id) {
+            return self.roll_into(name_id, level, rng, loot, depth + 1);
+        }
+
+        let item = LootItem {
+            name_id: name_id.to_string(),
+            level,
+        };
+        *loot.entry(item).or_default() += 1;
+
+        Ok(())
+    }
+
+    /// Exact chance of every amount of every item one roll of `name_id` can give at the given player level
+    pub fn distribution(&self, name_id: &str, level: i32) -> anyhow::Result<BTreeMap<LootItem, AmountDistribution>> {
+        self.table_distribution(name_id, level, 0)
+    }
+
+    //every item is worked out on its own, the parts of a roll are either independent (convolution)
+    //or exclusive choices (mixture), both work the same on one item as on the whole loot
+    fn table_distribution(
+        &self,
+        name_id: &str,
+        level: i32,
+        depth: usize,
+    ) -> anyhow::Result<BTreeMap<LootItem, AmountDistribution>> {
+        let table = self.table(name_id, depth)?;
+        let entries = entries_in_range(table, level)?;
+        if table.r#type() == LootTableType::WheelForced {
+            bail!(wheel_forced(name_id));
+        }
+
+        let mut parts = Vec::with_capacity(entries.len());
+        for entry in &entries {
+            parts.push(self.entry_distribution(entry, level, depth)?);
+        }
+
+        Ok(match table.r#type() {
+            LootTableType::Probability => parts
+                .into_iter()
+                .zip(&entries)
+                .map(|(part, entry)| mix(&[(chance(entry), part)]))
+                .fold(BTreeMap::new(), combine),
+            LootTableType::Weighted | LootTableType::Wheel => {
+                let total: f64 = entries.iter().map(|entry| weight(entry)).sum();
+                if total <= 0.0 {
+                    return Ok(BTreeMap::new());
+                }
+
+                let weighted: Vec<_> = parts
+                    .into_iter()
+                    .zip(&entries)
+                    .map(|(part, entry)| (weight(entry) / total, part))
+                    .collect();
+                mix(&weighted)
+            }
+            LootTableType::Inventory => parts.into_iter().fold(BTreeMap::new(), combine),
+            LootTableType::WheelForced => unreachable!("checked before the entries are worked out"),
+        })
+    }
+
+    fn entry_distribution(
+        &self,
+        entry: &LootTableEntry,
+        level: i32,
+        depth: usize,
+    ) -> anyhow::Result<BTreeMap<LootItem, AmountDistribution>> {
+        let amounts = AmountDistribution::uniform(entry.base_value(), entry.span());
+        let level = entry_level(entry, level);
+
+        if !self.contains(entry.name_id()) {
+            let item = LootItem {
+                name_id: entry.name_id().to_string(),
+                level,
+            };
+            return Ok(BTreeMap::from([(item, amounts)]));
+        }
+
+        let single = self.table_distribution(entry.name_id(), level, depth + 1)?;
+        Ok(single
+            .into_iter()
+            .map(|(item, distribution)| (item, distribution.repeated(&amounts)))
+            .collect())
+    }
+
+    fn table(&self, name_id: &str, depth: usize) -> anyhow::Result<&LootTableData> {
+        if depth > MAX_DEPTH {
+            bail!("Loot table {} is nested more than {} levels deep, the tables probably name each other", name_id, MAX_DEPTH);
+        }
+
+        self.get(name_id).ok_or_else(|| anyhow!("No loot table named {}", name_id))
+    }
+}
+
+impl AmountDistribution {
+    fn point(amount: u32) -> Self {
+        Self(BTreeMap::from([(amount, 1.0)]))
+    }
+
+    //base_value..=base_value + span, negative amounts drop nothing
+    fn uniform(base_value: i32, span: i32) -> Self {
+        let span = span.max(0);
+        let chance = 1.0 / (span as f64 + 1.0);
+
+        let mut distribution = BTreeMap::new();
+        for amount in base_value..=base_value + span {
+            *distribution.entry(amount.max(0) as u32).or_default() += chance;
+        }
+
+        Self(distribution)
+    }
+
+    pub fn probability(&self, amount: u32) -> f64 {
+        self.0.get(&amount).copied().unwrap_or_default()
+    }
+
+    /// Chance of getting at least one
+    pub fn chance_of_any(&self) -> f64 {
+        1.0 - self.probability(0)
+    }
+
+    pub fn expected(&self) -> f64 {
+        self.0.iter().map(|(amount, chance)| *amount as f64 * chance).sum()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u32, f64)> + '_ {
+        self.0.iter().map(|(amount, chance)| (*amount, *chance))
+    }
+
+    //sum of two independent amounts
+    fn convolve(&self, other: &Self) -> Self {
+        let mut distribution = BTreeMap::new();
+        for (a, chance_a) in &self.0 {
+            for (b, chance_b) in &other.0 {
+                *distribution.entry(a + b).or_default() += chance_a * chance_b;
+            }
+        }
+
+        Self(distribution)
+    }
+
+    //sum of `times` independent copies, `times` itself being random
+    fn repeated(&self, times: &Self) -> Self {
+        let mut distribution = BTreeMap::new();
+        let mut sum = Self::point(0);
+        let mut done = 0;
+
+        for (count, chance) in &times.0 {
+            while done < *count {
+                sum = sum.convolve(self);
+                done += 1;
+            }
+
+            for (amount, sum_chance) in &sum.0 {
+                *distribution.entry(*amount).or_default() += chance * sum_chance;
+            }
+        }
+
+        Self(distribution)
+    }
+}
+
+//0 is the proto default and has no upper bound, see LootTables
+fn entries_in_range(table: &LootTableData, level: i32) -> anyhow::Result<Vec<&LootTableEntry>> {
+    let mut entries = Vec::new();
+    for entry in &table.loot_table_entries {
+        let max = entry.level_max_excl();
+        if max < 0 {
+            bail!(
+                "Loot table {} has an entry for {} with a level_max_excl of {}, negative level windows aren't supported",
+                table.name_id(),
+                entry.name_id(),
+                max
+            );
+        }
+
+        if level >= entry.level_min_incl() && (max == 0 || level < max) {
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+fn wheel_forced(name_id: &str) -> String {
+    format!("Loot table {} is a WHEEL_FORCED table, which entry those land on isn't part of the balancing data", name_id)
+}
+
+fn entry_level(entry: &LootTableEntry, level: i32) -> i32 {
+    level + entry.current_player_level_delta()
+}
+
+//NaN and infinity decode fine but can't be rolled with, they count as 0
+fn probability(entry: &LootTableEntry) -> f64 {
+    let probability = entry.probability() as f64;
+    if probability.is_finite() {
+        probability
+    } else {
+        0.0
+    }
+}
+
+fn chance(entry: &LootTableEntry) -> f64 {
+    probability(entry).clamp(0.0, 1.0)
+}
+
+fn weight(entry: &LootTableEntry) -> f64 {
+    probability(entry).max(0.0)
+}
+
+//items missing from one side are a sure 0 there
+fn combine(
+    mut a: BTreeMap<LootItem, AmountDistribution>,
+    b: BTreeMap<LootItem, AmountDistribution>,
+) -> BTreeMap<LootItem, AmountDistribution> {
+    for (item, distribution) in b {
+        let combined = match a.remove(&item) {
+            Some(existing) => existing.convolve(&distribution),
+            None => distribution,
+        };
+        a.insert(item, combined);
+    }
+
+    a
+}
+
+//picks one of the parts with the given chances, whatever chance is left over gives nothing
+fn mix(parts: &[(f64, BTreeMap<LootItem, AmountDistribution>)]) -> BTreeMap<LootItem, AmountDistribution> {
+    let mut mixed: BTreeMap<LootItem, AmountDistribution> = BTreeMap::new();
+
+    for (index, (_, part)) in parts.iter().enumerate() {
+        for item in part.keys() {
+            if mixed.contains_key(item) {
+                continue;
+            }
+
+            let mut distribution = BTreeMap::new();
+            for (chance, other) in &parts[index..] {
+                let other = other.get(item).cloned().unwrap_or_else(|| AmountDistribution::point(0));
+                for (amount, other_chance) in other.0 {
+                    *distribution.entry(amount).or_default() += chance * other_chance;
+                }
+            }
+
+            //parts before this one never had the item, neither does whatever chance no part covers
+            let seen: f64 = parts[..index].iter().map(|(chance, _)| chance).sum();
+            let rest = 1.0 - parts.iter().map(|(chance, _)| chance).sum::<f64>();
+            *distribution.entry(0).or_default() += seen + rest.max(0.0);
+
+            mixed.insert(item.clone(), AmountDistribution(distribution));
+        }
+    }
+
+    mixed
+}
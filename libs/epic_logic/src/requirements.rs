@@ -0,0 +1,394 @@
+use std::fmt::{Display, Formatter};
+
+use chrono::{DateTime, Datelike, Utc};
+use epic_balance::proto::requirement::RequirementType;
+use epic_balance::proto::Requirement;
+use epic_prefs::proto::prefs::hotspot_data::HotspotUnlockState;
+use epic_prefs::proto::prefs::{HotspotData, InventoryData, PlayerData};
+use epic_prefs::PlayerPrefsData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Passed,
+    Failed,
+    /// The requirement needs something the save doesn't have (balancing data, server state...)
+    Unsupported,
+}
+
+/// What a requirement came out as and why
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    pub requirement: Requirement,
+    pub outcome: Outcome,
+    pub reason: String,
+}
+
+/// Every requirement of a list, the game only lets a list through if all of them pass
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Evaluations(pub Vec<Evaluation>);
+
+/// Checks balancing data requirements against a player save at a point in time.
+///
+/// Item counts are summed over the player's inventory and every bird's inventory, items that don't stack count as 1.
+/// Timestamps in the save are unix seconds, weekdays go from 0 (sunday) to 6 like .NET's `DayOfWeek` and are taken in utc.
+/// Everything here is worked out from field names, requirements that can't be answered from the save alone are
+/// [`Outcome::Unsupported`]
+pub struct RequirementEvaluator<'a> {
+    player: &'a PlayerData,
+    now: DateTime<Utc>,
+}
+
+impl<'a> RequirementEvaluator<'a> {
+    pub fn new(prefs: &'a PlayerPrefsData, now: DateTime<Utc>) -> Self {
+        Self::from_data(prefs.get_data(), now)
+    }
+
+    pub fn from_data(player: &'a PlayerData, now: DateTime<Utc>) -> Self {
+        Self { player, now }
+    }
+
+    /// Evaluates at the current time
+    pub fn now(prefs: &'a PlayerPrefsData) -> Self {
+        Self::new(prefs, Utc::now())
+    }
+
+    pub fn evaluate_all<'r, I>(&self, requirements: I) -> Evaluations
+    where
+        I: IntoIterator<Item = &'r Requirement>,
+    {
+        Evaluations(requirements.into_iter().map(|requirement| self.evaluate(requirement)).collect())
+    }
+
+    pub fn evaluate(&self, requirement: &Requirement) -> Evaluation {
+        let (outcome, reason) = self.check(requirement);
+
+        Evaluation {
+            requirement: requirement.clone(),
+            outcome,
+            reason,
+        }
+    }
+
+    fn check(&self, requirement: &Requirement) -> (Outcome, String) {
+        let player = self.player;
+        let name_id = requirement.name_id();
+        let value = requirement.value();
+
+        match requirement.requirement_type() {
+            RequirementType::None => passed("no requirement".to_string()),
+            RequirementType::PayItem | RequirementType::HaveItem => {
+                let count = self.item_count(name_id);
+                let needed = amount(value);
+                compare(count >= needed, format!("has {} {}, needs {}", count, name_id, needed))
+            }
+            RequirementType::NotHaveItem => {
+                let count = self.item_count(name_id);
+                let limit = amount(value);
+                compare(count < limit, format!("has {} {}, needs less than {}", count, name_id, limit))
+            }
+            RequirementType::HaveLessThan => {
+                let count = self.item_count(name_id);
+                compare(count < value as i64, format!("has {} {}, needs less than {}", count, name_id, value))
+            }
+            RequirementType::HaveItemWithLevel | RequirementType::NotHaveItemWithLevel => {
+                let level = self.item_level(name_id);
+                let has = level.is_some_and(|level| level as f32 >= value);
+                let reason = match level {
+                    Some(level) => format!("has {} at level {}, level {} is needed", name_id, level, value),
+                    None => format!("doesn't have {}, level {} is needed", name_id, value),
+                };
+                compare(has == (requirement.requirement_type() == RequirementType::HaveItemWithLevel), reason)
+            }
+            RequirementType::HaveBird => {
+                let has = self.available_birds().any(|bird| bird.name_id() == name_id);
+                compare(has, format!("{} is {}", name_id, if has { "unlocked" } else { "not unlocked" }))
+            }
+            RequirementType::HaveBirdCount => {
+                let count = self.available_birds().count();
+                compare(count as f32 >= value, format!("has {} birds, needs {}", count, value))
+            }
+            RequirementType::Level => compare(
+                player.level() as f32 >= value,
+                format!("player level is {}, needs {}", player.level(), value),
+            ),
+            RequirementType::CooldownFinished => match player.current_cooldown_offers.get(name_id) {
+                None => passed(format!("{} has no cooldown running", name_id)),
+                Some(end) => match DateTime::<Utc>::try_from(*end) {
+                    Ok(end) => compare(self.now >= end, format!("cooldown of {} ends at {}", name_id, end.to_rfc3339())),
+                    Err(e) => unsupported(format!("cooldown of {} has an invalid date: {}", name_id, e)),
+                },
+            },
+            RequirementType::IsSpecificWeekday | RequirementType::IsNotSpecificWeekday => {
+                let weekday = self.now.weekday().num_days_from_sunday();
+                let matches = weekday as f32 == value;
+                compare(
+                    matches == (requirement.requirement_type() == RequirementType::IsSpecificWeekday),
+                    format!("weekday is {}, requirement is about {}", weekday, value),
+                )
+            }
+            RequirementType::HaveCurrentHotpsotState => match self.hotspot(name_id) {
+                Some(hotspot) => compare(
+                    hotspot.unlock_state() as i32 as f32 >= value,
+                    format!("{} is {}, needs {}", name_id, hotspot.unlock_state().as_str_name(), value),
+                ),
+                None => failed(format!("{} was never visited", name_id)),
+            },
+            RequirementType::HaveUnlockedHotpsot | RequirementType::NotHaveUnlockedHotpsot => {
+                let unlocked = self
+                    .hotspot(name_id)
+                    .is_some_and(|hotspot| hotspot.unlock_state() as i32 >= HotspotUnlockState::Active as i32);
+                compare(
+                    unlocked == (requirement.requirement_type() == RequirementType::HaveUnlockedHotpsot),
+                    format!("{} is {}", name_id, if unlocked { "unlocked" } else { "locked" }),
+                )
+            }
+            RequirementType::UseBirdInBattle | RequirementType::NotUseBirdInBattle => {
+                let used = player
+                    .selected_bird_indices
+                    .iter()
+                    .filter_map(|index| player.birds.get(usize::try_from(*index).ok()?))
+                    .any(|bird| bird.name_id() == name_id);
+                compare(
+                    used == (requirement.requirement_type() == RequirementType::UseBirdInBattle),
+                    format!("{} is {}", name_id, if used { "in the team" } else { "not in the team" }),
+                )
+            }
+            RequirementType::HaveClass | RequirementType::NotHaveClass => {
+                let has = self.class(name_id).is_some();
+                compare(
+                    has == (requirement.requirement_type() == RequirementType::HaveClass),
+                    format!("{} is {}", name_id, if has { "unlocked" } else { "not unlocked" }),
+                )
+            }
+            RequirementType::HaveMasteryFactor | RequirementType::NotHaveMasteryFactor => {
+                let level = self.class(name_id).map(|class| class.level()).unwrap_or_default();
+                compare(
+                    (level as f32 >= value) == (requirement.requirement_type() == RequirementType::HaveMasteryFactor),
+                    format!("{} has mastery {}, requirement is about {}", name_id, level, value),
+                )
+            }
+            RequirementType::IsConverted => compare(
+                player.is_user_converted(),
+                format!("player {} paid", if player.is_user_converted() { "has" } else { "has never" }),
+            ),
+            RequirementType::LostPvpBattle => compare(
+                player.lost_any_pvp_battle(),
+                format!("player {} lost a pvp battle", if player.lost_any_pvp_battle() { "has" } else { "has never" }),
+            ),
+            RequirementType::HaveCurrentChronicleCaveState => {
+                let floor = player
+                    .chronicle_cave
+                    .as_ref()
+                    .map(|cave| cave.current_floor_index())
+                    .unwrap_or_default();
+                compare(floor as f32 >= value, format!("chronicle cave is on floor {}, needs {}", floor, value))
+            }
+            RequirementType::TutorialCompleted => match player.tutorial_tracks.get(name_id) {
+                Some(step) => compare(*step as f32 >= value, format!("tutorial {} is at step {}, needs {}", name_id, step, value)),
+                None => failed(format!("tutorial {} was never started", name_id)),
+            },
+            RequirementType::TotalMoneySpent => compare(
+                player.total_dollars_spent() >= value,
+                format!("player spent {}, needs {}", player.total_dollars_spent(), value),
+            ),
+            RequirementType::LostUnresolvedHotspot => {
+                let lost = player.unresolved_hotspots_lost.get(name_id).copied().unwrap_or_default();
+                compare(lost as f32 >= value.max(1.0), format!("lost {} {} times", name_id, lost))
+            }
+            RequirementType::HighestLeagueReached => compare(
+                player.highest_finished_league() as f32 >= value,
+                format!("highest league is {}, needs {}", player.highest_finished_league(), value),
+            ),
+            RequirementType::TimeSinceLastPurchase => {
+                if player.time_stamp_of_last_purchase() == 0 {
+                    return passed("player never bought anything".to_string());
+                }
+
+                let since = self.now.timestamp() - player.time_stamp_of_last_purchase() as i64;
+                compare(since as f32 >= value, format!("last purchase was {}s ago, needs {}s", since, value))
+            }
+            RequirementType::DeclinedOffer => {
+                listed(&player.offers_ended_without_purchase, name_id, "ended without being bought")
+            }
+            RequirementType::AcceptedOffer => listed(&player.offers_purchased, name_id, "bought"),
+            RequirementType::EndedOffer => listed(&player.offers_ended, name_id, "ended"),
+            requirement_type @ (RequirementType::HavePassedCycleTime
+            | RequirementType::NotHavePassedCycleTime
+            | RequirementType::UsedFriends
+            | RequirementType::HaveAllUpgrades
+            | RequirementType::NotHaveAllUpgrades
+            | RequirementType::HaveEventCampaignHotspotState
+            | RequirementType::HaveTotalItemsInCollection
+            | RequirementType::HaveEventScore
+            | RequirementType::UnlockedAllClasses
+            | RequirementType::BirdMasteryFactorMinimum
+            | RequirementType::BirdMasteryFactorMaximum
+            | RequirementType::UnlockedAllSkins) => unsupported(format!(
+                "{} can't be checked from the save alone",
+                requirement_type.as_str_name()
+            )),
+        }
+    }
+
+    fn inventories(&self) -> impl Iterator<Item = &InventoryData> {
+        self.player
+            .inventory
+            .iter()
+            .chain(self.player.birds.iter().filter_map(|bird| bird.inventory.as_ref()))
+    }
+
+    //(level, value) of every item in every inventory with the name id
+    fn items<'s>(&'s self, name_id: &'s str) -> impl Iterator<Item = (i32, i32)> + 's {
+        self.inventories().flat_map(move |inventory| {
+            let basic = inventory
+                .story_items
+                .iter()
+                .chain(&inventory.player_stats)
+                .chain(&inventory.trophy_items)
+                .chain(&inventory.collection_components)
+                .map(|item| (item.name_id(), item.level(), item.value()));
+            let class = inventory.class_items.iter().map(|item| (item.name_id(), item.level(), item.value()));
+            let equipment = inventory
+                .main_hand_items
+                .iter()
+                .chain(&inventory.off_hand_items)
+                .map(|item| (item.name_id(), item.level(), item.value()));
+            let crafting = inventory
+                .crafting_resource_items
+                .iter()
+                .chain(&inventory.crafting_ingredient_items)
+                .map(|item| (item.name_id(), item.level(), item.value()));
+            let consumable = inventory.consumable_items.iter().map(|item| (item.name_id(), item.level(), item.value()));
+            let recipe = inventory.crafting_recipes_items.iter().map(|item| (item.name_id(), item.level(), item.value()));
+            let event = inventory.event_items.iter().map(|item| (item.name_id(), item.level(), item.value()));
+            let mastery = inventory.mastery_items.iter().map(|item| (item.name_id(), item.level(), item.value()));
+            let banner = inventory.banner_items.iter().map(|item| (item.name_id(), item.level(), item.value()));
+            let skin = inventory.skin_items.iter().map(|item| (item.name_id(), item.level(), item.value()));
+
+            basic
+                .chain(class)
+                .chain(equipment)
+                .chain(crafting)
+                .chain(consumable)
+                .chain(recipe)
+                .chain(event)
+                .chain(mastery)
+                .chain(banner)
+                .chain(skin)
+                .filter(move |(item, _, _)| *item == name_id)
+                .map(|(_, level, value)| (level, value))
+        })
+    }
+
+    fn item_count(&self, name_id: &str) -> i64 {
+        self.items(name_id).map(|(_, value)| value.max(1) as i64).sum()
+    }
+
+    fn item_level(&self, name_id: &str) -> Option<i32> {
+        self.items(name_id).map(|(level, _)| level).max()
+    }
+
+    fn class(&self, name_id: &str) -> Option<&epic_prefs::proto::prefs::ClassItemData> {
+        self.inventories()
+            .flat_map(|inventory| &inventory.class_items)
+            .find(|class| class.name_id() == name_id)
+    }
+
+    fn available_birds(&self) -> impl Iterator<Item = &epic_prefs::proto::prefs::BirdData> {
+        self.player.birds.iter().filter(|bird| !bird.is_unavaliable())
+    }
+
+    fn hotspot(&self, name_id: &str) -> Option<&HotspotData> {
+        let world = self.player.world.as_ref()?;
+
+        world
+            .hot_spot_instances
+            .iter()
+            .chain(&world.current_hot_spot_instance)
+            .chain(&world.daily_hotspot_instance)
+            .find(|hotspot| hotspot.name_id() == name_id)
+    }
+}
+
+impl Evaluations {
+    /// [`Outcome::Failed`] if anything failed, [`Outcome::Unsupported`] if it all comes down to ones that can't be checked
+    pub fn outcome(&self) -> Outcome {
+        if self.0.iter().any(|evaluation| evaluation.outcome == Outcome::Failed) {
+            Outcome::Failed
+        } else if self.0.iter().any(|evaluation| evaluation.outcome == Outcome::Unsupported) {
+            Outcome::Unsupported
+        } else {
+            Outcome::Passed
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.outcome() == Outcome::Passed
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &Evaluation> {
+        self.0.iter().filter(|evaluation| evaluation.outcome == Outcome::Failed)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Evaluation> {
+        self.0.iter()
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Passed => write!(f, "passed"),
+            Outcome::Failed => write!(f, "failed"),
+            Outcome::Unsupported => write!(f, "unsupported"),
+        }
+    }
+}
+
+impl Display for Evaluation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {:?} {}: {} ({})",
+            self.requirement.requirement_type().as_str_name(),
+            self.requirement.name_id(),
+            self.requirement.value(),
+            self.outcome,
+            self.reason
+        )
+    }
+}
+
+//item requirements without a value mean having at least one
+fn amount(value: f32) -> i64 {
+    if value > 0.0 {
+        value.ceil() as i64
+    } else {
+        1
+    }
+}
+
+fn compare(condition: bool, reason: String) -> (Outcome, String) {
+    if condition {
+        passed(reason)
+    } else {
+        failed(reason)
+    }
+}
+
+fn passed(reason: String) -> (Outcome, String) {
+    (Outcome::Passed, reason)
+}
+
+fn failed(reason: String) -> (Outcome, String) {
+    (Outcome::Failed, reason)
+}
+
+fn unsupported(reason: String) -> (Outcome, String) {
+    (Outcome::Unsupported, reason)
+}
+
+fn listed(offers: &[String], name_id: &str, what: &str) -> (Outcome, String) {
+    let listed = offers.iter().any(|offer| offer == name_id);
+    compare(listed, format!("{} was {}{}", name_id, if listed { "" } else { "never " }, what))
+}
@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+
+use epic_balance::proto::loot_table_balancing_data::loot_table_data::LootTableType;
+use epic_balance::proto::loot_table_balancing_data::LootTableData;
+use epic_balance::proto::{LootTableBalancingData, LootTableEntry};
+use epic_logic::loot::{LootItem, LootTables};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const ROLLS: u32 = 20_000;
+
+fn entry(name_id: &str, probability: f32, base_value: i32, span: i32) -> LootTableEntry {
+    LootTableEntry {
+        name_id: Some(name_id.to_string()),
+        probability: Some(probability),
+        base_value: Some(base_value),
+        span: Some(span),
+        ..Default::default()
+    }
+}
+
+fn tables(table_type: LootTableType, entries: Vec<LootTableEntry>) -> LootTables {
+    LootTables::from_data(LootTableBalancingData {
+        loot_table_data: vec![LootTableData {
+            name_id: Some("table".to_string()),
+            loot_table_entries: entries,
+            r#type: Some(table_type as i32),
+            ..Default::default()
+        }],
+    })
+}
+
+fn item(name_id: &str) -> LootItem {
+    LootItem {
+        name_id: name_id.to_string(),
+        level: 1,
+    }
+}
+
+//exact chance of every amount of `name_id` in one roll, items that can't drop are left out of distributions
+fn exact(tables: &LootTables, name_id: &str) -> BTreeMap<u32, f64> {
+    tables
+        .distribution("table", 1)
+        .unwrap()
+        .get(&item(name_id))
+        .map(|distribution| distribution.iter().collect())
+        .unwrap_or_else(|| BTreeMap::from([(0, 1.0)]))
+}
+
+//how often every amount of `name_id` came up in seeded rolls
+fn rolled(tables: &LootTables, name_id: &str, seed: u64) -> BTreeMap<u32, f64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut counts = BTreeMap::new();
+
+    for _ in 0..ROLLS {
+        let loot = tables.roll("table", 1, &mut rng).unwrap();
+        let amount = loot.get(&item(name_id)).copied().unwrap_or_default();
+        *counts.entry(amount).or_default() += 1.0 / ROLLS as f64;
+    }
+
+    counts
+}
+
+fn assert_close(actual: &BTreeMap<u32, f64>, expected: &[(u32, f64)], tolerance: f64) {
+    let amounts = actual.keys().chain(expected.iter().map(|(amount, _)| amount));
+    for amount in amounts {
+        let actual = actual.get(amount).copied().unwrap_or_default();
+        let expected = expected
+            .iter()
+            .find(|(expected, _)| expected == amount)
+            .map(|(_, chance)| *chance)
+            .unwrap_or_default();
+        assert!((actual - expected).abs() <= tolerance, "amount {}: {} instead of {}", amount, actual, expected);
+    }
+}
+
+//the distribution is exact, seeded rolls have to land close to it
+fn assert_rolls_match(tables: &LootTables, name_id: &str, expected: &[(u32, f64)]) {
+    assert_close(&exact(tables, name_id), expected, 1e-9);
+    assert_close(&rolled(tables, name_id, 7), expected, 0.015);
+}
+
+#[test]
+fn probability_entries_drop_on_their_own() {
+    let tables = tables(
+        LootTableType::Probability,
+        vec![entry("gold", 0.5, 1, 1), entry("gold", 0.25, 1, 0), entry("gem", 1.0, 2, 0)],
+    );
+
+    //first entry: nothing half the time, 1 or 2 a quarter each, the second one adds 1 a quarter of the time
+    assert_rolls_match(&tables, "gold", &[(0, 0.375), (1, 0.3125), (2, 0.25), (3, 0.0625)]);
+    assert_rolls_match(&tables, "gem", &[(2, 1.0)]);
+}
+
+#[test]
+fn weighted_picks_one_entry_by_weight() {
+    let tables = tables(
+        LootTableType::Weighted,
+        vec![entry("gold", 1.0, 1, 0), entry("gem", 3.0, 2, 0), entry("nothing", 0.0, 1, 0)],
+    );
+
+    assert_rolls_match(&tables, "gold", &[(0, 0.75), (1, 0.25)]);
+    assert_rolls_match(&tables, "gem", &[(0, 0.25), (2, 0.75)]);
+    assert_rolls_match(&tables, "nothing", &[(0, 1.0)]);
+}
+
+#[test]
+fn wheel_picks_one_entry_by_weight() {
+    let tables = tables(
+        LootTableType::Wheel,
+        vec![entry("gold", 2.0, 1, 0), entry("gem", 2.0, 1, 2)],
+    );
+
+    assert_rolls_match(&tables, "gold", &[(0, 0.5), (1, 0.5)]);
+    assert_rolls_match(&tables, "gem", &[(0, 0.5), (1, 0.5 / 3.0), (2, 0.5 / 3.0), (3, 0.5 / 3.0)]);
+}
+
+#[test]
+fn wheel_forced_is_not_supported() {
+    let tables = tables(
+        LootTableType::WheelForced,
+        vec![entry("gold", 0.0, 1, 0), entry("gem", 1.0, 1, 0)],
+    );
+
+    let err = tables.distribution("table", 1).unwrap_err();
+    assert!(err.to_string().contains("WHEEL_FORCED"), "{}", err);
+    assert!(tables.roll("table", 1, &mut StdRng::seed_from_u64(7)).is_err());
+}
+
+#[test]
+fn inventory_drops_every_entry() {
+    let tables = tables(
+        LootTableType::Inventory,
+        vec![entry("gold", 0.0, 1, 1), entry("gold", 0.5, 2, 0)],
+    );
+
+    assert_rolls_match(&tables, "gold", &[(3, 0.5), (4, 0.5)]);
+}
+
+#[test]
+fn level_windows() {
+    let windowed = |min, max| LootTableEntry {
+        level_min_incl: Some(min),
+        level_max_excl: Some(max),
+        ..entry("gold", 1.0, 1, 0)
+    };
+    //no level_max_excl is the proto default of 0, which has no upper bound
+    let tables = tables(LootTableType::Inventory, vec![windowed(1, 10), windowed(5, 0), entry("gem", 1.0, 1, 0)]);
+
+    let gold = |level| {
+        let distribution = tables.distribution("table", level).unwrap();
+        let item = LootItem {
+            name_id: "gold".to_string(),
+            level,
+        };
+        distribution.get(&item).map(|gold| gold.expected()).unwrap_or_default()
+    };
+    assert_eq!(gold(1), 1.0);
+    assert_eq!(gold(5), 2.0);
+    assert_eq!(gold(10), 1.0);
+    assert_eq!(gold(500), 1.0);
+}
+
+#[test]
+fn negative_level_windows_are_not_supported() {
+    let tables = tables(
+        LootTableType::Inventory,
+        vec![LootTableEntry {
+            level_max_excl: Some(-1),
+            ..entry("gold", 1.0, 1, 0)
+        }],
+    );
+
+    let err = tables.distribution("table", 1).unwrap_err();
+    assert!(err.to_string().contains("level_max_excl"), "{}", err);
+    assert!(tables.roll("table", 1, &mut StdRng::seed_from_u64(7)).is_err());
+}
+
+#[test]
+fn non_finite_probabilities_count_as_zero() {
+    for table_type in [LootTableType::Probability, LootTableType::Weighted, LootTableType::Wheel] {
+        let tables = tables(
+            table_type,
+            vec![entry("gold", f32::NAN, 1, 0), entry("gem", f32::INFINITY, 1, 0), entry("coin", 1.0, 1, 0)],
+        );
+
+        assert_rolls_match(&tables, "gold", &[(0, 1.0)]);
+        assert_rolls_match(&tables, "gem", &[(0, 1.0)]);
+        assert_rolls_match(&tables, "coin", &[(1, 1.0)]);
+    }
+}
+
+#[test]
+fn seeded_rolls_repeat() {
+    let tables = tables(
+        LootTableType::Probability,
+        vec![entry("gold", 0.5, 1, 3), entry("gem", 0.5, 1, 3)],
+    );
+
+    let first = (0..100)
+        .map(|_| tables.roll("table", 1, &mut StdRng::seed_from_u64(42)).unwrap())
+        .collect::<Vec<_>>();
+    let mut rng = StdRng::seed_from_u64(42);
+    let second = tables.roll("table", 1, &mut rng).unwrap();
+
+    assert!(first.iter().all(|loot| *loot == second));
+}
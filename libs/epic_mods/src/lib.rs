@@ -0,0 +1,6 @@
+//community mods, a manifest listing patches and replacement files that get layered on top of the game files
+mod loader;
+mod manifest;
+
+pub use loader::{ModIssue, ModLoader};
+pub use manifest::{Mod, ModFile, ModManifest};
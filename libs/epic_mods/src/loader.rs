@@ -0,0 +1,272 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use anyhow::{anyhow, bail};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use epic_balance::{paths_overlap, BalancingDataArchive, BalancingDataTypes, Patch};
+use epic_locale::LocaleDataContainer;
+
+use crate::manifest::{Mod, ModFile};
+
+/// Layers mods on top of a balancing data container and its locales, remembering what every mod changed
+/// so mods that change the same thing can be reported
+pub struct ModLoader {
+    balancing: BalancingDataArchive,
+    locales: Vec<LocaleDataContainer>,
+    applied: Vec<String>,
+    //paths every applied mod changed by container key, the index is the one in applied
+    containers: HashMap<String, Vec<(String, usize)>>,
+    //same for locale text keys by language
+    texts: HashMap<String, HashMap<String, usize>>,
+    issues: Vec<ModIssue>,
+    reported: HashSet<(String, String, usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModIssue {
+    /// Both mods change the same value (or one changes something inside the other), the later one wins
+    Conflict {
+        target: String,
+        path: String,
+        first: String,
+        second: String,
+    },
+    /// The mod was made for another version of the balancing data container
+    VersionMismatch {
+        name: String,
+        target_version: String,
+        version: String,
+    },
+    /// Texts for a language none of the locales are in, they are left out
+    MissingLocale { name: String, language: String },
+}
+
+impl ModLoader {
+    pub fn new(balancing: BalancingDataArchive, locales: Vec<LocaleDataContainer>) -> Self {
+        Self {
+            balancing,
+            locales,
+            applied: Vec::new(),
+            containers: HashMap::new(),
+            texts: HashMap::new(),
+            issues: Vec::new(),
+            reported: HashSet::new(),
+        }
+    }
+
+    /// Applies the mods lowest priority first, mods with the same priority in the order they are given in
+    pub fn apply_all(&mut self, mods: &[Mod]) -> anyhow::Result<()> {
+        let mut mods: Vec<&Mod> = mods.iter().collect();
+        mods.sort_by_key(|loaded| loaded.manifest.priority);
+
+        for loaded in mods {
+            self.apply(loaded)?;
+        }
+
+        Ok(())
+    }
+
+    /// Applies every file of the mod in order, on top of every mod applied before it
+    pub fn apply(&mut self, loaded: &Mod) -> anyhow::Result<()> {
+        let manifest = &loaded.manifest;
+        let index = self.applied.len();
+        self.applied.push(manifest.name.clone());
+
+        if let Some(target_version) = manifest.target_version.as_ref().filter(|v| *v != self.balancing.get_version()) {
+            self.issues.push(ModIssue::VersionMismatch {
+                name: manifest.name.clone(),
+                target_version: target_version.clone(),
+                version: self.balancing.get_version().to_string(),
+            });
+        }
+
+        for file in &manifest.files {
+            let path = match file {
+                ModFile::Patch { path } | ModFile::Container { path, .. } | ModFile::Locale { path, .. } => path,
+            };
+
+            self.apply_file(loaded, file, index)
+                .map_err(|e| anyhow!("mod {}, {}: {}", manifest.name, path.display(), e))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn issues(&self) -> &[ModIssue] {
+        &self.issues
+    }
+
+    pub fn has_conflicts(&self) -> bool {
+        self.issues.iter().any(|issue| matches!(issue, ModIssue::Conflict { .. }))
+    }
+
+    pub fn balancing(&self) -> &BalancingDataArchive {
+        &self.balancing
+    }
+
+    pub fn locales(&self) -> &[LocaleDataContainer] {
+        &self.locales
+    }
+
+    pub fn into_parts(self) -> (BalancingDataArchive, Vec<LocaleDataContainer>) {
+        (self.balancing, self.locales)
+    }
+
+    fn apply_file(&mut self, loaded: &Mod, file: &ModFile, index: usize) -> anyhow::Result<()> {
+        match file {
+            ModFile::Patch { path } => {
+                let patch = Patch::from_json(&std::fs::read_to_string(loaded.file_path(path))?)?;
+                self.balancing.apply_patch(&patch)?;
+
+                for operation in &patch.operations {
+                    self.touch_container(operation.container_key(), operation.path(), index);
+                }
+            }
+            ModFile::Container { container, path } => {
+                let data_type = BalancingDataTypes::from_any_name(container);
+                let key = match data_type {
+                    Some(data_type) => data_type.to_string(),
+                    None => container.clone(),
+                };
+
+                let path = loaded.file_path(path);
+                match (raw_container(&path)?, data_type) {
+                    (Some(raw), _) => self.balancing.set_data_key_raw(&key, raw),
+                    (None, Some(data_type)) => {
+                        let data = std::fs::read_to_string(&path)?;
+                        if data.trim_start().starts_with('{') {
+                            self.balancing.set_data_any_json(data_type, &data)?;
+                        } else {
+                            self.balancing.set_data_any_ron(data_type, &data)?;
+                        }
+                    }
+                    (None, None) => bail!("{} is not a known container type, only .bin and .b64 files can replace it", container),
+                }
+
+                self.touch_container(key, "", index);
+            }
+            ModFile::Locale { path, language } => {
+                let texts = read_locale(&loaded.file_path(path))?;
+                let Some(language) = language.clone().or_else(|| texts.get_locale().language_id.clone()) else {
+                    bail!("the locale file has no language id, it has to be set in the manifest");
+                };
+
+                let Some(locale) = self
+                    .locales
+                    .iter_mut()
+                    .find(|locale| locale.get_locale().language_id.as_deref() == Some(language.as_str()))
+                else {
+                    self.issues.push(ModIssue::MissingLocale {
+                        name: loaded.name().to_string(),
+                        language,
+                    });
+                    return Ok(());
+                };
+
+                let mut keys = Vec::new();
+                for text in &texts.get_locale().texts {
+                    let Some(name_id) = &text.name_id else {
+                        continue;
+                    };
+
+                    locale.insert_translated_text(name_id, text.translated_text.as_deref().unwrap_or_default());
+                    keys.push(name_id.clone());
+                }
+
+                for key in keys {
+                    self.touch_text(&language, key, index);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn touch_container(&mut self, key: String, path: &str, index: usize) {
+        let touched = self.containers.entry(key.clone()).or_default();
+
+        let mut conflicts = Vec::new();
+        for (other_path, other) in touched.iter() {
+            if *other != index && paths_overlap(other_path, path) {
+                conflicts.push(*other);
+            }
+        }
+        touched.push((path.to_string(), index));
+
+        for other in conflicts {
+            self.conflict(key.clone(), path.to_string(), other, index);
+        }
+    }
+
+    fn touch_text(&mut self, language: &str, key: String, index: usize) {
+        let touched = self.texts.entry(language.to_string()).or_default();
+
+        if let Some(other) = touched.insert(key.clone(), index).filter(|other| *other != index) {
+            self.conflict(format!("{} locale", language), key, other, index);
+        }
+    }
+
+    fn conflict(&mut self, target: String, path: String, first: usize, second: usize) {
+        if !self.reported.insert((target.clone(), path.clone(), first, second)) {
+            return;
+        }
+
+        self.issues.push(ModIssue::Conflict {
+            target,
+            path,
+            first: self.applied[first].clone(),
+            second: self.applied[second].clone(),
+        });
+    }
+}
+
+//.bin and .b64 files go in as is
+fn raw_container(path: &Path) -> anyhow::Result<Option<Vec<u8>>> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("bin") => Ok(Some(std::fs::read(path)?)),
+        Some("b64") => Ok(Some(BASE64_STANDARD.decode(std::fs::read_to_string(path)?.trim())?)),
+        _ => Ok(None),
+    }
+}
+
+fn read_locale(path: &Path) -> anyhow::Result<LocaleDataContainer> {
+    let data = std::fs::read_to_string(path)?;
+    let trimmed = data.trim_start();
+
+    if trimmed.starts_with('{') {
+        LocaleDataContainer::from_json(&data)
+    } else if trimmed.starts_with('(') {
+        LocaleDataContainer::from_ron(&data)
+    } else {
+        LocaleDataContainer::from_csv(&data)
+    }
+}
+
+impl Display for ModIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ModIssue::Conflict {
+                target,
+                path,
+                first,
+                second,
+            } if path.is_empty() => write!(f, "{} and {} both change {}, {} wins", first, second, target, second),
+            ModIssue::Conflict {
+                target,
+                path,
+                first,
+                second,
+            } => write!(f, "{} and {} both change {} {}, {} wins", first, second, target, path, second),
+            ModIssue::VersionMismatch {
+                name,
+                target_version,
+                version,
+            } => write!(f, "{} was made for {}, the container is {}", name, target_version, version),
+            ModIssue::MissingLocale { name, language } => {
+                write!(f, "{} has texts for {} but no locale file is in that language", name, language)
+            }
+        }
+    }
+}
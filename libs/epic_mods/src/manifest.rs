@@ -0,0 +1,69 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
+/// `mod.json` (or `.ron`) of a mod, file paths in it are relative to the manifest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModManifest {
+    pub name: String,
+    pub version: String,
+    /// Version of the balancing data container the mod was made for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_version: Option<String>,
+    /// Higher priorities are applied later and win conflicts, mods with the same priority keep the order they were given in
+    #[serde(default)]
+    pub priority: i32,
+    pub files: Vec<ModFile>,
+}
+
+/// Applied in the order they are listed in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum ModFile {
+    /// Json patch file for the balancing data container, see [`epic_balance::Patch`]
+    Patch { path: PathBuf },
+    /// Replaces a whole balancing container, json/ron like `balancing encode` takes or `.bin`/`.b64` raw bytes
+    Container { container: String, path: PathBuf },
+    /// Json/ron/csv locale file with texts to add or change, the language is taken from the file if not given
+    Locale {
+        path: PathBuf,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+    },
+}
+
+/// Manifest and the directory it was loaded from
+#[derive(Debug, Clone)]
+pub struct Mod {
+    pub manifest: ModManifest,
+    pub dir: PathBuf,
+}
+
+impl Mod {
+    pub fn load(manifest_path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read_to_string(manifest_path)
+            .map_err(|e| anyhow!("can't read mod manifest {}: {}", manifest_path.display(), e))?;
+
+        let manifest = if data.trim_start().starts_with('{') {
+            serde_json::from_str(&data)?
+        } else {
+            epic_balance::ron_options().from_str(&data)?
+        };
+
+        Ok(Self {
+            manifest,
+            dir: manifest_path.parent().map(Path::to_path_buf).unwrap_or_default(),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.manifest.name
+    }
+
+    /// Path of a file listed in the manifest
+    pub fn file_path(&self, path: &Path) -> PathBuf {
+        self.dir.join(path)
+    }
+}
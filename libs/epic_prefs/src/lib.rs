@@ -7,11 +7,19 @@ use crate::xml::PlayerPrefsXml;
 use anyhow::anyhow;
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
+#[cfg(feature = "reflect")]
+use epic_wire::Descriptors;
+use epic_wire::UnknownFields;
 use prost::bytes::Buf;
 use prost::Message;
 use url_escape::NON_ALPHANUMERIC;
 use yaserde::ser::Config;
 
+#[cfg(any(feature = "json", feature = "ron"))]
+use epic_wire::{capture_unknown, WithUnknown};
+#[cfg(feature = "json")]
+use serde::de::DeserializeSeed;
+
 #[cfg(feature = "ron")]
 use ron::{
     extensions::Extensions,
@@ -32,18 +40,41 @@ pub mod proto {
     }
 }
 
+const PLAYER_DATA_MESSAGE: &str = "abepic.prefs.PlayerData";
+
+epic_wire::include_descriptors!(fn descriptors);
+
 pub struct PlayerPrefsData {
     data: PlayerData,
+    unknown: UnknownFields,
 }
 
 impl PlayerPrefsData {
     pub fn default() -> Self {
         Self {
             data: PlayerData::default(),
+            unknown: UnknownFields::default(),
         }
     }
 
+    fn decode(buf: &[u8]) -> anyhow::Result<Self> {
+        let data = PlayerData::decode(buf)?;
+        let unknown = UnknownFields::extract(descriptors(), PLAYER_DATA_MESSAGE, buf)?;
+        Ok(Self { data, unknown })
+    }
+
+    fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(self
+            .unknown
+            .merge(descriptors(), PLAYER_DATA_MESSAGE, &self.data.encode_to_vec())?)
+    }
+
     pub fn from_prefs_xml(xml_contents: &str) -> anyhow::Result<Self> {
+        Self::decode(&Self::payload_from_prefs_xml(xml_contents)?)
+    }
+
+    /// The encoded player data in the player key, without decoding it
+    pub fn payload_from_prefs_xml(xml_contents: &str) -> anyhow::Result<Vec<u8>> {
         let mut xml =
             yaserde::de::from_str::<PlayerPrefsXml>(xml_contents).map_err(anyhow::Error::msg)?;
         let player_key = xml
@@ -53,44 +84,67 @@ impl PlayerPrefsData {
             .next()
             .ok_or(anyhow!("no player key"))?;
         let player_data = &player_key.value;
-        let decoded = BASE64_STANDARD.decode(url_escape::decode(player_data).as_bytes())?;
-
-        let data = PlayerData::decode(decoded.as_slice())?;
-        Ok(Self { data })
+        Ok(BASE64_STANDARD.decode(url_escape::decode(player_data).as_bytes())?)
     }
 
     #[cfg(feature = "json")]
     pub fn from_json(contents: &str) -> anyhow::Result<Self> {
-        let data = serde_json::from_str::<PlayerData>(contents)?;
-        Ok(Self { data })
+        let mut deserializer = serde_json::Deserializer::from_str(contents);
+        let (data, unknown) = capture_unknown::<PlayerData>().deserialize(&mut deserializer)?;
+        deserializer.end()?;
+        Ok(Self { data, unknown })
     }
 
     #[cfg(feature = "json")]
     pub fn to_json_pretty(&self) -> serde_json::Result<String> {
-        serde_json::to_string_pretty::<PlayerData>(&self.data)
+        serde_json::to_string_pretty(&WithUnknown::new(&self.data, &self.unknown))
     }
 
     #[cfg(feature = "json")]
     pub fn to_json(&self) -> serde_json::Result<String> {
-        serde_json::to_string::<PlayerData>(&self.data)
+        serde_json::to_string(&WithUnknown::new(&self.data, &self.unknown))
+    }
+
+    /// Json form of the save decoded against descriptors loaded at runtime, `message` defaults to `PlayerData`
+    #[cfg(feature = "reflect")]
+    pub fn to_reflect(&self, descriptors: &Descriptors, message: Option<&str>) -> anyhow::Result<serde_json::Value> {
+        descriptors.decode_dynamic(message.unwrap_or("PlayerData"), &self.encode()?)
+    }
+
+    /// Reverse of [`PlayerPrefsData::to_reflect`], whatever the compiled in proto doesn't know is kept as unknown fields
+    #[cfg(feature = "reflect")]
+    pub fn from_reflect(
+        descriptors: &Descriptors,
+        message: Option<&str>,
+        value: &serde_json::Value,
+    ) -> anyhow::Result<Self> {
+        Self::decode(&descriptors.encode_dynamic(message.unwrap_or("PlayerData"), value)?)
+    }
+
+    /// JSON Schema of the json form of the save
+    #[cfg(feature = "json")]
+    pub fn json_schema() -> serde_json::Value {
+        descriptors()
+            .json_schema(PLAYER_DATA_MESSAGE)
+            .expect("player data is in the descriptors")
     }
 
     #[cfg(feature = "ron")]
     pub fn from_ron(contents: &str) -> anyhow::Result<Self> {
         //todo: there is def a less stupid way of doing this
         let options = Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
-        let data = options.from_str::<PlayerData>(contents)?;
-        Ok(Self { data })
+        let (data, unknown) = options.from_str_seed(contents, capture_unknown::<PlayerData>())?;
+        Ok(Self { data, unknown })
     }
 
     #[cfg(feature = "ron")]
     pub fn to_ron(&self) -> ron::Result<String> {
-        ron::ser::to_string::<PlayerData>(&self.data)
+        ron::ser::to_string(&WithUnknown::new(&self.data, &self.unknown))
     }
 
     #[cfg(feature = "ron")]
     pub fn to_ron_pretty(&self) -> ron::Result<String> {
-        ron::ser::to_string_pretty::<PlayerData>(&self.data, Default::default())
+        ron::ser::to_string_pretty(&WithUnknown::new(&self.data, &self.unknown), Default::default())
     }
     
     #[cfg(feature = "sdkv2")]
@@ -104,14 +158,12 @@ impl PlayerPrefsData {
         
         let decoded = BASE64_STANDARD.decode(decompressed)?;
         
-        let data = PlayerData::decode(decoded.as_slice())?;
-        Ok(Self { data })
+        Self::decode(decoded.as_slice())
     }
     
     #[cfg(feature = "sdkv2")]
     pub fn to_sdkv2(&self) -> anyhow::Result<String> {
-        let mut buf = Vec::new();
-        self.data.encode(&mut buf)?;
+        let buf = self.encode()?;
         
         let encoded = BASE64_STANDARD.encode(buf);
         
@@ -140,8 +192,7 @@ impl PlayerPrefsData {
             .next()
             .ok_or(anyhow!("no player key"))?;
 
-        let mut encoded_player_data = Vec::new();
-        self.data.encode(&mut encoded_player_data)?;
+        let encoded_player_data = self.encode()?;
         let encoded_player_data = url_escape::encode(
             BASE64_STANDARD.encode(&encoded_player_data).as_str(),
             NON_ALPHANUMERIC,
@@ -165,11 +216,19 @@ impl PlayerPrefsData {
         self.data = data;
     }
 
-    pub fn new<B>(buf: B) -> anyhow::Result<Self>
+    pub fn get_unknown_fields(&self) -> &UnknownFields {
+        &self.unknown
+    }
+
+    pub fn set_unknown_fields(&mut self, unknown: UnknownFields) {
+        self.unknown = unknown;
+    }
+
+    pub fn new<B>(mut buf: B) -> anyhow::Result<Self>
     where
         B: Buf,
     {
-        let data = PlayerData::decode(buf)?;
-        Ok(Self { data })
+        let buf = buf.copy_to_bytes(buf.remaining());
+        Self::decode(&buf)
     }
 }
@@ -0,0 +1,40 @@
+#![cfg(feature = "json")]
+
+use epic_prefs::PlayerPrefsData;
+use serde_json::{json, Value};
+
+fn definition<'a>(schema: &'a Value, name: &str) -> &'a Value {
+    &schema["$defs"][name]
+}
+
+#[test]
+fn date_times_are_strings() {
+    let schema = PlayerPrefsData::json_schema();
+
+    let offers = &schema["properties"]["currentSpecialShopOffers"]["additionalProperties"];
+    assert_eq!(offers, &json!({ "type": "string", "format": "date-time" }));
+    assert!(definition(&schema, "abepic.bcl.DateTime").is_null());
+}
+
+#[test]
+fn proto_names_are_allowed() {
+    let schema = PlayerPrefsData::json_schema();
+    let properties = &schema["properties"];
+
+    assert_eq!(properties["nameId"].get("deprecated"), None);
+    assert_eq!(properties["name_id"]["deprecated"], json!(true));
+    //no second name if both are the same
+    assert_eq!(properties["level"].get("deprecated"), None);
+    assert_eq!(schema["additionalProperties"], json!(false));
+}
+
+#[test]
+fn enum_numbers_are_allowed() {
+    let schema = PlayerPrefsData::json_schema();
+    let states = definition(&schema, "abepic.prefs.HotspotData.HotspotUnlockState")["enum"]
+        .as_array()
+        .unwrap();
+
+    assert!(states.contains(&json!("HOTSPOT_UNLOCK_STATE_UNKNOWN")));
+    assert!(states.contains(&json!(0)));
+}
@@ -0,0 +1,131 @@
+use std::cmp::Ordering;
+
+use serde_json::{Map, Value};
+
+use crate::parse::{Expr, Operand, Operator, Step};
+
+pub(crate) fn select<'a>(steps: &[Step], root: &'a Value, start: &'a Value) -> Vec<&'a Value> {
+    let mut nodes = vec![start];
+
+    for step in steps {
+        let mut next = Vec::new();
+        for node in nodes {
+            apply(step, root, node, &mut next);
+        }
+        nodes = next;
+    }
+
+    nodes
+}
+
+fn apply<'a>(step: &Step, root: &'a Value, node: &'a Value, out: &mut Vec<&'a Value>) {
+    match (step, node) {
+        (Step::Field(name), Value::Object(fields)) => out.extend(field(fields, name)),
+        (Step::Index(index), Value::Array(items)) => {
+            let index = if *index < 0 { items.len() as i64 + index } else { *index };
+            out.extend(usize::try_from(index).ok().and_then(|index| items.get(index)));
+        }
+        (Step::Wildcard, _) => out.extend(children(node)),
+        (Step::Descend(name), _) => descend(name.as_deref(), node, out),
+        (Step::Filter(expr), _) => out.extend(children(node).filter(|child| matches(expr, root, child))),
+        _ => {}
+    }
+}
+
+//snake_case names from the proto files work too, `skill_template_type` finds `skillTemplateType`
+fn field<'a>(fields: &'a Map<String, Value>, name: &str) -> Option<&'a Value> {
+    fields.get(name).or_else(|| fields.get(&camel_case(name)))
+}
+
+fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = false;
+
+    for c in name.chars() {
+        match c {
+            '_' if !camel.is_empty() => upper = true,
+            c if upper => {
+                camel.extend(c.to_uppercase());
+                upper = false;
+            }
+            c => camel.push(c),
+        }
+    }
+
+    camel
+}
+
+fn children(node: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match node {
+        Value::Object(fields) => Box::new(fields.values()),
+        Value::Array(items) => Box::new(items.iter()),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+fn descend<'a>(name: Option<&str>, node: &'a Value, out: &mut Vec<&'a Value>) {
+    match (name, node) {
+        (Some(name), Value::Object(fields)) => out.extend(field(fields, name)),
+        (None, _) => out.extend(children(node)),
+        _ => {}
+    }
+
+    for child in children(node) {
+        descend(name, child, out);
+    }
+}
+
+fn matches(expr: &Expr, root: &Value, item: &Value) -> bool {
+    match expr {
+        Expr::Or(a, b) => matches(a, root, item) || matches(b, root, item),
+        Expr::And(a, b) => matches(a, root, item) && matches(b, root, item),
+        Expr::Not(expr) => !matches(expr, root, item),
+        Expr::Truthy(operand) => values(operand, root, item)
+            .iter()
+            .any(|value| !matches!(value, Value::Null | Value::Bool(false))),
+        //any of the values on one side against any on the other, like jsonpath does for wildcards
+        Expr::Compare(left, operator, right) => {
+            let right = values(right, root, item);
+            values(left, root, item)
+                .iter()
+                .any(|left| right.iter().any(|right| compare(left, *operator, right)))
+        }
+    }
+}
+
+fn values<'a>(operand: &'a Operand, root: &'a Value, item: &'a Value) -> Vec<&'a Value> {
+    match operand {
+        Operand::Relative(steps) => select(steps, root, item),
+        Operand::Absolute(steps) => select(steps, root, root),
+        Operand::Literal(value) => vec![value],
+    }
+}
+
+fn compare(left: &Value, operator: Operator, right: &Value) -> bool {
+    let ordering = match (left, right) {
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        _ => match (number(left), number(right)) {
+            (Some(left), Some(right)) => left.partial_cmp(&right),
+            _ if left == right => Some(Ordering::Equal),
+            _ => None,
+        },
+    };
+
+    match operator {
+        Operator::Eq => ordering == Some(Ordering::Equal),
+        Operator::Ne => ordering != Some(Ordering::Equal),
+        Operator::Lt => ordering == Some(Ordering::Less),
+        Operator::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        Operator::Gt => ordering == Some(Ordering::Greater),
+        Operator::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+    }
+}
+
+//64 bit integers are strings in the json form
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(string) => string.parse().ok(),
+        _ => None,
+    }
+}
@@ -0,0 +1,88 @@
+//jsonpath like queries over the json form of balancing data and player saves
+mod eval;
+mod parse;
+
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use epic_balance::{BalancingDataArchive, BalancingDataTypes};
+use epic_prefs::PlayerPrefsData;
+use epic_wire::WithUnknown;
+use serde_json::{Map, Value};
+
+use crate::parse::Step;
+
+/// A parsed query, mostly JSONPath:
+///
+/// - `.name`, `["name"]` a field, the leading `$` and the dot of the first field are optional.
+///   snake_case names work as well as the camelCase ones of the json form
+/// - `[0]`, `[-1]` list items, negative ones from the end
+/// - `.*`, `[*]` every item or field value
+/// - `..name`, `..*` the field (or everything) at any depth
+/// - `[?(@.damage > 100 && @.type == "FIRE")]` items the filter is true for, `@` is the item and `$` the root.
+///   Comparisons are `== != < <= > >=`, with `&&`, `||`, `!` and parentheses, a path alone is true if it's there and not false/null
+///
+/// `SkillBalancingData.skillData[?(@.skillTemplateType == "X" && @.skillParameters.damage > 100)].nameId`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+impl Query {
+    pub fn parse(expr: &str) -> anyhow::Result<Self> {
+        let steps = parse::parse(expr).map_err(|e| anyhow!("invalid query: {}", e))?;
+        Ok(Self { steps })
+    }
+
+    pub fn run<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        eval::select(&self.steps, root, root)
+    }
+
+    /// Runs over the typed containers, the root has the `version` and every container of a known type under its short name
+    /// (`SkillBalancingData`). Only the container the query starts with is decoded if it starts with one
+    pub fn run_archive(&self, archive: &BalancingDataArchive) -> anyhow::Result<Vec<Value>> {
+        let mut root = Map::new();
+        root.insert("version".to_string(), Value::from(archive.get_version()));
+
+        let data_types: Vec<(String, BalancingDataTypes)> = match self.steps.first() {
+            Some(Step::Field(name)) if name == "version" => Vec::new(),
+            Some(Step::Field(name)) => {
+                let data_type = BalancingDataTypes::from_any_name(name)
+                    .ok_or_else(|| anyhow!("{} is not a balancing container type", name))?;
+                vec![(name.clone(), data_type)]
+            }
+            _ => archive
+                .get_balaning_data_keys()
+                .iter()
+                .filter_map(|key| BalancingDataTypes::from_str(key).ok())
+                .map(|data_type| (data_type.short_name().to_string(), data_type))
+                .collect(),
+        };
+
+        for (name, data_type) in data_types {
+            if archive.get_data_key(&data_type.to_string()).is_none() {
+                continue;
+            }
+
+            let (data, unknown) = archive.get_data_any_with_unknown(data_type)?;
+            root.insert(name, serde_json::to_value(WithUnknown::new(&data, &unknown))?);
+        }
+
+        let root = Value::Object(root);
+        Ok(self.run(&root).into_iter().cloned().collect())
+    }
+
+    /// Runs over the json form of the save
+    pub fn run_player(&self, prefs: &PlayerPrefsData) -> anyhow::Result<Vec<Value>> {
+        let root = serde_json::to_value(WithUnknown::new(prefs.get_data(), prefs.get_unknown_fields()))?;
+        Ok(self.run(&root).into_iter().cloned().collect())
+    }
+}
+
+impl FromStr for Query {
+    type Err = anyhow::Error;
+
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        Self::parse(expr)
+    }
+}
@@ -0,0 +1,304 @@
+use anyhow::{anyhow, bail};
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Step {
+    /// `.name` or `["name"]`
+    Field(String),
+    /// `[n]`, negative counts from the end
+    Index(i64),
+    /// `.*` or `[*]`, every value of an object or list
+    Wildcard,
+    /// `..name` or `..*`, the node and everything under it
+    Descend(Option<String>),
+    /// `[?(...)]`, list items or object values the expression is true for
+    Filter(Expr),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(Operand, Operator, Operand),
+    /// There is a value that isn't `false` or `null`
+    Truthy(Operand),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Operand {
+    /// `@.path`, from the item being filtered
+    Relative(Vec<Step>),
+    /// `$.path`, from the root
+    Absolute(Vec<Step>),
+    Literal(Value),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+pub(crate) fn parse(expr: &str) -> anyhow::Result<Vec<Step>> {
+    let mut parser = Parser { chars: expr.chars().collect(), position: 0 };
+
+    parser.skip_whitespace();
+    parser.eat('$');
+    //the first field doesn't need a dot
+    let mut steps = Vec::new();
+    if parser.peek().is_some_and(is_ident_start) {
+        steps.push(Step::Field(parser.ident()?));
+    }
+    steps.extend(parser.steps()?);
+
+    parser.skip_whitespace();
+    if let Some(c) = parser.peek() {
+        bail!("unexpected {:?} at {}", c, parser.position);
+    }
+
+    Ok(steps)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.position + offset).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn eat_str(&mut self, s: &str) -> bool {
+        let found = s.chars().enumerate().all(|(i, c)| self.peek_at(i) == Some(c));
+        if found {
+            self.position += s.chars().count();
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> anyhow::Result<()> {
+        match self.eat(c) {
+            true => Ok(()),
+            false => Err(self.error(&format!("{:?}", c))),
+        }
+    }
+
+    fn error(&self, expected: &str) -> anyhow::Error {
+        match self.peek() {
+            Some(c) => anyhow!("expected {} at {}, found {:?}", expected, self.position, c),
+            None => anyhow!("expected {} at the end", expected),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    fn steps(&mut self) -> anyhow::Result<Vec<Step>> {
+        let mut steps = Vec::new();
+
+        loop {
+            if self.eat_str("..") {
+                if self.eat('*') {
+                    steps.push(Step::Descend(None));
+                } else {
+                    steps.push(Step::Descend(Some(self.ident()?)));
+                }
+            } else if self.eat('.') {
+                if self.eat('*') {
+                    steps.push(Step::Wildcard);
+                } else {
+                    steps.push(Step::Field(self.ident()?));
+                }
+            } else if self.eat('[') {
+                self.skip_whitespace();
+                steps.push(self.bracket()?);
+                self.skip_whitespace();
+                self.expect(']')?;
+            } else {
+                return Ok(steps);
+            }
+        }
+    }
+
+    //inside of a [], without the brackets
+    fn bracket(&mut self) -> anyhow::Result<Step> {
+        if self.eat('*') {
+            return Ok(Step::Wildcard);
+        }
+
+        //the usual parentheses around the expression are just a group
+        if self.eat('?') {
+            return Ok(Step::Filter(self.or()?));
+        }
+
+        match self.peek() {
+            Some('"' | '\'') => Ok(Step::Field(self.string()?)),
+            Some(c) if c == '-' || c.is_ascii_digit() => {
+                let number = self.number()?;
+                let index = number.as_i64().ok_or_else(|| anyhow!("{} is not a list index", number))?;
+                Ok(Step::Index(index))
+            }
+            _ => Err(self.error("a field name, index, * or filter")),
+        }
+    }
+
+    fn ident(&mut self) -> anyhow::Result<String> {
+        if !self.peek().is_some_and(is_ident_start) {
+            return Err(self.error("a field name"));
+        }
+
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric() || c == '_') {
+            self.position += 1;
+        }
+
+        Ok(self.chars[start..self.position].iter().collect())
+    }
+
+    fn string(&mut self) -> anyhow::Result<String> {
+        let quote = self.peek().filter(|c| matches!(c, '"' | '\'')).ok_or_else(|| self.error("a string"))?;
+        self.position += 1;
+
+        let mut string = String::new();
+        loop {
+            match self.peek() {
+                None => bail!("unterminated string"),
+                Some(c) if c == quote => {
+                    self.position += 1;
+                    return Ok(string);
+                }
+                Some('\\') => {
+                    let escaped = self.peek_at(1).ok_or_else(|| anyhow!("unterminated string"))?;
+                    string.push(match escaped {
+                        'n' => '\n',
+                        't' => '\t',
+                        'r' => '\r',
+                        c => c,
+                    });
+                    self.position += 2;
+                }
+                Some(c) => {
+                    string.push(c);
+                    self.position += 1;
+                }
+            }
+        }
+    }
+
+    fn number(&mut self) -> anyhow::Result<Value> {
+        let start = self.position;
+        self.eat('-');
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '+' | '-')) {
+            self.position += 1;
+        }
+
+        let number: String = self.chars[start..self.position].iter().collect();
+        serde_json::from_str(&number).map_err(|_| anyhow!("{} is not a number", number))
+    }
+
+    fn or(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.and()?;
+
+        loop {
+            self.skip_whitespace();
+            if !self.eat_str("||") {
+                return Ok(expr);
+            }
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+    }
+
+    fn and(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.unary()?;
+
+        loop {
+            self.skip_whitespace();
+            if !self.eat_str("&&") {
+                return Ok(expr);
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Expr> {
+        self.skip_whitespace();
+
+        if self.peek() == Some('!') && self.peek_at(1) != Some('=') {
+            self.position += 1;
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+
+        if self.eat('(') {
+            let expr = self.or()?;
+            self.skip_whitespace();
+            self.expect(')')?;
+            return Ok(expr);
+        }
+
+        let left = self.operand()?;
+        self.skip_whitespace();
+
+        let operator = if self.eat_str("==") {
+            Operator::Eq
+        } else if self.eat_str("!=") {
+            Operator::Ne
+        } else if self.eat_str("<=") {
+            Operator::Le
+        } else if self.eat_str(">=") {
+            Operator::Ge
+        } else if self.eat('<') {
+            Operator::Lt
+        } else if self.eat('>') {
+            Operator::Gt
+        } else {
+            return Ok(Expr::Truthy(left));
+        };
+
+        self.skip_whitespace();
+        Ok(Expr::Compare(left, operator, self.operand()?))
+    }
+
+    fn operand(&mut self) -> anyhow::Result<Operand> {
+        match self.peek() {
+            Some('@') => {
+                self.position += 1;
+                Ok(Operand::Relative(self.steps()?))
+            }
+            Some('$') => {
+                self.position += 1;
+                Ok(Operand::Absolute(self.steps()?))
+            }
+            Some('"' | '\'') => Ok(Operand::Literal(Value::String(self.string()?))),
+            Some(c) if c == '-' || c.is_ascii_digit() => Ok(Operand::Literal(self.number()?)),
+            _ if self.eat_str("true") => Ok(Operand::Literal(Value::Bool(true))),
+            _ if self.eat_str("false") => Ok(Operand::Literal(Value::Bool(false))),
+            _ if self.eat_str("null") => Ok(Operand::Literal(Value::Null)),
+            _ => Err(self.error("@, $, a string, number, true, false or null")),
+        }
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
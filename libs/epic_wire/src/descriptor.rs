@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use prost::{DecodeError, Message};
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FileDescriptorSet,
+};
+
+//source code info paths, see descriptor.proto
+const FILE_MESSAGE: i32 = 4;
+const FILE_ENUM: i32 = 5;
+const MESSAGE_FIELD: i32 = 2;
+const MESSAGE_NESTED: i32 = 3;
+const MESSAGE_ENUM: i32 = 4;
+const ENUM_VALUE: i32 = 2;
+
+/// Message layouts taken from the `proto_descriptor.bin` prost-build writes next to the generated code
+#[derive(Debug, Clone, Default)]
+pub struct Descriptors {
+    messages: HashMap<String, MessageInfo>,
+    enums: HashMap<String, EnumInfo>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MessageInfo {
+    /// Fully qualified name without the leading dot, e.g. `abepic.locale.SerializedLocalizedTexts`
+    pub name: String,
+    pub fields: Vec<FieldInfo>,
+    pub map_entry: bool,
+    /// Comment above the message in the proto file
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldInfo {
+    pub name: String,
+    pub number: u32,
+    pub field_type: Type,
+    pub repeated: bool,
+    /// Repeated scalars written as a single length delimited record
+    pub packed: bool,
+    /// Fully qualified name of the message or enum type, if the field has one
+    pub type_name: Option<String>,
+    /// `[default = ...]` as written in the proto file
+    pub default_value: Option<String>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumInfo {
+    /// Fully qualified name without the leading dot
+    pub name: String,
+    pub values: Vec<EnumValueInfo>,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EnumValueInfo {
+    pub name: String,
+    pub number: i32,
+    pub comment: Option<String>,
+}
+
+impl Descriptors {
+    pub fn decode(buf: &[u8]) -> Result<Self, DecodeError> {
+        let set = FileDescriptorSet::decode(buf)?;
+
+        let mut descriptors = Self::default();
+        for file in &set.file {
+            //leading comments, or trailing ones for things commented at the end of the line
+            let comments: HashMap<&[i32], String> = file
+                .source_code_info
+                .iter()
+                .flat_map(|info| &info.location)
+                .filter_map(|location| {
+                    let comment = location.leading_comments.as_deref().or(location.trailing_comments.as_deref())?;
+                    Some((location.path.as_slice(), comment.trim().to_string()))
+                })
+                .filter(|(_, comment)| !comment.is_empty())
+                .collect();
+
+            let proto3 = file.syntax() == "proto3";
+            for (index, message) in file.message_type.iter().enumerate() {
+                descriptors.add_message(file.package(), message, &[FILE_MESSAGE, index as i32], &comments, proto3);
+            }
+            for (index, enum_type) in file.enum_type.iter().enumerate() {
+                descriptors.add_enum(file.package(), enum_type, &[FILE_ENUM, index as i32], &comments);
+            }
+        }
+
+        Ok(descriptors)
+    }
+
+    fn add_message(
+        &mut self,
+        scope: &str,
+        message: &DescriptorProto,
+        path: &[i32],
+        comments: &HashMap<&[i32], String>,
+        proto3: bool,
+    ) {
+        let name = if scope.is_empty() {
+            message.name().to_string()
+        } else {
+            format!("{}.{}", scope, message.name())
+        };
+
+        for (index, nested) in message.nested_type.iter().enumerate() {
+            self.add_message(&name, nested, &[path, &[MESSAGE_NESTED, index as i32]].concat(), comments, proto3);
+        }
+        for (index, enum_type) in message.enum_type.iter().enumerate() {
+            self.add_enum(&name, enum_type, &[path, &[MESSAGE_ENUM, index as i32]].concat(), comments);
+        }
+
+        let fields = message
+            .field
+            .iter()
+            .enumerate()
+            .map(|(index, field)| FieldInfo {
+                name: field.name().to_string(),
+                number: field.number() as u32,
+                field_type: field.r#type(),
+                repeated: field.label() == Label::Repeated,
+                //proto3 packs repeated scalars unless told not to, proto2 only when told to
+                packed: field.label() == Label::Repeated
+                    && is_packable(field.r#type())
+                    && field.options.as_ref().and_then(|options| options.packed).unwrap_or(proto3),
+                type_name: field
+                    .type_name
+                    .as_deref()
+                    .map(|type_name| type_name.trim_start_matches('.').to_string()),
+                default_value: field.default_value.clone(),
+                comment: comments.get([path, &[MESSAGE_FIELD, index as i32]].concat().as_slice()).cloned(),
+            })
+            .collect();
+
+        let map_entry = message
+            .options
+            .as_ref()
+            .is_some_and(|options| options.map_entry());
+
+        self.messages.insert(
+            name.clone(),
+            MessageInfo {
+                name,
+                fields,
+                map_entry,
+                comment: comments.get(path).cloned(),
+            },
+        );
+    }
+
+    fn add_enum(&mut self, scope: &str, enum_type: &EnumDescriptorProto, path: &[i32], comments: &HashMap<&[i32], String>) {
+        let name = if scope.is_empty() {
+            enum_type.name().to_string()
+        } else {
+            format!("{}.{}", scope, enum_type.name())
+        };
+
+        let values = enum_type
+            .value
+            .iter()
+            .enumerate()
+            .map(|(index, value)| EnumValueInfo {
+                name: value.name().to_string(),
+                number: value.number(),
+                comment: comments.get([path, &[ENUM_VALUE, index as i32]].concat().as_slice()).cloned(),
+            })
+            .collect();
+
+        self.enums.insert(
+            name.clone(),
+            EnumInfo {
+                name,
+                values,
+                comment: comments.get(path).cloned(),
+            },
+        );
+    }
+
+    /// Looks a message up by its fully qualified name, a leading dot is allowed
+    pub fn message(&self, name: &str) -> Option<&MessageInfo> {
+        self.messages.get(name.trim_start_matches('.'))
+    }
+
+    /// Same as [`Descriptors::message`], but a name without the package works too if only one message has it
+    pub fn find_message(&self, name: &str) -> Option<&MessageInfo> {
+        self.message(name).or_else(|| {
+            let suffix = format!(".{}", name.trim_start_matches('.'));
+            let mut found = self.messages.values().filter(|message| message.name.ends_with(&suffix));
+
+            match (found.next(), found.next()) {
+                (Some(message), None) => Some(message),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn messages(&self) -> impl Iterator<Item = &MessageInfo> {
+        self.messages.values()
+    }
+
+    /// Looks an enum up by its fully qualified name, a leading dot is allowed
+    pub fn enum_type(&self, name: &str) -> Option<&EnumInfo> {
+        self.enums.get(name.trim_start_matches('.'))
+    }
+}
+
+impl MessageInfo {
+    pub fn field(&self, number: u32) -> Option<&FieldInfo> {
+        self.fields.iter().find(|field| field.number == number)
+    }
+
+    pub fn field_by_name(&self, name: &str) -> Option<&FieldInfo> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+impl FieldInfo {
+    /// Name of the enum type the field holds
+    pub fn enum_type(&self) -> Option<&str> {
+        match self.field_type {
+            Type::Enum => self.type_name.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Name of the message type the field holds, `None` for scalars and enums
+    pub fn message_type(&self) -> Option<&str> {
+        match self.field_type {
+            Type::Message | Type::Group => self.type_name.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn is_string(&self) -> bool {
+        self.field_type == Type::String
+    }
+
+    /// lowerCamelCase name the field has in json/ron, same conversion protoc and pbjson use
+    pub fn json_name(&self) -> String {
+        let mut json_name = String::with_capacity(self.name.len());
+        let mut capitalize = false;
+
+        for c in self.name.chars() {
+            if c == '_' {
+                capitalize = true;
+            } else if capitalize {
+                json_name.push(c.to_ascii_uppercase());
+                capitalize = false;
+            } else {
+                json_name.push(c);
+            }
+        }
+
+        json_name
+    }
+}
+
+fn is_packable(field_type: Type) -> bool {
+    !matches!(field_type, Type::String | Type::Bytes | Type::Message | Type::Group)
+}
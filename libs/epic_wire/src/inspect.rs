@@ -0,0 +1,216 @@
+use std::fmt::{Display, Formatter};
+
+use prost::encoding::{decode_key, decode_varint, WireType};
+
+/// Everything that could be read of a blob walked as raw protobuf, like `protoc --decode_raw` but with offsets
+/// and without giving up on the first broken record
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inspection {
+    pub fields: Vec<InspectedField>,
+    /// Where and why the walk stopped, `None` if the whole blob was read
+    pub error: Option<InspectError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectedField {
+    /// Offset of the field key from the start of the inspected blob
+    pub offset: usize,
+    pub number: u32,
+    pub wire_type: WireType,
+    pub value: InspectedValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum InspectedValue {
+    Varint(u64),
+    Fixed32(u32),
+    Fixed64(u64),
+    /// Length delimited value that is printable text
+    String(String),
+    /// Length delimited value that reads as a message, at least its first record.
+    /// If it breaks part-way the nested [`Inspection::error`] says where
+    Message(Inspection),
+    Bytes(Vec<u8>),
+    Group(Inspection),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct InspectError {
+    /// Offset of the record that couldn't be read
+    pub offset: usize,
+    pub message: String,
+    /// Bytes from `offset` to the end of the message
+    pub remaining: Vec<u8>,
+}
+
+/// Walks `buf` as raw protobuf wire format. Length delimited values are guessed: printable text is a string,
+/// something that starts with a readable record is a nested message (with its own error if it breaks part-way),
+/// everything else is bytes
+pub fn inspect(buf: &[u8]) -> Inspection {
+    inspect_at(buf, 0)
+}
+
+fn inspect_at(buf: &[u8], base: usize) -> Inspection {
+    let mut fields = Vec::new();
+    let mut cursor = buf;
+
+    while !cursor.is_empty() {
+        let offset = base + buf.len() - cursor.len();
+        let start = cursor;
+
+        match inspect_field(&mut cursor, offset) {
+            Ok(field) => fields.push(field),
+            Err(message) => {
+                let error = InspectError {
+                    offset,
+                    message,
+                    remaining: start.to_vec(),
+                };
+                return Inspection { fields, error: Some(error) };
+            }
+        }
+    }
+
+    Inspection { fields, error: None }
+}
+
+fn inspect_field(cursor: &mut &[u8], offset: usize) -> Result<InspectedField, String> {
+    let start = cursor.len();
+    let (number, wire_type) = decode_key(cursor).map_err(|e| e.to_string())?;
+
+    let value = match wire_type {
+        WireType::Varint => InspectedValue::Varint(decode_varint(cursor).map_err(|e| e.to_string())?),
+        WireType::ThirtyTwoBit => InspectedValue::Fixed32(u32::from_le_bytes(take(cursor, 4)?.try_into().unwrap_or_default())),
+        WireType::SixtyFourBit => InspectedValue::Fixed64(u64::from_le_bytes(take(cursor, 8)?.try_into().unwrap_or_default())),
+        WireType::LengthDelimited => {
+            let len = decode_varint(cursor).map_err(|e| e.to_string())? as usize;
+            let value_offset = offset + start - cursor.len();
+            guess(take(cursor, len)?, value_offset)
+        }
+        WireType::StartGroup => {
+            let mut fields = Vec::new();
+            loop {
+                if cursor.is_empty() {
+                    return Err(format!("group {} has no end group", number));
+                }
+
+                let mut peek = *cursor;
+                if let Ok((end, WireType::EndGroup)) = decode_key(&mut peek) {
+                    if end != number {
+                        return Err(format!("group {} ends with end group {}", number, end));
+                    }
+                    *cursor = peek;
+                    break;
+                }
+
+                fields.push(inspect_field(cursor, offset + start - cursor.len())?);
+            }
+            InspectedValue::Group(Inspection { fields, error: None })
+        }
+        WireType::EndGroup => return Err(format!("end group {} without a start group", number)),
+    };
+
+    Ok(InspectedField {
+        offset,
+        number,
+        wire_type,
+        value,
+    })
+}
+
+fn take<'a>(cursor: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if cursor.len() < len {
+        return Err(format!("value needs {} bytes, only {} left", len, cursor.len()));
+    }
+
+    let (value, rest) = cursor.split_at(len);
+    *cursor = rest;
+    Ok(value)
+}
+
+fn guess(value: &[u8], offset: usize) -> InspectedValue {
+    if let Ok(text) = std::str::from_utf8(value) {
+        if !text.chars().any(|c| c.is_control() && c != '\n' && c != '\t') {
+            return InspectedValue::String(text.to_string());
+        }
+    }
+
+    //a message that breaks part-way still shows what could be read and where it stopped
+    let nested = inspect_at(value, offset);
+    if !nested.fields.is_empty() {
+        return InspectedValue::Message(nested);
+    }
+
+    InspectedValue::Bytes(value.to_vec())
+}
+
+impl Inspection {
+    fn write(&self, f: &mut Formatter<'_>, depth: usize) -> std::fmt::Result {
+        let indent = "  ".repeat(depth);
+
+        for field in &self.fields {
+            write!(f, "{:#06x} {}{}: ", field.offset, indent, field.number)?;
+
+            match &field.value {
+                InspectedValue::Varint(value) => {
+                    write!(f, "varint {}", value)?;
+                    //the other ways to read it, if they say something different
+                    if (*value as i64) < 0 {
+                        write!(f, " (int {})", *value as i64)?;
+                    }
+                    if *value > 1 {
+                        write!(f, " (sint {})", ((value >> 1) as i64) ^ -((value & 1) as i64))?;
+                    }
+                    writeln!(f)?;
+                }
+                InspectedValue::Fixed32(value) => {
+                    writeln!(f, "fixed32 {} (int {}) (float {})", value, *value as i32, f32::from_bits(*value))?
+                }
+                InspectedValue::Fixed64(value) => {
+                    writeln!(f, "fixed64 {} (int {}) (double {})", value, *value as i64, f64::from_bits(*value))?
+                }
+                InspectedValue::String(text) => writeln!(f, "string {:?}", text)?,
+                InspectedValue::Bytes(bytes) => writeln!(f, "bytes ({}) {}", bytes.len(), hex(bytes))?,
+                InspectedValue::Message(nested) => {
+                    writeln!(f, "message {{")?;
+                    nested.write(f, depth + 1)?;
+                    writeln!(f, "{:6} {}}}", "", indent)?;
+                }
+                InspectedValue::Group(nested) => {
+                    writeln!(f, "group {{")?;
+                    nested.write(f, depth + 1)?;
+                    writeln!(f, "{:6} {}}}", "", indent)?;
+                }
+            }
+        }
+
+        if let Some(error) = &self.error {
+            //the start is enough to tell what went wrong
+            let shown = &error.remaining[..error.remaining.len().min(32)];
+            let more = if shown.len() < error.remaining.len() { " ..." } else { "" };
+
+            writeln!(
+                f,
+                "{:#06x} {}error: {}; rest of the message ({} bytes): {}{}",
+                error.offset,
+                indent,
+                error.message,
+                error.remaining.len(),
+                hex(shown),
+                more,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Inspection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.write(f, 0)
+    }
+}
+
+fn hex(buf: &[u8]) -> String {
+    buf.iter().map(|byte| format!("{:02x}", byte)).collect::<Vec<_>>().join(" ")
+}
@@ -0,0 +1,33 @@
+mod descriptor;
+mod inspect;
+pub mod path;
+#[cfg(feature = "reflect")]
+mod reflect;
+#[cfg(feature = "json")]
+mod schema;
+mod unknown;
+pub mod wire;
+#[cfg(feature = "serde")]
+mod with_unknown;
+
+pub use descriptor::{Descriptors, EnumInfo, EnumValueInfo, FieldInfo, MessageInfo};
+pub use inspect::{inspect, InspectError, InspectedField, InspectedValue, Inspection};
+#[cfg(feature = "json")]
+pub use schema::SchemaBuilder;
+pub use unknown::{EntryKey, PathSegment, UnknownField, UnknownFields};
+#[cfg(feature = "serde")]
+pub use with_unknown::{capture_unknown, CaptureUnknown, WithUnknown, UNKNOWN_FIELD_NAME};
+
+/// Defines a function returning the [`Descriptors`] of the calling crate, read from the `proto_descriptor.bin` its build script wrote to `OUT_DIR`
+#[macro_export]
+macro_rules! include_descriptors {
+    ($vis:vis fn $name:ident) => {
+        $vis fn $name() -> &'static $crate::Descriptors {
+            static DESCRIPTORS: ::std::sync::OnceLock<$crate::Descriptors> = ::std::sync::OnceLock::new();
+            DESCRIPTORS.get_or_init(|| {
+                $crate::Descriptors::decode(include_bytes!(concat!(env!("OUT_DIR"), "/proto_descriptor.bin")))
+                    .expect("build script wrote an invalid proto_descriptor.bin")
+            })
+        }
+    };
+}
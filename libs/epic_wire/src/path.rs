@@ -0,0 +1,65 @@
+/// `name` as a json string, quotes included. Names in paths like `1["pig_king"]` or `skillData["skill_x"].damage` are quoted with it
+pub fn quote(name: &str) -> String {
+    let mut quoted = String::with_capacity(name.len() + 2);
+    quoted.push('"');
+
+    for c in name.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\u{8}' => quoted.push_str("\\b"),
+            '\u{c}' => quoted.push_str("\\f"),
+            c if c < ' ' => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+/// Reads the json string `text` starts with, returns it and what's after its closing quote
+pub fn unquote(text: &str) -> Option<(String, &str)> {
+    let mut chars = text.strip_prefix('"')?.char_indices();
+    let mut name = String::new();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Some((name, &text[index + 2..])),
+            '\\' => match chars.next()?.1 {
+                'n' => name.push('\n'),
+                'r' => name.push('\r'),
+                't' => name.push('\t'),
+                'b' => name.push('\u{8}'),
+                'f' => name.push('\u{c}'),
+                'u' => {
+                    let high = hex4(&mut chars)?;
+                    let c = match high {
+                        //surrogate pair, the low half has to follow right away
+                        0xd800..=0xdbff => {
+                            if (chars.next()?.1, chars.next()?.1) != ('\\', 'u') {
+                                return None;
+                            }
+                            let low = hex4(&mut chars).filter(|low| (0xdc00..=0xdfff).contains(low))?;
+                            char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00))?
+                        }
+                        _ => char::from_u32(high)?,
+                    };
+                    name.push(c);
+                }
+                //`"`, `\` and `/`, older paths escaped nothing else either
+                c => name.push(c),
+            },
+            c => name.push(c),
+        }
+    }
+
+    None
+}
+
+fn hex4(chars: &mut std::str::CharIndices) -> Option<u32> {
+    (0..4).try_fold(0, |value, _| Some(value * 16 + chars.next()?.1.to_digit(16)?))
+}
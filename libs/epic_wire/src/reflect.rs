@@ -0,0 +1,438 @@
+//runtime decoding and encoding of messages only known from a descriptor set, done on the Descriptors the rest of the
+//crate uses instead of with prost-reflect. Its dynamic messages keep map fields in a HashMap, the balancing data
+//relies on map entries (loot, offer contents...) keeping their order like the IndexMap maps of the generated types do.
+//The json is the pbjson form of the generated types with records the descriptors don't know in `_unknown`, so typed
+//and reflected output can stand in for each other. tests/reflect.rs holds it to that for every field type
+
+use std::path::Path;
+use std::process::Command;
+
+use anyhow::{anyhow, bail};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use prost::encoding::{decode_varint, encode_key, encode_varint, WireType};
+use prost_types::field_descriptor_proto::Type;
+use serde_json::{Map, Number, Value};
+
+use crate::descriptor::{Descriptors, FieldInfo, MessageInfo};
+use crate::unknown::UnknownFields;
+use crate::wire::records;
+use crate::with_unknown::UNKNOWN_FIELD_NAME;
+
+impl Descriptors {
+    /// Loads descriptors at runtime, `.proto` files are compiled with protoc (`PROTOC` or the one on the path),
+    /// anything else is read as a descriptor set made with `protoc --include_imports -o`
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let descriptor_set = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("proto") => compile(path)?,
+            _ => std::fs::read(path)?,
+        };
+
+        Ok(Self::decode(&descriptor_set)?)
+    }
+
+    /// Decodes `buf` as `message` into the same json form pbjson writes for the generated types,
+    /// records the descriptors don't know end up in `_unknown`
+    pub fn decode_dynamic(&self, message: &str, buf: &[u8]) -> anyhow::Result<Value> {
+        let info = self.find_message(message).ok_or_else(|| anyhow!("no message named {}", message))?;
+
+        let mut value = decode_message(self, info, buf)?;
+        let unknown = UnknownFields::extract(self, &info.name, buf)?;
+        if let (false, Value::Object(fields)) = (unknown.is_empty(), &mut value) {
+            fields.insert(UNKNOWN_FIELD_NAME.to_string(), serde_json::to_value(&unknown)?);
+        }
+
+        Ok(value)
+    }
+
+    /// Encodes the json form of `message` back, the reverse of [`Descriptors::decode_dynamic`]
+    pub fn encode_dynamic(&self, message: &str, value: &Value) -> anyhow::Result<Vec<u8>> {
+        let info = self.find_message(message).ok_or_else(|| anyhow!("no message named {}", message))?;
+
+        let unknown = match value.get(UNKNOWN_FIELD_NAME) {
+            Some(unknown) => serde_json::from_value::<UnknownFields>(unknown.clone())?,
+            None => UnknownFields::default(),
+        };
+
+        let mut buf = Vec::new();
+        encode_message(self, info, value, &mut buf, true)?;
+
+        Ok(unknown.merge(self, &info.name, &buf)?)
+    }
+}
+
+fn compile(path: &Path) -> anyhow::Result<Vec<u8>> {
+    let protoc = std::env::var_os("PROTOC").unwrap_or_else(|| "protoc".into());
+    let output = std::env::temp_dir().join(format!("epic_wire_{}.bin", std::process::id()));
+    let include = path.parent().filter(|parent| !parent.as_os_str().is_empty()).unwrap_or(Path::new("."));
+
+    let result = Command::new(&protoc)
+        .arg("--include_imports")
+        .arg("--include_source_info")
+        .arg(format!("--proto_path={}", include.display()))
+        .arg(format!("--descriptor_set_out={}", output.display()))
+        .arg(path)
+        .output()
+        .map_err(|e| anyhow!("couldn't run {}: {}, set PROTOC or use a descriptor set", protoc.to_string_lossy(), e))?;
+
+    if !result.status.success() {
+        bail!("protoc failed on {}:\n{}", path.display(), String::from_utf8_lossy(&result.stderr).trim());
+    }
+
+    let descriptor_set = std::fs::read(&output)?;
+    let _ = std::fs::remove_file(&output);
+
+    Ok(descriptor_set)
+}
+
+fn decode_message(descriptors: &Descriptors, message: &MessageInfo, buf: &[u8]) -> anyhow::Result<Value> {
+    let mut fields = Map::new();
+
+    for record in records(buf)? {
+        let Some(field) = message.field(record.number) else {
+            continue;
+        };
+        let name = field.json_name();
+
+        if let Some(entry) = map_entry(descriptors, field) {
+            let (key, value) = decode_entry(descriptors, entry, record.value)?;
+            let map = fields.entry(name).or_insert_with(|| Value::Object(Map::new()));
+            if let Value::Object(map) = map {
+                map.insert(key, value);
+            }
+        } else if field.repeated {
+            let items = fields.entry(name).or_insert_with(|| Value::Array(Vec::new()));
+            let Value::Array(items) = items else {
+                continue;
+            };
+
+            //packed and unpacked are both accepted, whatever the descriptor says
+            if record.wire_type == WireType::LengthDelimited && is_scalar(field) {
+                let mut packed = record.value;
+                while !packed.is_empty() {
+                    let (value, rest) = split_scalar(field, packed)?;
+                    items.push(decode_value(descriptors, message, field, scalar_wire_type(field), value)?);
+                    packed = rest;
+                }
+            } else {
+                items.push(decode_value(descriptors, message, field, record.wire_type, record.value)?);
+            }
+        } else {
+            fields.insert(name, decode_value(descriptors, message, field, record.wire_type, record.value)?);
+        }
+    }
+
+    Ok(Value::Object(fields))
+}
+
+//map keys are always strings in json
+fn decode_entry(descriptors: &Descriptors, entry: &MessageInfo, buf: &[u8]) -> anyhow::Result<(String, Value)> {
+    let decoded = decode_message(descriptors, entry, buf)?;
+
+    let key = match decoded.get("key") {
+        Some(Value::String(key)) => key.clone(),
+        Some(key) => key.to_string(),
+        None => String::new(),
+    };
+    let value = decoded.get("value").cloned().unwrap_or(Value::Null);
+
+    Ok((key, value))
+}
+
+fn decode_value(
+    descriptors: &Descriptors,
+    message: &MessageInfo,
+    field: &FieldInfo,
+    wire_type: WireType,
+    value: &[u8],
+) -> anyhow::Result<Value> {
+    if wire_type != scalar_wire_type(field) {
+        bail!("{}.{} has wire type {:?}, expected {:?}", message.name, field.name, wire_type, scalar_wire_type(field));
+    }
+
+    let varint = || decode_varint(&mut &value[..]);
+    let fixed32 = || u32::from_le_bytes(value.try_into().unwrap_or_default());
+    let fixed64 = || u64::from_le_bytes(value.try_into().unwrap_or_default());
+
+    Ok(match field.field_type {
+        Type::Int32 => Value::from(varint()? as i32),
+        Type::Uint32 => Value::from(varint()? as u32),
+        Type::Sint32 => Value::from(zigzag(varint()?) as i32),
+        //64 bit integers are strings, same as pbjson
+        Type::Int64 => Value::from((varint()? as i64).to_string()),
+        Type::Uint64 => Value::from(varint()?.to_string()),
+        Type::Sint64 => Value::from(zigzag(varint()?).to_string()),
+        Type::Fixed32 => Value::from(fixed32()),
+        Type::Sfixed32 => Value::from(fixed32() as i32),
+        Type::Fixed64 => Value::from(fixed64().to_string()),
+        Type::Sfixed64 => Value::from((fixed64() as i64).to_string()),
+        Type::Float => float(f32::from_bits(fixed32()).to_string().parse()?),
+        Type::Double => float(f64::from_bits(fixed64())),
+        Type::Bool => Value::from(varint()? != 0),
+        Type::Enum => {
+            let number = varint()? as i32;
+            let name = field
+                .enum_type()
+                .and_then(|name| descriptors.enum_type(name))
+                .and_then(|info| info.values.iter().find(|value| value.number == number));
+
+            match name {
+                Some(name) => Value::from(name.name.as_str()),
+                None => Value::from(number),
+            }
+        }
+        Type::String => Value::from(std::str::from_utf8(value).map_err(|e| anyhow!("{}.{}: {}", message.name, field.name, e))?),
+        Type::Bytes => Value::from(BASE64_STANDARD.encode(value)),
+        Type::Message => {
+            let nested = field
+                .message_type()
+                .and_then(|name| descriptors.message(name))
+                .ok_or_else(|| anyhow!("{}.{} has an unknown message type", message.name, field.name))?;
+            decode_message(descriptors, nested, value)?
+        }
+        Type::Group => bail!("{}.{} is a group, groups aren't supported", message.name, field.name),
+    })
+}
+
+fn encode_message(
+    descriptors: &Descriptors,
+    message: &MessageInfo,
+    value: &Value,
+    buf: &mut Vec<u8>,
+    root: bool,
+) -> anyhow::Result<()> {
+    let Value::Object(fields) = value else {
+        bail!("{} has to be an object", message.name);
+    };
+
+    //pbjson rejects fields it doesn't know, so do we
+    for name in fields.keys() {
+        let known = message.fields.iter().any(|field| field.json_name() == *name || field.name == *name);
+        if !(known || root && name == UNKNOWN_FIELD_NAME) {
+            bail!("{} has no field {}", message.name, name);
+        }
+    }
+
+    //declaration order, the same one the generated code writes in
+    for field in &message.fields {
+        let value = match fields.get(&field.json_name()).or_else(|| fields.get(&field.name)) {
+            None | Some(Value::Null) => continue,
+            Some(value) => value,
+        };
+
+        if let Some(entry) = map_entry(descriptors, field) {
+            let Value::Object(map) = value else {
+                bail!("{}.{} has to be an object", message.name, field.name);
+            };
+
+            for (key, value) in map {
+                let key_field = entry.field(1).ok_or_else(|| anyhow!("{} has no key", entry.name))?;
+                let value_field = entry.field(2).ok_or_else(|| anyhow!("{} has no value", entry.name))?;
+
+                let mut entry_buf = Vec::new();
+                encode_field(descriptors, entry, key_field, &Value::from(key.as_str()), &mut entry_buf)?;
+                encode_field(descriptors, entry, value_field, value, &mut entry_buf)?;
+
+                encode_key(field.number, WireType::LengthDelimited, buf);
+                encode_varint(entry_buf.len() as u64, buf);
+                buf.extend_from_slice(&entry_buf);
+            }
+        } else if field.repeated {
+            let Value::Array(items) = value else {
+                bail!("{}.{} has to be a list", message.name, field.name);
+            };
+
+            if field.packed {
+                if items.is_empty() {
+                    continue;
+                }
+
+                let mut packed = Vec::new();
+                for item in items {
+                    encode_scalar(descriptors, message, field, item, &mut packed)?;
+                }
+                encode_key(field.number, WireType::LengthDelimited, buf);
+                encode_varint(packed.len() as u64, buf);
+                buf.extend_from_slice(&packed);
+            } else {
+                for item in items {
+                    encode_field(descriptors, message, field, item, buf)?;
+                }
+            }
+        } else {
+            encode_field(descriptors, message, field, value, buf)?;
+        }
+    }
+
+    Ok(())
+}
+
+//a single record, key included
+fn encode_field(
+    descriptors: &Descriptors,
+    message: &MessageInfo,
+    field: &FieldInfo,
+    value: &Value,
+    buf: &mut Vec<u8>,
+) -> anyhow::Result<()> {
+    encode_key(field.number, scalar_wire_type(field), buf);
+
+    match field.field_type {
+        Type::Message => {
+            let nested = field
+                .message_type()
+                .and_then(|name| descriptors.message(name))
+                .ok_or_else(|| anyhow!("{}.{} has an unknown message type", message.name, field.name))?;
+
+            let mut nested_buf = Vec::new();
+            encode_message(descriptors, nested, value, &mut nested_buf, false)?;
+            encode_varint(nested_buf.len() as u64, buf);
+            buf.extend_from_slice(&nested_buf);
+        }
+        Type::String | Type::Bytes => {
+            let bytes = match (field.field_type, value) {
+                (Type::String, Value::String(string)) => string.as_bytes().to_vec(),
+                (Type::Bytes, Value::String(string)) => BASE64_STANDARD.decode(string)?,
+                _ => bail!("{}.{} has to be a string", message.name, field.name),
+            };
+            encode_varint(bytes.len() as u64, buf);
+            buf.extend_from_slice(&bytes);
+        }
+        _ => encode_scalar(descriptors, message, field, value, buf)?,
+    }
+
+    Ok(())
+}
+
+//value of a number, bool or enum field without the key, also how packed values are written
+fn encode_scalar(
+    descriptors: &Descriptors,
+    message: &MessageInfo,
+    field: &FieldInfo,
+    value: &Value,
+    buf: &mut Vec<u8>,
+) -> anyhow::Result<()> {
+    let invalid = || anyhow!("{}.{} can't be {}", message.name, field.name, value);
+
+    //numbers can be strings, pbjson reads both
+    let integer = || -> anyhow::Result<i128> {
+        match value {
+            Value::Number(number) => number
+                .as_i64()
+                .map(i128::from)
+                .or_else(|| number.as_u64().map(i128::from))
+                .ok_or_else(invalid),
+            Value::String(string) => string.parse().map_err(|_| invalid()),
+            _ => Err(invalid()),
+        }
+    };
+    let number = || -> anyhow::Result<f64> {
+        match value {
+            Value::Number(number) => number.as_f64().ok_or_else(invalid),
+            Value::String(string) => match string.as_str() {
+                "NaN" => Ok(f64::NAN),
+                "Infinity" => Ok(f64::INFINITY),
+                "-Infinity" => Ok(f64::NEG_INFINITY),
+                string => string.parse().map_err(|_| invalid()),
+            },
+            _ => Err(invalid()),
+        }
+    };
+
+    match field.field_type {
+        Type::Int32 => encode_varint(fit::<i32>(integer()?, invalid)? as i64 as u64, buf),
+        Type::Int64 => encode_varint(fit::<i64>(integer()?, invalid)? as u64, buf),
+        Type::Uint32 => encode_varint(fit::<u32>(integer()?, invalid)? as u64, buf),
+        Type::Uint64 => encode_varint(fit::<u64>(integer()?, invalid)?, buf),
+        Type::Sint32 => encode_varint(unzigzag(fit::<i32>(integer()?, invalid)? as i64), buf),
+        Type::Sint64 => encode_varint(unzigzag(fit::<i64>(integer()?, invalid)?), buf),
+        Type::Fixed32 => buf.extend_from_slice(&fit::<u32>(integer()?, invalid)?.to_le_bytes()),
+        Type::Sfixed32 => buf.extend_from_slice(&fit::<i32>(integer()?, invalid)?.to_le_bytes()),
+        Type::Fixed64 => buf.extend_from_slice(&fit::<u64>(integer()?, invalid)?.to_le_bytes()),
+        Type::Sfixed64 => buf.extend_from_slice(&fit::<i64>(integer()?, invalid)?.to_le_bytes()),
+        Type::Float => buf.extend_from_slice(&(number()? as f32).to_le_bytes()),
+        Type::Double => buf.extend_from_slice(&number()?.to_le_bytes()),
+        Type::Bool => match value {
+            Value::Bool(value) => encode_varint(*value as u64, buf),
+            //map keys
+            Value::String(string) => encode_varint(string.parse::<bool>().map_err(|_| invalid())? as u64, buf),
+            _ => return Err(invalid()),
+        },
+        Type::Enum => {
+            let number = match value {
+                Value::String(name) => field
+                    .enum_type()
+                    .and_then(|name| descriptors.enum_type(name))
+                    .and_then(|info| info.values.iter().find(|value| value.name == *name))
+                    .map(|value| value.number)
+                    .ok_or_else(invalid)?,
+                _ => fit::<i32>(integer()?, invalid)?,
+            };
+            encode_varint(number as i64 as u64, buf);
+        }
+        Type::String | Type::Bytes | Type::Message | Type::Group => return Err(invalid()),
+    }
+
+    Ok(())
+}
+
+fn fit<T: TryFrom<i128>>(value: i128, invalid: impl Fn() -> anyhow::Error) -> anyhow::Result<T> {
+    T::try_from(value).map_err(|_| invalid())
+}
+
+fn map_entry<'a>(descriptors: &'a Descriptors, field: &FieldInfo) -> Option<&'a MessageInfo> {
+    field
+        .message_type()
+        .and_then(|name| descriptors.message(name))
+        .filter(|message| message.map_entry)
+}
+
+fn is_scalar(field: &FieldInfo) -> bool {
+    !matches!(field.field_type, Type::String | Type::Bytes | Type::Message | Type::Group)
+}
+
+fn scalar_wire_type(field: &FieldInfo) -> WireType {
+    match field.field_type {
+        Type::Fixed32 | Type::Sfixed32 | Type::Float => WireType::ThirtyTwoBit,
+        Type::Fixed64 | Type::Sfixed64 | Type::Double => WireType::SixtyFourBit,
+        Type::String | Type::Bytes | Type::Message => WireType::LengthDelimited,
+        Type::Group => WireType::StartGroup,
+        _ => WireType::Varint,
+    }
+}
+
+//first packed value and the rest
+fn split_scalar<'a>(field: &FieldInfo, buf: &'a [u8]) -> anyhow::Result<(&'a [u8], &'a [u8])> {
+    let len = match scalar_wire_type(field) {
+        WireType::ThirtyTwoBit => 4,
+        WireType::SixtyFourBit => 8,
+        _ => {
+            let mut cursor = buf;
+            decode_varint(&mut cursor)?;
+            buf.len() - cursor.len()
+        }
+    };
+
+    if buf.len() < len {
+        bail!("{} is cut off", field.name);
+    }
+    Ok(buf.split_at(len))
+}
+
+fn zigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn unzigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+//json has no NaN or infinity, pbjson writes them as strings
+fn float(value: f64) -> Value {
+    match Number::from_f64(value) {
+        Some(number) => Value::Number(number),
+        None if value.is_nan() => Value::from("NaN"),
+        None if value > 0.0 => Value::from("Infinity"),
+        None => Value::from("-Infinity"),
+    }
+}
@@ -0,0 +1,267 @@
+use prost_types::field_descriptor_proto::Type;
+use serde_json::{json, Map, Value};
+
+use crate::descriptor::{Descriptors, EnumInfo, FieldInfo, MessageInfo};
+use crate::with_unknown::UNKNOWN_FIELD_NAME;
+
+const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+const UNKNOWN_DEFINITION: &str = "UnknownFields";
+//epic_prefs writes these as rfc 3339 strings and not as the message
+const DATE_TIME_MESSAGE: &str = "abepic.bcl.DateTime";
+
+/// Builds JSON Schema documents for the json form pbjson reads and writes, messages and enums
+/// something refers to end up in `$defs` under their fully qualified name.
+/// The proto field names and enum numbers pbjson also reads are allowed, the proto names are marked deprecated
+pub struct SchemaBuilder<'a> {
+    descriptors: &'a Descriptors,
+    definitions: Map<String, Value>,
+    date_fields: &'a [(&'a str, &'a str)],
+}
+
+impl Descriptors {
+    /// Schema of a whole json document of the message, like the ones [`crate::WithUnknown`] writes
+    pub fn json_schema(&self, message: &str) -> Option<Value> {
+        let mut builder = SchemaBuilder::new(self);
+        let root = builder.message(message)?;
+        let root = builder.with_unknown(root);
+
+        Some(builder.build(message.trim_start_matches('.'), root))
+    }
+}
+
+impl<'a> SchemaBuilder<'a> {
+    pub fn new(descriptors: &'a Descriptors) -> Self {
+        Self {
+            descriptors,
+            definitions: Map::new(),
+            date_fields: &[],
+        }
+    }
+
+    /// Integer fields that can be RFC 3339 dates too, as fully qualified message name and json name of the field
+    pub fn date_fields(mut self, date_fields: &'a [(&'a str, &'a str)]) -> Self {
+        self.date_fields = date_fields;
+        self
+    }
+
+    /// Schema of the message itself, everything it uses is added to the definitions
+    pub fn message(&mut self, name: &str) -> Option<Value> {
+        let message = self.descriptors.message(name)?;
+        Some(self.message_schema(message))
+    }
+
+    /// `$ref` to the message, it's added to the definitions if it isn't there yet
+    pub fn reference(&mut self, name: &str) -> Option<Value> {
+        let message = self.descriptors.message(name)?;
+        Some(self.message_reference(message))
+    }
+
+    /// Allows the `_unknown` section on an object schema
+    pub fn with_unknown(&mut self, mut schema: Value) -> Value {
+        if !self.definitions.contains_key(UNKNOWN_DEFINITION) {
+            self.definitions.insert(UNKNOWN_DEFINITION.to_string(), unknown_schema());
+        }
+
+        if let Some(properties) = schema.get_mut("properties").and_then(Value::as_object_mut) {
+            properties.insert(
+                UNKNOWN_FIELD_NAME.to_string(),
+                json!({ "$ref": format!("#/$defs/{}", UNKNOWN_DEFINITION) }),
+            );
+        }
+
+        schema
+    }
+
+    /// Schema document with `root` at the top level
+    pub fn build(self, title: &str, root: Value) -> Value {
+        let mut schema = Map::new();
+        schema.insert("$schema".to_string(), Value::from(DRAFT));
+        schema.insert("title".to_string(), Value::from(title));
+
+        if let Value::Object(root) = root {
+            schema.extend(root);
+        }
+        if !self.definitions.is_empty() {
+            schema.insert("$defs".to_string(), Value::Object(self.definitions));
+        }
+
+        Value::Object(schema)
+    }
+
+    fn message_schema(&mut self, message: &MessageInfo) -> Value {
+        let mut properties = Map::new();
+        for field in &message.fields {
+            let date = self
+                .date_fields
+                .iter()
+                .any(|(date_message, date_field)| *date_message == message.name && *date_field == field.json_name());
+            let schema = self.field_schema(field, date);
+            //pbjson reads the proto name too, editors strike it through so the json name is what gets completed
+            if field.json_name() != field.name {
+                let mut proto_name = schema.clone();
+                if let Some(object) = proto_name.as_object_mut() {
+                    object.insert("deprecated".to_string(), Value::Bool(true));
+                }
+                properties.insert(field.name.clone(), proto_name);
+            }
+            properties.insert(field.json_name(), schema);
+        }
+
+        let mut schema = Map::new();
+        schema.insert("type".to_string(), Value::from("object"));
+        schema.insert(
+            "description".to_string(),
+            Value::from(message.comment.clone().unwrap_or_else(|| format!("message {}", message.name))),
+        );
+        schema.insert("properties".to_string(), Value::Object(properties));
+        //pbjson rejects fields it doesn't know, so typos are errors here too
+        schema.insert("additionalProperties".to_string(), Value::Bool(false));
+
+        Value::Object(schema)
+    }
+
+    fn message_reference(&mut self, message: &MessageInfo) -> Value {
+        if !self.definitions.contains_key(&message.name) {
+            //placeholder first, messages can contain themselves
+            self.definitions.insert(message.name.clone(), Value::Null);
+            let schema = self.message_schema(message);
+            self.definitions.insert(message.name.clone(), schema);
+        }
+
+        json!({ "$ref": format!("#/$defs/{}", message.name) })
+    }
+
+    fn enum_reference(&mut self, enum_type: &EnumInfo) -> Value {
+        if !self.definitions.contains_key(&enum_type.name) {
+            let mut schema = Map::new();
+            schema.insert("type".to_string(), json!(["string", "integer"]));
+            schema.insert(
+                "description".to_string(),
+                Value::from(enum_type.comment.clone().unwrap_or_else(|| format!("enum {}", enum_type.name))),
+            );
+            //names are what gets written, the numbers are read too
+            let names = enum_type.values.iter().map(|value| Value::from(value.name.as_str()));
+            let numbers = enum_type.values.iter().map(|value| Value::from(value.number));
+            schema.insert("enum".to_string(), names.chain(numbers).collect());
+            //not part of the standard, vscode shows them next to the values
+            if enum_type.values.iter().any(|value| value.comment.is_some()) {
+                let descriptions = enum_type
+                    .values
+                    .iter()
+                    .map(|value| Value::from(value.comment.clone().unwrap_or_default()));
+                schema.insert("enumDescriptions".to_string(), descriptions.clone().chain(descriptions).collect());
+            }
+
+            self.definitions.insert(enum_type.name.clone(), Value::Object(schema));
+        }
+
+        json!({ "$ref": format!("#/$defs/{}", enum_type.name) })
+    }
+
+    fn field_schema(&mut self, field: &FieldInfo, date: bool) -> Value {
+        let map_entry = field
+            .message_type()
+            .and_then(|name| self.descriptors.message(name))
+            .filter(|message| message.map_entry);
+
+        let mut schema = match map_entry {
+            //keys are always strings in json
+            Some(entry) => {
+                let value = match entry.field_by_name("value") {
+                    Some(value) => self.value_schema(value),
+                    None => json!({}),
+                };
+                json!({ "type": "object", "additionalProperties": value })
+            }
+            None if field.repeated => json!({ "type": "array", "items": self.date_or_value_schema(field, date) }),
+            None => self.date_or_value_schema(field, date),
+        };
+
+        let description = match &field.comment {
+            Some(comment) => format!("{}\n\n{} = {}", comment, field.name, field.number),
+            None => format!("{} = {}", field.name, field.number),
+        };
+
+        let default = self.default_value(field);
+        //siblings of $ref are fine since 2019-09
+        if let Some(object) = schema.as_object_mut() {
+            object.insert("description".to_string(), Value::from(description));
+            if let Some(default) = default {
+                object.insert("default".to_string(), default);
+            }
+        }
+
+        schema
+    }
+
+    //dates are written as rfc 3339 strings when asked to, the seconds are read too
+    fn date_or_value_schema(&mut self, field: &FieldInfo, date: bool) -> Value {
+        let value = self.value_schema(field);
+        match date {
+            true => json!({ "anyOf": [value, { "type": "string", "format": "date-time" }] }),
+            false => value,
+        }
+    }
+
+    //schema of a single value of the field, without repeated/map
+    fn value_schema(&mut self, field: &FieldInfo) -> Value {
+        match field.field_type {
+            Type::Message | Type::Group => match field.message_type().and_then(|name| self.descriptors.message(name)) {
+                Some(message) if message.name == DATE_TIME_MESSAGE => json!({ "type": "string", "format": "date-time" }),
+                Some(message) => self.message_reference(message),
+                None => json!({ "type": "object" }),
+            },
+            Type::Enum => match field.enum_type().and_then(|name| self.descriptors.enum_type(name)) {
+                Some(enum_type) => self.enum_reference(enum_type),
+                None => json!({ "type": ["string", "integer"] }),
+            },
+            Type::Double | Type::Float => {
+                json!({ "anyOf": [{ "type": "number" }, { "enum": ["NaN", "Infinity", "-Infinity"] }] })
+            }
+            Type::Int32 | Type::Sint32 | Type::Sfixed32 => json!({ "type": "integer" }),
+            Type::Uint32 | Type::Fixed32 => json!({ "type": "integer", "minimum": 0 }),
+            //64 bit integers are written as strings, numbers are read too
+            Type::Int64 | Type::Sint64 | Type::Sfixed64 => json!({ "type": ["string", "integer"], "pattern": "^-?[0-9]+$" }),
+            Type::Uint64 | Type::Fixed64 => json!({ "type": ["string", "integer"], "pattern": "^[0-9]+$", "minimum": 0 }),
+            Type::Bool => json!({ "type": "boolean" }),
+            Type::String => json!({ "type": "string" }),
+            Type::Bytes => json!({ "type": "string", "contentEncoding": "base64" }),
+        }
+    }
+
+    //`[default = ...]` the way the value is written in json
+    fn default_value(&self, field: &FieldInfo) -> Option<Value> {
+        let default = field.default_value.as_deref()?;
+
+        match field.field_type {
+            Type::String | Type::Bytes | Type::Enum => Some(Value::from(default)),
+            Type::Bool => default.parse::<bool>().ok().map(Value::from),
+            Type::Int64 | Type::Sint64 | Type::Sfixed64 | Type::Uint64 | Type::Fixed64 => Some(Value::from(default)),
+            Type::Double | Type::Float => match default.parse::<f64>() {
+                Ok(number) if number.is_finite() => Some(Value::from(number)),
+                _ => Some(Value::from(default)),
+            },
+            Type::Int32 | Type::Sint32 | Type::Sfixed32 | Type::Uint32 | Type::Fixed32 => {
+                default.parse::<i64>().ok().map(Value::from)
+            }
+            Type::Message | Type::Group => None,
+        }
+    }
+}
+
+fn unknown_schema() -> Value {
+    json!({
+        "type": "array",
+        "description": "Fields that are not in the proto files yet, written back as is on encode",
+        "items": {
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "position": { "type": "integer", "minimum": 0 },
+                "data": { "type": "string", "contentEncoding": "base64" }
+            },
+            "required": ["path", "position", "data"],
+            "additionalProperties": false
+        }
+    })
+}
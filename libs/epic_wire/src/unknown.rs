@@ -0,0 +1,244 @@
+use std::collections::HashMap;
+
+use prost::{
+    encoding::{decode_varint, WireType},
+    DecodeError,
+};
+
+use crate::descriptor::{Descriptors, FieldInfo, MessageInfo};
+use crate::wire::{records, write_length_delimited};
+
+//repeated entries with this field are matched by it instead of their position
+const NAME_ID_FIELD: &str = "name_id";
+
+/// Field records the proto files don't know about, collected from a message and every message nested in it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UnknownFields {
+    fields: Vec<UnknownField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownField {
+    /// Every message field leading from the root message to the one holding the record
+    pub path: Vec<PathSegment>,
+    /// How many known records came before this one in its message
+    pub position: usize,
+    /// The whole record, key included
+    pub record: Vec<u8>,
+}
+
+/// A nested message in the message holding it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PathSegment {
+    pub field: u32,
+    pub entry: EntryKey,
+}
+
+/// Which record of a field a nested message is, entries are matched by id where they have one
+/// so records stay with their entry when a list or map is reordered
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EntryKey {
+    /// Position among the records of the field, for singular fields and entries without an id
+    Index(usize),
+    /// `name_id` of a repeated entry or key of a map entry, and which entry with that id it is, counting from 1
+    Id(String, usize),
+}
+
+impl UnknownFields {
+    pub fn new(fields: Vec<UnknownField>) -> Self {
+        Self { fields }
+    }
+
+    /// Walks `buf` as the message `message_name` and collects every record the descriptors don't have a field for
+    pub fn extract(
+        descriptors: &Descriptors,
+        message_name: &str,
+        buf: &[u8],
+    ) -> Result<Self, DecodeError> {
+        let message = lookup(descriptors, message_name)?;
+
+        let mut fields = Vec::new();
+        extract_message(descriptors, message, buf, &mut Vec::new(), &mut fields)?;
+
+        Ok(Self { fields })
+    }
+
+    /// Puts the unknown records back into `encoded`, an encoding of the message `message_name` without them
+    pub fn merge(
+        &self,
+        descriptors: &Descriptors,
+        message_name: &str,
+        encoded: &[u8],
+    ) -> Result<Vec<u8>, DecodeError> {
+        if self.fields.is_empty() {
+            return Ok(encoded.to_vec());
+        }
+
+        let message = lookup(descriptors, message_name)?;
+        merge_message(descriptors, message, encoded, 0, &self.fields.iter().collect::<Vec<_>>())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &UnknownField> {
+        self.fields.iter()
+    }
+}
+
+fn lookup<'a>(descriptors: &'a Descriptors, message_name: &str) -> Result<&'a MessageInfo, DecodeError> {
+    descriptors
+        .message(message_name)
+        .ok_or_else(|| DecodeError::new(format!("unknown message type {}", message_name)))
+}
+
+fn extract_message(
+    descriptors: &Descriptors,
+    message: &MessageInfo,
+    buf: &[u8],
+    path: &mut Vec<PathSegment>,
+    fields: &mut Vec<UnknownField>,
+) -> Result<(), DecodeError> {
+    let mut position = 0;
+    let mut segments = Segments::default();
+
+    for record in records(buf)? {
+        let Some(field) = message.field(record.number) else {
+            fields.push(UnknownField {
+                path: path.clone(),
+                position,
+                record: record.raw.to_vec(),
+            });
+            continue;
+        };
+
+        position += 1;
+
+        if let (Some(nested), WireType::LengthDelimited) = (field.message_type(), record.wire_type) {
+            let nested = lookup(descriptors, nested)?;
+
+            path.push(segments.next(field, nested, record.value).0);
+            extract_message(descriptors, nested, record.value, path, fields)?;
+            path.pop();
+        }
+    }
+
+    Ok(())
+}
+
+//`fields` are the ones inside this message, `depth` is the length of its path
+fn merge_message(
+    descriptors: &Descriptors,
+    message: &MessageInfo,
+    buf: &[u8],
+    depth: usize,
+    fields: &[&UnknownField],
+) -> Result<Vec<u8>, DecodeError> {
+    if fields.is_empty() {
+        return Ok(buf.to_vec());
+    }
+
+    let mut here = fields.iter().filter(|field| field.path.len() == depth).peekable();
+    let mut output = Vec::with_capacity(buf.len());
+    let mut segments = Segments::default();
+
+    for (position, record) in records(buf)?.into_iter().enumerate() {
+        while let Some(field) = here.next_if(|field| field.position <= position) {
+            output.extend_from_slice(&field.record);
+        }
+
+        let nested = message
+            .field(record.number)
+            .and_then(|field| Some((field, field.message_type()?)))
+            .filter(|_| record.wire_type == WireType::LengthDelimited);
+
+        match nested {
+            Some((field, nested)) => {
+                let nested = lookup(descriptors, nested)?;
+
+                //paths written before entries were matched by id still point at them by index
+                let (segment, index) = segments.next(field, nested, record.value);
+                let inside = fields
+                    .iter()
+                    .copied()
+                    .filter(|unknown| {
+                        unknown.path.get(depth).is_some_and(|at| {
+                            *at == segment || (at.field == segment.field && at.entry == EntryKey::Index(index))
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                let value = merge_message(descriptors, nested, record.value, depth + 1, &inside)?;
+
+                write_length_delimited(&mut output, record.key, &value);
+            }
+            None => output.extend_from_slice(record.raw),
+        }
+    }
+
+    //anything positioned after the last known record, or in a message that lost records since
+    for field in here {
+        output.extend_from_slice(&field.record);
+    }
+
+    Ok(output)
+}
+
+//hands out the path segments of the nested messages of one message, extracting and merging have to agree on them
+#[derive(Default)]
+struct Segments {
+    occurrences: HashMap<u32, usize>,
+    ids: HashMap<(u32, String), usize>,
+}
+
+impl Segments {
+    /// The segment of the next record of `field` and its position among the records of the field
+    fn next(&mut self, field: &FieldInfo, nested: &MessageInfo, value: &[u8]) -> (PathSegment, usize) {
+        let occurrence = self.occurrences.entry(field.number).or_default();
+        let index = *occurrence;
+        *occurrence += 1;
+
+        let entry = match entry_id(field, nested, value) {
+            Some(id) => {
+                let count = self.ids.entry((field.number, id.clone())).or_default();
+                *count += 1;
+                EntryKey::Id(id, *count)
+            }
+            None => EntryKey::Index(index),
+        };
+
+        let segment = PathSegment {
+            field: field.number,
+            entry,
+        };
+        (segment, index)
+    }
+}
+
+//map entries by their key, repeated entries by their `name_id`, the way diff and patch match them
+fn entry_id(field: &FieldInfo, nested: &MessageInfo, value: &[u8]) -> Option<String> {
+    let id_field = if nested.map_entry {
+        nested.field(1)?
+    } else if field.repeated {
+        nested.field_by_name(NAME_ID_FIELD).filter(|id_field| id_field.is_string())?
+    } else {
+        return None;
+    };
+
+    //the last record wins, same as when decoding
+    let record = records(value)
+        .ok()?
+        .into_iter()
+        .rev()
+        .find(|record| record.number == id_field.number)?;
+
+    match record.wire_type {
+        WireType::LengthDelimited => String::from_utf8(record.value.to_vec()).ok(),
+        WireType::Varint => decode_varint(&mut &*record.value).ok().map(|key| key.to_string()),
+        _ => None,
+    }
+}
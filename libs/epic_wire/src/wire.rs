@@ -0,0 +1,163 @@
+use prost::{
+    encoding::{decode_key, decode_varint, encode_varint, WireType},
+    DecodeError,
+};
+
+/// A single field record of an encoded message
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'a> {
+    pub number: u32,
+    pub wire_type: WireType,
+    /// The encoded field key
+    pub key: &'a [u8],
+    /// The value without the key, length delimited values have their length prefix stripped
+    pub value: &'a [u8],
+    /// The whole record, key included
+    pub raw: &'a [u8],
+}
+
+/// Splits an encoded message into its top level field records, each slice includes the field key
+pub fn split_records(mut buf: &[u8]) -> Result<Vec<&[u8]>, DecodeError> {
+    let mut records = Vec::new();
+
+    while !buf.is_empty() {
+        let len = record_len(buf)?;
+        let (record, rest) = buf.split_at(len);
+        records.push(record);
+        buf = rest;
+    }
+
+    Ok(records)
+}
+
+/// Same as [`split_records`], but with every record already taken apart
+pub fn records(buf: &[u8]) -> Result<Vec<Record<'_>>, DecodeError> {
+    split_records(buf)?.into_iter().map(parse_record).collect()
+}
+
+pub fn parse_record(raw: &[u8]) -> Result<Record<'_>, DecodeError> {
+    let mut cursor = raw;
+    let (number, wire_type) = decode_key(&mut cursor)?;
+    let key = &raw[..raw.len() - cursor.len()];
+
+    let value = match wire_type {
+        WireType::LengthDelimited => {
+            let len = decode_varint(&mut cursor)? as usize;
+            if cursor.len() < len {
+                return Err(DecodeError::new("buffer underflow"));
+            }
+            &cursor[..len]
+        }
+        _ => {
+            let start = cursor;
+            skip_value(&mut cursor, wire_type)?;
+            &start[..start.len() - cursor.len()]
+        }
+    };
+
+    Ok(Record {
+        number,
+        wire_type,
+        key,
+        value,
+        raw,
+    })
+}
+
+/// Length of the first record in `buf`, key included
+pub fn record_len(buf: &[u8]) -> Result<usize, DecodeError> {
+    let mut cursor = buf;
+    let (_, wire_type) = decode_key(&mut cursor)?;
+    skip_value(&mut cursor, wire_type)?;
+
+    Ok(buf.len() - cursor.len())
+}
+
+fn skip_value(cursor: &mut &[u8], wire_type: WireType) -> Result<(), DecodeError> {
+    let len = match wire_type {
+        WireType::Varint => {
+            decode_varint(cursor)?;
+            0
+        }
+        WireType::SixtyFourBit => 8,
+        WireType::ThirtyTwoBit => 4,
+        WireType::LengthDelimited => decode_varint(cursor)? as usize,
+        WireType::StartGroup => {
+            loop {
+                let (_, wire_type) = decode_key(cursor)?;
+                if wire_type == WireType::EndGroup {
+                    break;
+                }
+                skip_value(cursor, wire_type)?;
+            }
+            0
+        }
+        WireType::EndGroup => return Err(DecodeError::new("unexpected end group")),
+    };
+
+    if cursor.len() < len {
+        return Err(DecodeError::new("buffer underflow"));
+    }
+    *cursor = &cursor[len..];
+
+    Ok(())
+}
+
+/// Writes a length delimited record with the given key bytes and value
+pub fn write_length_delimited(output: &mut Vec<u8>, key: &[u8], value: &[u8]) {
+    output.extend_from_slice(key);
+    encode_varint(value.len() as u64, output);
+    output.extend_from_slice(value);
+}
+
+/// Human readable dump of an encoded message without a schema.
+/// Length delimited values are shown as strings if they are printable text, as nested messages if they parse as one and as hex otherwise,
+/// see [`crate::inspect`] for one that shows where a broken message stops
+pub fn dump(buf: &[u8]) -> Result<String, DecodeError> {
+    let mut output = String::new();
+    dump_message(&mut output, buf, 0)?;
+    Ok(output)
+}
+
+fn dump_message(output: &mut String, buf: &[u8], depth: usize) -> Result<(), DecodeError> {
+    let indent = "  ".repeat(depth);
+
+    for record in records(buf)? {
+        output.push_str(&format!("{}{}: ", indent, record.number));
+
+        match record.wire_type {
+            WireType::Varint => {
+                let value = decode_varint(&mut &record.value[..])?;
+                output.push_str(&format!("varint {}\n", value));
+            }
+            WireType::SixtyFourBit => {
+                let value = u64::from_le_bytes(record.value.try_into().unwrap_or_default());
+                output.push_str(&format!("fixed64 {} ({})\n", value, f64::from_bits(value)));
+            }
+            WireType::ThirtyTwoBit => {
+                let value = u32::from_le_bytes(record.value.try_into().unwrap_or_default());
+                output.push_str(&format!("fixed32 {} ({})\n", value, f32::from_bits(value)));
+            }
+            WireType::LengthDelimited => match std::str::from_utf8(record.value) {
+                Ok(text) if !text.chars().any(|c| c.is_control() && c != '\n' && c != '\t') => {
+                    output.push_str(&format!("string {:?}\n", text));
+                }
+                _ if !record.value.is_empty() && records(record.value).is_ok() => {
+                    output.push_str("message {\n");
+                    dump_message(output, record.value, depth + 1)?;
+                    output.push_str(&format!("{}}}\n", indent));
+                }
+                _ => output.push_str(&format!("bytes {}\n", hex(record.value))),
+            },
+            WireType::StartGroup | WireType::EndGroup => {
+                output.push_str(&format!("group {}\n", hex(record.raw)));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn hex(buf: &[u8]) -> String {
+    buf.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
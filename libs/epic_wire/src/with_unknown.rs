@@ -0,0 +1,477 @@
+use std::fmt::Formatter;
+use std::marker::PhantomData;
+
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::path::{quote, unquote};
+use crate::unknown::{EntryKey, PathSegment, UnknownField, UnknownFields};
+
+/// Name of the extra field unknown records are exported under
+pub const UNKNOWN_FIELD_NAME: &str = "_unknown";
+
+//one entry of the `_unknown` section, the record stays opaque
+#[derive(Serialize, Deserialize)]
+struct UnknownFieldRepr {
+    path: String,
+    position: usize,
+    data: String,
+}
+
+//`1[3]` for the fourth record of field 1, `1["pig_king"]` for the entry with that id and `1["x"#2]` for the second one named `x`
+fn format_path(path: &[PathSegment]) -> String {
+    path.iter()
+        .map(|segment| match &segment.entry {
+            EntryKey::Index(index) => format!("{}[{}]", segment.field, index),
+            EntryKey::Id(id, 1) => format!("{}[{}]", segment.field, quote(id)),
+            EntryKey::Id(id, count) => format!("{}[{}#{}]", segment.field, quote(id), count),
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn parse_path(path: &str) -> Option<Vec<PathSegment>> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+
+    while !rest.is_empty() {
+        if !segments.is_empty() {
+            rest = rest.strip_prefix('.')?;
+        }
+
+        let (field, after) = rest.split_once('[')?;
+        let field = field.parse().ok()?;
+
+        let (entry, after) = match after.starts_with('"') {
+            true => {
+                let (id, after) = unquote(after)?;
+                let (count, after) = match after.strip_prefix('#') {
+                    Some(count) => {
+                        let (count, after) = count.split_once(']')?;
+                        (count.parse().ok()?, after)
+                    }
+                    None => (1, after.strip_prefix(']')?),
+                };
+                (EntryKey::Id(id, count), after)
+            }
+            false => {
+                let (index, after) = after.split_once(']')?;
+                (EntryKey::Index(index.parse().ok()?), after)
+            }
+        };
+
+        segments.push(PathSegment { field, entry });
+        rest = after;
+    }
+
+    Some(segments)
+}
+
+impl Serialize for UnknownFields {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_seq(self.iter().map(|field| UnknownFieldRepr {
+            path: format_path(&field.path),
+            position: field.position,
+            data: BASE64_STANDARD.encode(&field.record),
+        }))
+    }
+}
+
+impl<'de> Deserialize<'de> for UnknownFields {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let fields = Vec::<UnknownFieldRepr>::deserialize(deserializer)?
+            .into_iter()
+            .map(|repr| {
+                Ok(UnknownField {
+                    path: parse_path(&repr.path)
+                        .ok_or_else(|| de::Error::custom(format!("invalid unknown field path {}", repr.path)))?,
+                    position: repr.position,
+                    record: BASE64_STANDARD.decode(&repr.data).map_err(de::Error::custom)?,
+                })
+            })
+            .collect::<Result<_, D::Error>>()?;
+
+        Ok(UnknownFields::new(fields))
+    }
+}
+
+/// Serializes `data` with its unknown fields appended as an extra `_unknown` field, nothing is added if there are none
+pub struct WithUnknown<'a, T> {
+    data: &'a T,
+    unknown: &'a UnknownFields,
+}
+
+impl<'a, T> WithUnknown<'a, T> {
+    pub fn new(data: &'a T, unknown: &'a UnknownFields) -> Self {
+        Self { data, unknown }
+    }
+}
+
+impl<T> Serialize for WithUnknown<'_, T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.unknown.is_empty() {
+            return self.data.serialize(serializer);
+        }
+
+        self.data.serialize(AppendUnknown {
+            inner: serializer,
+            unknown: self.unknown,
+        })
+    }
+}
+
+/// Deserializes with `seed` and pulls the `_unknown` field out of the top level struct before the generated code sees it
+pub struct CaptureUnknown<S>(pub S);
+
+/// [`CaptureUnknown`] for a plain `Deserialize` type
+pub fn capture_unknown<T>() -> CaptureUnknown<PhantomData<T>> {
+    CaptureUnknown(PhantomData)
+}
+
+impl<'de, S> DeserializeSeed<'de> for CaptureUnknown<S>
+where
+    S: DeserializeSeed<'de>,
+{
+    type Value = (S::Value, UnknownFields);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let mut unknown = UnknownFields::default();
+        let value = self.0.deserialize(CaptureDeserializer {
+            inner: deserializer,
+            unknown: &mut unknown,
+        })?;
+
+        Ok((value, unknown))
+    }
+}
+
+//forwards everything to the wrapped serializer, only the first struct gets the extra field
+struct AppendUnknown<'a, S> {
+    inner: S,
+    unknown: &'a UnknownFields,
+}
+
+struct AppendUnknownStruct<'a, S> {
+    inner: S,
+    unknown: &'a UnknownFields,
+}
+
+impl<S> SerializeStruct for AppendUnknownStruct<'_, S>
+where
+    S: SerializeStruct,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_field(key, value)
+    }
+
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        self.inner.serialize_field(UNKNOWN_FIELD_NAME, self.unknown)?;
+        self.inner.end()
+    }
+}
+
+macro_rules! forward_serialize {
+    ($($method:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $($arg: $ty),*) -> Result<$ret, Self::Error> {
+                self.inner.$method($($arg),*)
+            }
+        )*
+    };
+}
+
+impl<'a, S> Serializer for AppendUnknown<'a, S>
+where
+    S: Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = S::SerializeSeq;
+    type SerializeTuple = S::SerializeTuple;
+    type SerializeTupleStruct = S::SerializeTupleStruct;
+    type SerializeTupleVariant = S::SerializeTupleVariant;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = AppendUnknownStruct<'a, S::SerializeStruct>;
+    type SerializeStructVariant = S::SerializeStructVariant;
+
+    forward_serialize! {
+        serialize_bool(v: bool) -> Self::Ok;
+        serialize_i8(v: i8) -> Self::Ok;
+        serialize_i16(v: i16) -> Self::Ok;
+        serialize_i32(v: i32) -> Self::Ok;
+        serialize_i64(v: i64) -> Self::Ok;
+        serialize_u8(v: u8) -> Self::Ok;
+        serialize_u16(v: u16) -> Self::Ok;
+        serialize_u32(v: u32) -> Self::Ok;
+        serialize_u64(v: u64) -> Self::Ok;
+        serialize_f32(v: f32) -> Self::Ok;
+        serialize_f64(v: f64) -> Self::Ok;
+        serialize_char(v: char) -> Self::Ok;
+        serialize_str(v: &str) -> Self::Ok;
+        serialize_bytes(v: &[u8]) -> Self::Ok;
+        serialize_none() -> Self::Ok;
+        serialize_unit() -> Self::Ok;
+        serialize_unit_struct(name: &'static str) -> Self::Ok;
+        serialize_unit_variant(name: &'static str, variant_index: u32, variant: &'static str) -> Self::Ok;
+        serialize_seq(len: Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(len: usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(name: &'static str, len: usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Self::SerializeTupleVariant;
+        serialize_map(len: Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(name: &'static str, variant_index: u32, variant: &'static str, len: usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_some(value)
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_newtype_struct(name, value)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_newtype_variant(name, variant_index, variant, value)
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(AppendUnknownStruct {
+            inner: self.inner.serialize_struct(name, len + 1)?,
+            unknown: self.unknown,
+        })
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+//forwards everything to the wrapped deserializer, only the first struct gets its `_unknown` field taken out
+struct CaptureDeserializer<'u, D> {
+    inner: D,
+    unknown: &'u mut UnknownFields,
+}
+
+struct CaptureVisitor<'u, V> {
+    inner: V,
+    unknown: &'u mut UnknownFields,
+}
+
+struct CaptureMap<'u, A> {
+    inner: A,
+    unknown: &'u mut UnknownFields,
+}
+
+//reads a map key as a plain string, struct keys in ron are identifiers so strings can't be requested directly
+struct KeySeed;
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = String;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_identifier(KeyVisitor)
+    }
+}
+
+struct KeyVisitor;
+
+impl Visitor<'_> for KeyVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        formatter.write_str("a field name")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(v.to_string())
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(v)
+    }
+}
+
+impl<'de, A> MapAccess<'de> for CaptureMap<'_, A>
+where
+    A: MapAccess<'de>,
+{
+    type Error = A::Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        loop {
+            let Some(key) = self.inner.next_key_seed(KeySeed)? else {
+                return Ok(None);
+            };
+
+            if key == UNKNOWN_FIELD_NAME {
+                *self.unknown = self.inner.next_value()?;
+                continue;
+            }
+
+            return seed.deserialize(key.into_deserializer()).map(Some);
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        self.inner.next_value_seed(seed)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+impl<'de, V> Visitor<'de> for CaptureVisitor<'_, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.inner.visit_map(CaptureMap {
+            inner: map,
+            unknown: self.unknown,
+        })
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        self.inner.visit_seq(seq)
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.inner.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de, D> Deserializer<'de> for CaptureDeserializer<'_, D>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any();
+        deserialize_bool();
+        deserialize_i8();
+        deserialize_i16();
+        deserialize_i32();
+        deserialize_i64();
+        deserialize_u8();
+        deserialize_u16();
+        deserialize_u32();
+        deserialize_u64();
+        deserialize_f32();
+        deserialize_f64();
+        deserialize_char();
+        deserialize_str();
+        deserialize_string();
+        deserialize_bytes();
+        deserialize_byte_buf();
+        deserialize_option();
+        deserialize_unit();
+        deserialize_unit_struct(name: &'static str);
+        deserialize_newtype_struct(name: &'static str);
+        deserialize_seq();
+        deserialize_tuple(len: usize);
+        deserialize_tuple_struct(name: &'static str, len: usize);
+        deserialize_map();
+        deserialize_enum(name: &'static str, variants: &'static [&'static str]);
+        deserialize_identifier();
+        deserialize_ignored_any();
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_struct(
+            name,
+            fields,
+            CaptureVisitor {
+                inner: visitor,
+                unknown: self.unknown,
+            },
+        )
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
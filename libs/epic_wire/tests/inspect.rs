@@ -0,0 +1,39 @@
+use epic_wire::{inspect, InspectedValue};
+use epic_wire::wire::dump;
+
+//field 1 = varint 150, then field 2 = a message with field 1 = "ab" that is cut off after another key
+fn broken_nested() -> Vec<u8> {
+    let nested = [0x0a, 0x02, b'a', b'b', 0x12, 0x05, 0x01];
+    [[0x08, 0x96, 0x01].as_slice(), &[0x12, nested.len() as u8], &nested].concat()
+}
+
+#[test]
+fn broken_nested_message_keeps_what_was_read() {
+    let inspection = inspect(&broken_nested());
+    assert_eq!(inspection.error, None);
+    assert_eq!(inspection.fields[0].value, InspectedValue::Varint(150));
+
+    let InspectedValue::Message(nested) = &inspection.fields[1].value else {
+        panic!("expected a message, got {:?}", inspection.fields[1].value);
+    };
+    assert_eq!(nested.fields[0].value, InspectedValue::String("ab".to_string()));
+
+    //offsets count from the start of the whole blob
+    let error = nested.error.as_ref().unwrap();
+    assert_eq!(error.offset, 9);
+    assert_eq!(error.remaining, [0x12, 0x05, 0x01]);
+    assert!(inspection.to_string().contains("0x0009   error:"));
+}
+
+#[test]
+fn garbage_is_bytes() {
+    let inspection = inspect(&[0x12, 0x03, 0x0a, 0xff, 0x00]);
+    assert_eq!(inspection.fields[0].value, InspectedValue::Bytes(vec![0x0a, 0xff, 0x00]));
+}
+
+#[test]
+fn dump_shows_broken_nested_messages_as_bytes() {
+    assert_eq!(dump(&broken_nested()).unwrap(), "1: varint 150\n2: bytes 0a026162120501\n");
+    //only a broken top level record is an error
+    assert!(dump(&[0x12, 0x05, 0x01]).is_err());
+}
@@ -0,0 +1,28 @@
+use epic_wire::path::{quote, unquote};
+
+#[test]
+fn quoted_names_read_back() {
+    for name in ["pig_king", "say \"hi\"", "back\\slash", "line\nbreak", "tab\tbell\u{7}", "übung 🐦"] {
+        let quoted = quote(name);
+        assert_eq!(unquote(&format!("{}]", quoted)), Some((name.to_string(), "]")), "{}", quoted);
+    }
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn quoting_is_json() {
+    let name = "a\"b\\c\nd\u{1}é";
+    assert_eq!(quote(name), serde_json::to_string(name).unwrap());
+}
+
+#[test]
+fn json_escapes_are_read() {
+    assert_eq!(unquote(r#""\u00e9\ud83d\udc26\/x""#), Some(("é🐦/x".to_string(), "")));
+}
+
+#[test]
+fn broken_quotes_are_rejected() {
+    assert_eq!(unquote(r#""no end"#), None);
+    assert_eq!(unquote(r#"no start""#), None);
+    assert_eq!(unquote(r#""\ud83d""#), None);
+}
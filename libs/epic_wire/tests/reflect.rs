@@ -0,0 +1,265 @@
+#![cfg(feature = "reflect")]
+
+use epic_wire::Descriptors;
+use prost::encoding::{self, encode_key, encode_varint, WireType};
+use prost::Message;
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto, FieldOptions,
+    FileDescriptorProto, FileDescriptorSet, MessageOptions,
+};
+use serde_json::{json, Value};
+
+const MESSAGE: &str = "test.Everything";
+
+fn field(name: &str, number: i32, field_type: Type) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.to_string()),
+        number: Some(number),
+        r#type: Some(field_type as i32),
+        label: Some(Label::Optional as i32),
+        ..Default::default()
+    }
+}
+
+fn typed(name: &str, number: i32, field_type: Type, type_name: &str) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        type_name: Some(type_name.to_string()),
+        ..field(name, number, field_type)
+    }
+}
+
+fn repeated(field: FieldDescriptorProto, packed: bool) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        label: Some(Label::Repeated as i32),
+        options: Some(FieldOptions {
+            packed: Some(packed),
+            ..Default::default()
+        }),
+        ..field
+    }
+}
+
+//proto2, one field of every type plus packed and unpacked lists, a map and a list of messages
+fn descriptors() -> Descriptors {
+    let amounts_entry = DescriptorProto {
+        name: Some("AmountsEntry".to_string()),
+        field: vec![field("key", 1, Type::String), field("value", 2, Type::Int32)],
+        options: Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let everything = DescriptorProto {
+        name: Some("Everything".to_string()),
+        field: vec![
+            field("int32_value", 1, Type::Int32),
+            field("int64_value", 2, Type::Int64),
+            field("uint32_value", 3, Type::Uint32),
+            field("uint64_value", 4, Type::Uint64),
+            field("sint32_value", 5, Type::Sint32),
+            field("sint64_value", 6, Type::Sint64),
+            field("fixed32_value", 7, Type::Fixed32),
+            field("fixed64_value", 8, Type::Fixed64),
+            field("sfixed32_value", 9, Type::Sfixed32),
+            field("sfixed64_value", 10, Type::Sfixed64),
+            field("float_value", 11, Type::Float),
+            field("double_value", 12, Type::Double),
+            field("bool_value", 13, Type::Bool),
+            field("string_value", 14, Type::String),
+            field("bytes_value", 15, Type::Bytes),
+            typed("kind", 16, Type::Enum, ".test.Kind"),
+            typed("inner", 17, Type::Message, ".test.Inner"),
+            repeated(field("packed", 18, Type::Int32), true),
+            repeated(field("unpacked", 19, Type::Int32), false),
+            repeated(typed("amounts", 20, Type::Message, ".test.Everything.AmountsEntry"), false),
+            repeated(typed("inners", 21, Type::Message, ".test.Inner"), false),
+        ],
+        nested_type: vec![amounts_entry],
+        ..Default::default()
+    };
+
+    let inner = DescriptorProto {
+        name: Some("Inner".to_string()),
+        field: vec![field("name", 1, Type::String)],
+        ..Default::default()
+    };
+
+    let kind = EnumDescriptorProto {
+        name: Some("Kind".to_string()),
+        value: ["NONE", "CHEST"]
+            .iter()
+            .enumerate()
+            .map(|(number, name)| EnumValueDescriptorProto {
+                name: Some(name.to_string()),
+                number: Some(number as i32),
+                ..Default::default()
+            })
+            .collect(),
+        ..Default::default()
+    };
+
+    let set = FileDescriptorSet {
+        file: vec![FileDescriptorProto {
+            name: Some("test.proto".to_string()),
+            package: Some("test".to_string()),
+            syntax: Some("proto2".to_string()),
+            message_type: vec![everything, inner],
+            enum_type: vec![kind],
+            ..Default::default()
+        }],
+    };
+
+    Descriptors::decode(&set.encode_to_vec()).unwrap()
+}
+
+fn nested(number: u32, contents: &[u8], buf: &mut Vec<u8>) {
+    encode_key(number, WireType::LengthDelimited, buf);
+    encode_varint(contents.len() as u64, buf);
+    buf.extend_from_slice(contents);
+}
+
+fn inner(name: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encoding::string::encode(1, &name.to_string(), &mut buf);
+    buf
+}
+
+fn amount(key: &str, value: i32) -> Vec<u8> {
+    let mut buf = Vec::new();
+    encoding::string::encode(1, &key.to_string(), &mut buf);
+    encoding::int32::encode(2, &value, &mut buf);
+    buf
+}
+
+//every field in declaration order, the way the generated code writes them
+fn everything() -> Vec<u8> {
+    let mut buf = Vec::new();
+    encoding::int32::encode(1, &-3, &mut buf);
+    encoding::int64::encode(2, &-5, &mut buf);
+    encoding::uint32::encode(3, &7, &mut buf);
+    encoding::uint64::encode(4, &u64::MAX, &mut buf);
+    encoding::sint32::encode(5, &-9, &mut buf);
+    encoding::sint64::encode(6, &-11, &mut buf);
+    encoding::fixed32::encode(7, &13, &mut buf);
+    encoding::fixed64::encode(8, &15, &mut buf);
+    encoding::sfixed32::encode(9, &-17, &mut buf);
+    encoding::sfixed64::encode(10, &-19, &mut buf);
+    encoding::float::encode(11, &1.5, &mut buf);
+    encoding::double::encode(12, &f64::NAN, &mut buf);
+    encoding::bool::encode(13, &true, &mut buf);
+    encoding::string::encode(14, &"chest".to_string(), &mut buf);
+    encoding::bytes::encode(15, &vec![0u8, 1, 2], &mut buf);
+    encoding::int32::encode(16, &1, &mut buf);
+    nested(17, &inner("gold"), &mut buf);
+    encoding::int32::encode_packed(18, &[1, 2, 3], &mut buf);
+    encoding::int32::encode_repeated(19, &[4, 5], &mut buf);
+    nested(20, &amount("b", 2), &mut buf);
+    nested(20, &amount("a", 1), &mut buf);
+    nested(21, &inner("x"), &mut buf);
+    buf
+}
+
+#[test]
+fn every_field_type_reads_like_pbjson() {
+    let value = descriptors().decode_dynamic(MESSAGE, &everything()).unwrap();
+
+    let expected = json!({
+        "int32Value": -3,
+        "int64Value": "-5",
+        "uint32Value": 7,
+        "uint64Value": "18446744073709551615",
+        "sint32Value": -9,
+        "sint64Value": "-11",
+        "fixed32Value": 13,
+        "fixed64Value": "15",
+        "sfixed32Value": -17,
+        "sfixed64Value": "-19",
+        "floatValue": 1.5,
+        "doubleValue": "NaN",
+        "boolValue": true,
+        "stringValue": "chest",
+        "bytesValue": "AAEC",
+        "kind": "CHEST",
+        "inner": { "name": "gold" },
+        "packed": [1, 2, 3],
+        "unpacked": [4, 5],
+        "amounts": { "b": 2, "a": 1 },
+        "inners": [{ "name": "x" }]
+    });
+    assert_eq!(value, expected);
+}
+
+#[test]
+fn every_field_type_writes_back_the_same_bytes() {
+    let descriptors = descriptors();
+    let value = descriptors.decode_dynamic(MESSAGE, &everything()).unwrap();
+
+    assert_eq!(descriptors.encode_dynamic(MESSAGE, &value).unwrap(), everything());
+}
+
+#[test]
+fn map_entries_keep_their_order() {
+    let descriptors = descriptors();
+    let mut buf = Vec::new();
+    for (key, value) in [("z", 1), ("a", 2), ("m", 3)] {
+        nested(20, &amount(key, value), &mut buf);
+    }
+
+    let value = descriptors.decode_dynamic(MESSAGE, &buf).unwrap();
+    let keys: Vec<&str> = value["amounts"].as_object().unwrap().keys().map(String::as_str).collect();
+    assert_eq!(keys, ["z", "a", "m"]);
+    assert_eq!(descriptors.encode_dynamic(MESSAGE, &value).unwrap(), buf);
+}
+
+#[test]
+fn packed_and_unpacked_lists_are_both_read() {
+    let mut buf = Vec::new();
+    encoding::int32::encode_repeated(18, &[1, 2], &mut buf);
+    encoding::int32::encode_packed(19, &[3, 4], &mut buf);
+
+    let value = descriptors().decode_dynamic(MESSAGE, &buf).unwrap();
+    assert_eq!(value["packed"], json!([1, 2]));
+    assert_eq!(value["unpacked"], json!([3, 4]));
+}
+
+#[test]
+fn unknown_records_and_enum_numbers_round_trip() {
+    let descriptors = descriptors();
+    let mut buf = Vec::new();
+    encoding::string::encode(14, &"chest".to_string(), &mut buf);
+    encoding::int32::encode(16, &7, &mut buf);
+    encoding::uint64::encode(99, &42, &mut buf);
+
+    let value = descriptors.decode_dynamic(MESSAGE, &buf).unwrap();
+    assert_eq!(value["kind"], 7);
+    assert!(value.get("_unknown").is_some(), "{}", value);
+
+    assert_eq!(descriptors.encode_dynamic(MESSAGE, &value).unwrap(), buf);
+}
+
+#[test]
+fn values_that_dont_fit_are_errors() {
+    let descriptors = descriptors();
+    let invalid: [Value; 4] = [
+        json!({ "int32Value": 3000000000u64 }),
+        json!({ "uint32Value": -1 }),
+        json!({ "kind": "OPEN" }),
+        json!({ "noSuchField": 1 }),
+    ];
+
+    for value in invalid {
+        assert!(descriptors.encode_dynamic(MESSAGE, &value).is_err(), "{}", value);
+    }
+}
+
+#[test]
+fn wrong_wire_types_are_errors() {
+    //int32_value written as a string
+    let mut buf = Vec::new();
+    encoding::string::encode(1, &"3".to_string(), &mut buf);
+
+    assert!(descriptors().decode_dynamic(MESSAGE, &buf).is_err());
+}
@@ -1,28 +1,68 @@
-use crate::{generate_container_encode_match, DataFormat};
-use crate::util::{get_key_from_name, key_to_string};
-use clap::{Args, Subcommand};
-use epic_balance::{proto, BalancingDataArchive, BalancingDataTypes};
+use crate::{DataFormat, RawFormat};
+use crate::util::{
+    all_keys_to_string, bytes_to_raw, get_key_from_name, key_to_string, raw_to_bytes, read_raw_container,
+    reflect_from_str, reflect_to_string, ReflectArgs,
+};
+use clap::{Args, Subcommand, ValueEnum};
+use epic_balance::{BalancingDataArchive, BalancingDataTypes, CharacterKind, CharacterStats, Patch, SchemaRevision};
+use epic_logic::loot::{Loot, LootTables};
+use epic_logic::requirements::{Outcome, RequirementEvaluator};
+use epic_prefs::PlayerPrefsData;
+use chrono::{DateTime, Utc};
+use epic_wire::UnknownFields;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 use std::fs::File;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
 
 #[derive(Args, Clone)]
 #[command(version, about, long_about = Some("Encode or decode a serialized balancing data container"), aliases = ["b", "bal", "balance", "balancing-data"]
 )]
 pub(super) struct BalancingArgs {
-    #[arg(help = "Location of the serialized balancing data container")]
-    pub live_data_path: PathBuf,
+    #[arg(help = "Location of the serialized balancing data container, repack doesn't need one")]
+    pub live_data_path: Option<PathBuf>,
     #[arg(help = "Name of the balancing data container to be encoded/decoded")]
     pub container_name: Option<String>,
+    #[command(flatten)]
+    pub revision: RevisionArgs,
 
     #[command(subcommand)]
     pub balancing_action: BalancingAction,
 }
 
+impl BalancingArgs {
+    fn live_data_path(&self) -> anyhow::Result<&Path> {
+        self.live_data_path
+            .as_deref()
+            .ok_or(anyhow!("No balancing data container path provided"))
+    }
+
+    //the container at the path, in the revision given with --from
+    fn archive(&self, lossless: bool) -> anyhow::Result<BalancingDataArchive> {
+        let (mut archive, _) = read_archive(self.live_data_path()?, lossless)?;
+        self.revision.apply(&mut archive)?;
+        Ok(archive)
+    }
+}
+
 #[derive(Subcommand, Clone)]
 pub(super) enum BalancingAction {
     Decode(BalancingDecodeArgs),
     Encode(BalancingEncodeArgs),
+    Extract(BalancingExtractArgs),
+    Repack(BalancingRepackArgs),
+    Validate(BalancingValidateArgs),
+    Loot(BalancingLootArgs),
+    Stats(BalancingStatsArgs),
+    Equipment(BalancingEquipmentArgs),
+    Requirements(BalancingRequirementsArgs),
+    Diff(BalancingDiffArgs),
+    Patch(BalancingPatchArgs),
+    Migrate(BalancingMigrateArgs),
 }
 
 #[derive(Args, Clone)]
@@ -32,7 +72,7 @@ pub(super) struct BalancingDecodeArgs {
     #[arg(
         long,
         short,
-        help = "Location to save the decoded container file, does not apply if --all is used",
+        help = "Location to save the decoded container file, with --all the whole container is saved as a single document",
         value_name = "FILE"
     )]
     pub output_file_path: Option<PathBuf>,
@@ -41,48 +81,281 @@ pub(super) struct BalancingDecodeArgs {
 
     #[arg(help = "Data format to output the container file in", long, short = 'O', default_value_t=DataFormat::Json)]
     pub output_as: DataFormat,
+    #[arg(help = "Format to output containers of an unknown type in, wire dumps can't be encoded back", long, short = 'U', default_value_t=RawFormat::Bin)]
+    pub unknown_as: RawFormat,
+    #[arg(help = "Write dates like event start and end times as RFC 3339 instead of Unix seconds, encoding takes either", long, short = 'D')]
+    pub readable_dates: bool,
+    #[command(flatten)]
+    pub reflect: ReflectArgs,
 }
 #[derive(Args, Clone)]
 #[command(version, about, long_about = Some("Encode a serialized balancing data container"), aliases = ["e", "pack", "import", "reimport"]
 )]
 pub(super) struct BalancingEncodeArgs {
-    #[arg(help = "Location of the container file to encode into the container")]
+    #[arg(help = "Location of the container file to encode into the container, .bin and .b64 files are inserted as is. Without a container name it's a whole container document")]
     pub container_file_path: PathBuf,
     #[arg(help = "Location to save the encoded container")]
     pub output_file_path: PathBuf,
+    #[arg(
+        long,
+        short = 'L',
+        help = "Keep the original encoding of everything that was not changed, untouched containers are written back byte for byte"
+    )]
+    pub lossless: bool,
+    #[command(flatten)]
+    pub reflect: ReflectArgs,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Extract every container of a serialized balancing data container into a directory, along with a manifest to repack it"), aliases = ["x", "unpack-all"]
+)]
+pub(super) struct BalancingExtractArgs {
+    #[arg(help = "Directory to extract the containers into, created if it doesn't exist")]
+    pub output_dir: PathBuf,
+
+    #[arg(help = "Data format to output the containers in", long, short = 'O', default_value_t=DataFormat::Json)]
+    pub output_as: DataFormat,
+    #[arg(help = "Format to output containers of an unknown type in, has to be bin or base64 to be repacked", long, short = 'U', default_value_t=RawFormat::Bin)]
+    pub unknown_as: RawFormat,
+    #[arg(help = "Write dates like event start and end times as RFC 3339 instead of Unix seconds, encoding takes either", long, short = 'D')]
+    pub readable_dates: bool,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Repack a directory made by extract into a serialized balancing data container, no container path is needed"), aliases = ["r", "repack-all"]
+)]
+pub(super) struct BalancingRepackArgs {
+    #[arg(help = "Directory made by extract, has to contain a manifest.json")]
+    pub input_dir: PathBuf,
+    #[arg(help = "Location to save the repacked container")]
+    pub output_file_path: PathBuf,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Check a serialized balancing data container for references to ids that don't exist and duplicate name ids"), aliases = ["v", "check"]
+)]
+pub(super) struct BalancingValidateArgs {}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Roll a loot table or show the chance of everything it can drop"), aliases = ["l", "roll"]
+)]
+pub(super) struct BalancingLootArgs {
+    #[arg(help = "Name id of the loot table")]
+    pub loot_table: String,
+    #[arg(help = "Player level to roll the loot table at", long, short, default_value_t = 1)]
+    pub level: i32,
+    #[arg(help = "Roll the loot table this many times instead of showing the drop chances", long, short)]
+    pub rolls: Option<u32>,
+    #[arg(help = "Seed for the rolls, the same seed always gives the same loot", long, short)]
+    pub seed: Option<u64>,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Show the stats of a bird or pig at a level or range of levels"), aliases = ["s", "stat"]
+)]
+pub(super) struct BalancingStatsArgs {
+    #[arg(help = "Name id of the bird or pig, every bird and pig if not given")]
+    pub character: Option<String>,
+    #[arg(help = "Level to show the stats at, first level of the range with --to", long, short, default_value_t = 1)]
+    pub level: i32,
+    #[arg(help = "Last level of the range to show the stats for", long, short)]
+    pub to: Option<i32>,
+    #[arg(help = "Format to output the stats in", long, short = 'O', default_value_t = ReportFormat::Table)]
+    pub output_as: ReportFormat,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Show the stat, perk and set of a piece of equipment, or compare every piece of equipment"), aliases = ["eq", "gear"]
+)]
+pub(super) struct BalancingEquipmentArgs {
+    #[arg(help = "Name id of the equipment, the whole equipment catalogue if not given")]
+    pub equipment: Option<String>,
+    #[arg(help = "Level to show the stat at", long, short, default_value_t = 1)]
+    pub level: i32,
+    #[arg(help = "Quality (stars) to show the stat at, starts at 1", long, short, default_value_t = 1)]
+    pub quality: i32,
+    #[arg(help = "Format to output the stats in", long, short = 'O', default_value_t = ReportFormat::Table)]
+    pub output_as: ReportFormat,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Check the requirements of a shop offer, message or anything else with a name id against a player prefs file"), aliases = ["req", "why"]
+)]
+pub(super) struct BalancingRequirementsArgs {
+    #[arg(help = "Name id of the entry to check the requirements of")]
+    pub name_id: String,
+    #[arg(help = "Location of the xml player prefs file")]
+    pub player_prefs_path: PathBuf,
+    #[arg(help = "Time to check the requirements at (RFC 3339, e.g. 2016-05-01T12:00:00Z), now if not given", long)]
+    pub at: Option<DateTime<Utc>>,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Show what changed from the container to another one, entries are matched by name id and map order is ignored"), aliases = ["compare"]
+)]
+pub(super) struct BalancingDiffArgs {
+    #[arg(help = "Location of the newer serialized balancing data container")]
+    pub new_data_path: PathBuf,
+    #[arg(help = "Location to save the diff to, printed if not given", long, short, value_name = "FILE")]
+    pub output_file_path: Option<PathBuf>,
+    #[arg(help = "Format to output the diff in, patch makes a patch file for the patch command", long, short = 'O', default_value_t = DiffFormat::Text)]
+    pub output_as: DiffFormat,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Apply a patch file to the container, entries are found by name id so patches keep working on newer containers"), aliases = ["apply"]
+)]
+pub(super) struct BalancingPatchArgs {
+    #[arg(help = "Location of the json patch file")]
+    pub patch_file_path: PathBuf,
+    #[arg(help = "Location to save the patched container")]
+    pub output_file_path: PathBuf,
+    #[arg(
+        long,
+        short = 'L',
+        help = "Keep the original encoding of everything that was not changed, untouched containers are written back byte for byte"
+    )]
+    pub lossless: bool,
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Convert the containers to another revision of balancing.proto, for containers of game builds the current proto doesn't fit"), aliases = ["migrate-schema", "convert-schema"]
+)]
+pub(super) struct BalancingMigrateArgs {
+    #[arg(help = "Location to save the converted container")]
+    pub output_file_path: PathBuf,
+    #[arg(help = "Revision to convert the containers to, the current one if not given", long)]
+    pub to: Option<String>,
+}
+
+//the revision of balancing.proto to read and write the containers in
+#[derive(Args, Clone)]
+pub(super) struct RevisionArgs {
+    #[arg(
+        long,
+        global = true,
+        help = "Revision of balancing.proto the containers are in, picked from the container version if not given",
+        value_name = "REVISION"
+    )]
+    pub from: Option<String>,
+}
+
+impl RevisionArgs {
+    fn apply(&self, archive: &mut BalancingDataArchive) -> anyhow::Result<()> {
+        if let Some(name) = &self.from {
+            archive.set_schema_revision(Some(schema_revision(name)?));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(ValueEnum, Copy, Clone)]
+pub(super) enum DiffFormat {
+    Text,
+    Json,
+    Patch,
+}
+
+#[derive(ValueEnum, Copy, Clone)]
+pub(super) enum ReportFormat {
+    Table,
+    Csv,
+}
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+//everything needed to rebuild an extracted archive, containers are repacked in the order they are listed in
+#[derive(Serialize, Deserialize)]
+struct ArchiveManifest {
+    version: String,
+    compression: Compression,
+    //only there if it was given with --from, otherwise the version picks it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revision: Option<String>,
+    #[serde(default, skip_serializing_if = "UnknownFields::is_empty")]
+    unknown: UnknownFields,
+    containers: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+pub(super) enum Compression {
+    None,
+    Gzip,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestEntry {
+    key: String,
+    file: String,
+    format: EntryFormat,
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone)]
+#[serde(rename_all = "lowercase")]
+enum EntryFormat {
+    Json,
+    Ron,
+    Bin,
+    Base64,
 }
 
 pub(super) fn decode_container(
     balancing_args: BalancingArgs,
     args: BalancingDecodeArgs,
 ) -> anyhow::Result<()> {
-    let data = std::fs::read(balancing_args.live_data_path)?;
+    if args.export_all && args.reflect.proto.is_some() {
+        bail!("--proto only works on a single container");
+    }
 
-    let reader = match BalancingDataArchive::new_gzipped(data.as_slice()) {
-        Ok(reader) => reader,
-        Err(_) => BalancingDataArchive::new(data.as_slice())?,
-    };
+    let mut reader = balancing_args.archive(false)?;
+    reader.set_readable_dates(args.readable_dates);
 
-    if args.export_all {
-        let keys = reader.get_balaning_data_keys();
+    if let (true, Some(output_file_path)) = (args.export_all, &args.output_file_path) {
+        let data = match args.output_as {
+            DataFormat::Json => reader.to_json_pretty()?,
+            DataFormat::Ron => reader.to_ron_pretty()?,
+        };
 
-        for key in keys {
-            let enum_key = BalancingDataTypes::from_str(&key)?;
+        std::fs::write(output_file_path, data)?;
+    } else if args.export_all {
+        for (key, data) in all_keys_to_string(&reader, args.output_as) {
+            std::fs::write(format!("{}.{}", key, args.output_as), data?)?;
+        }
 
-            // prost_fix(enum_key, &mut reader)?;
+        for key in reader.get_balaning_data_keys() {
+            if BalancingDataTypes::from_str(&key).is_err() {
+                eprintln!("Warning: Unknown BalancingData type: {}, exporting it as {}", key, args.unknown_as);
+                export_raw(&reader, &key, None, args.unknown_as)?;
+            }
+        }
+    } else {
+        let container_name = balancing_args
+            .container_name
+            .ok_or(anyhow!("No container name provided"))?;
 
-            let data = key_to_string(enum_key, &reader, args.output_as)?;
+        if let Some(descriptors) = args.reflect.load()? {
+            let key = container_key(container_name);
+            let value = reader.get_data_key_reflect(&key, &descriptors, args.reflect.message.as_deref())?;
 
-            std::fs::write(format!("{}.{}", key.to_string(), args.output_as.to_string()), data)?;
+            let output_file_path = args
+                .output_file_path
+                .unwrap_or(PathBuf::from(format!("{}.{}", key, args.output_as)));
+            std::fs::write(output_file_path, reflect_to_string(&value, args.output_as)?)?;
+
+            return Ok(());
         }
-    } else {
-        let key = get_key_from_name(
-            &balancing_args
-                .container_name
-                .ok_or(anyhow!("No container name provided"))?,
-        )?;
 
-        // prost_fix(key, &mut reader)?;
+        //keys the archive has but we don't know the type of can still be exported raw
+        if BalancingDataTypes::from_any_name(&container_name).is_none()
+            && reader.get_data_key(&container_name).is_some()
+        {
+            eprintln!("Warning: Unknown BalancingData type: {}, exporting it as {}", container_name, args.unknown_as);
+            return export_raw(&reader, &container_name, args.output_file_path, args.unknown_as);
+        }
+
+        let key = get_key_from_name(&container_name)?;
 
         let data = key_to_string(key, &reader, args.output_as)?;
 
@@ -99,117 +372,538 @@ pub(super) fn decode_container(
 
     Ok(())
 }
+
+fn export_raw(
+    reader: &BalancingDataArchive,
+    key: &str,
+    output_file_path: Option<PathBuf>,
+    raw_format: RawFormat,
+) -> anyhow::Result<()> {
+    let data = reader
+        .get_data_key(key)
+        .ok_or(anyhow!("No container named {}", key))?;
+
+    let output_file_path = output_file_path.unwrap_or(PathBuf::from(format!("{}.{}", key, raw_format)));
+    std::fs::write(output_file_path, raw_to_bytes(data, raw_format)?)?;
+
+    Ok(())
+}
+
 pub(super) fn encode_container(
     balancing_args: BalancingArgs,
     args: BalancingEncodeArgs,
 ) -> anyhow::Result<()> {
-    let key = get_key_from_name(
-        &balancing_args
-            .container_name
-            .ok_or(anyhow!("No container name provided"))?,
+    let mut archive = balancing_args.archive(args.lossless)?;
+
+    let Some(container_name) = balancing_args.container_name else {
+        if args.reflect.proto.is_some() {
+            bail!("--proto only works on a single container");
+        }
+
+        //whole container document, every container in it replaces the one in the archive
+        let data = std::fs::read_to_string(args.container_file_path)?;
+
+        if data.starts_with("{") {
+            archive.update_from_json(&data)?;
+        } else {
+            archive.update_from_ron(&data)?;
+        }
+
+        let file = File::create(args.output_file_path)?;
+        return archive.save_gzipped(file);
+    };
+
+    if let Some(descriptors) = args.reflect.load()? {
+        let key = container_key(container_name);
+        let value = reflect_from_str(&std::fs::read_to_string(args.container_file_path)?)?;
+
+        archive.set_data_key_reflect(&key, &descriptors, args.reflect.message.as_deref(), &value)?;
+    } else if let Some(raw) = read_raw_container(&args.container_file_path)? {
+        //raw containers go back in verbatim, under the exact key they were exported with if the type is unknown
+        archive.set_data_key_raw(&container_key(container_name), raw);
+    } else {
+        let key = get_key_from_name(&container_name)?;
+
+        let data = std::fs::read_to_string(args.container_file_path)?;
+        let data_format = if data.starts_with("{") { DataFormat::Json } else { DataFormat::Ron };
+
+        match data_format {
+            DataFormat::Json => archive.set_data_any_json(key, &data)?,
+            DataFormat::Ron => archive.set_data_any_ron(key, &data)?,
+        }
+    }
+
+    let file = File::create(args.output_file_path)?;
+    archive.save_gzipped(file)?;
+
+    Ok(())
+}
+
+//known types by any of their names, anything else as the exact key
+pub(super) fn container_key(container_name: String) -> String {
+    match BalancingDataTypes::from_any_name(&container_name) {
+        Some(key) => key.to_string(),
+        None => container_name,
+    }
+}
+
+pub(super) fn extract_container(
+    balancing_args: BalancingArgs,
+    args: BalancingExtractArgs,
+) -> anyhow::Result<()> {
+    if let RawFormat::Wire = args.unknown_as {
+        bail!("Wire dumps can't be repacked, use bin or base64 for containers of an unknown type");
+    }
+
+    let (mut reader, compression) = read_archive(balancing_args.live_data_path()?, false)?;
+    balancing_args.revision.apply(&mut reader)?;
+    reader.set_readable_dates(args.readable_dates);
+
+    std::fs::create_dir_all(&args.output_dir)?;
+
+    let mut decoded: HashMap<String, anyhow::Result<String>> = all_keys_to_string(&reader, args.output_as)
+        .into_iter()
+        .map(|(key, data)| (key.to_string(), data))
+        .collect();
+    let mut containers = Vec::new();
+
+    for key in reader.get_balaning_data_keys() {
+        let (data, format, extension) = match decoded.remove(&key) {
+            Some(data) => {
+                let format = match args.output_as {
+                    DataFormat::Json => EntryFormat::Json,
+                    DataFormat::Ron => EntryFormat::Ron,
+                };
+
+                (data?.into_bytes(), format, args.output_as.to_string())
+            }
+            None => {
+                eprintln!("Warning: Unknown BalancingData type: {}, extracting it as {}", key, args.unknown_as);
+
+                let format = match args.unknown_as {
+                    RawFormat::Base64 => EntryFormat::Base64,
+                    _ => EntryFormat::Bin,
+                };
+                let data = raw_to_bytes(
+                    reader.get_data_key(&key).ok_or(anyhow!("No container named {}", key))?,
+                    args.unknown_as,
+                )?;
+
+                (data, format, args.unknown_as.to_string())
+            }
+        };
+
+        let file = format!("{}.{}", key, extension);
+        std::fs::write(args.output_dir.join(&file), data)?;
+
+        containers.push(ManifestEntry { key, file, format });
+    }
+
+    let manifest = ArchiveManifest {
+        version: reader.get_version().to_string(),
+        compression,
+        revision: balancing_args.revision.from,
+        unknown: reader.get_unknown_fields().clone(),
+        containers,
+    };
+
+    std::fs::write(
+        args.output_dir.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)?,
     )?;
 
-    let data = std::fs::read(balancing_args.live_data_path)?;
-    let mut archive = match BalancingDataArchive::new(data.as_slice()) {
-        Ok(reader) => reader,
-        Err(_) => BalancingDataArchive::new_gzipped(data.as_slice())?,
+    Ok(())
+}
+
+pub(super) fn repack_container(
+    balancing_args: BalancingArgs,
+    args: BalancingRepackArgs,
+) -> anyhow::Result<()> {
+    let manifest = std::fs::read_to_string(args.input_dir.join(MANIFEST_FILE))?;
+    let manifest: ArchiveManifest = serde_json::from_str(&manifest)?;
+
+    let mut archive = BalancingDataArchive::default();
+    archive.set_version(&manifest.version);
+    archive.set_unknown_fields(manifest.unknown);
+    let revision = RevisionArgs {
+        from: balancing_args.revision.from.or(manifest.revision),
     };
+    revision.apply(&mut archive)?;
 
-    let data = std::fs::read_to_string(args.container_file_path)?;
-    let data_format = if data.starts_with("{") { DataFormat::Json } else { DataFormat::Ron };
-
-    generate_container_encode_match!(archive, key, &data, data_format,
-        BattleBalancingData => proto::BattleBalancingData,
-        ChronicleCaveFloorBalancingData => proto::ChronicleCaveFloorBalancingData,
-        CustomMessageBalancingData => proto::CustomMessageBalancingData,
-        HotspotBalancingData => proto::HotspotBalancingData,
-        ExperienceScalingBalancingData => proto::ExperienceScalingBalancingData,
-        CollectionGroupBalancingData => proto::CollectionGroupBalancingData,
-        ExperienceLevelBalancingData => proto::ExperienceLevelBalancingData,
-        InventoryBalancingData => proto::InventoryBalancingData,
-        ShopBalancingData => proto::ShopBalancingData,
-        BattleParticipantTableBalancingData => proto::BattleParticipantTableBalancingData,
-        ClassSkinBalancingData => proto::ClassSkinBalancingData,
-        ConditionalInventoryBalancingData => proto::ConditionalInventoryBalancingData,
-        ChronicleCaveBattleBalancingData => proto::BattleBalancingData,
-        ExperienceMasteryBalancingData => proto::ExperienceMasteryBalancingData,
-        BirdBalancingData => proto::BirdBalancingData,
-        CraftingRecipeBalancingData => proto::CraftingRecipeBalancingData,
-        MiniCampaignBalancingData => proto::MiniCampaignBalancingData,
-        LootTableBalancingData => proto::LootTableBalancingData,
-        SocialEnvironmentBalancingData => proto::SocialEnvironmentBalancingData,
-        BannerItemBalancingData => proto::BannerItemBalancingData,
-        PremiumShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        GachaShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        PowerLevelBalancingData => proto::PowerLevelBalancingData,
-        PigBalancingData => proto::PigBalancingData,
-        EnchantingBalancingData => proto::EnchantingBalancingData,
-        BuyableShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        ClientConfigBalancingData => proto::ClientConfigBalancingData,
-        ConsumableItemBalancingData => proto::ConsumableItemBalancingData,
-        ResourceCostPerLevelBalancingData => proto::ResourceCostPerLevelBalancingData,
-        SkillBalancingData => proto::SkillBalancingData,
-        EventItemBalancingData => proto::EventItemBalancingData,
-        GameConstantsBalancingData => proto::GameConstantsBalancingData,
-        CraftingItemBalancingData => proto::CraftingItemBalancingData,
-        DailyLoginGiftsBalancingData => proto::DailyLoginGiftsBalancingData,
-        LoadingHintBalancingData => proto::LoadingHintBalancingData,
-        SetFusionBalancingData => proto::SetFusionBalancingData,
-        EquipmentBalancingData => proto::EquipmentBalancingData,
-        ChronicleCaveHotspotBalancingData => proto::HotspotBalancingData,
-        MasteryItemBalancingData => proto::MasteryItemBalancingData,
-        SplashScreenBalancingData => proto::SplashScreenBalancingData,
-        PigTypePowerLevelBalancingData => proto::PigTypePowerLevelBalancingData,
-        PvPObjectivesBalancingData => proto::PvPObjectivesBalancingData,
-        ThirdPartyIdBalancingData => proto::ThirdPartyIdBalancingData,
-        BasicItemBalancingData => proto::BasicItemBalancingData,
-        BattleHintBalancingData => proto::BattleHintBalancingData,
-        BossBalancingData => proto::BossBalancingData,
-        ScoreBalancingData => proto::ScoreBalancingData,
-        SalesManagerBalancingData => proto::SalesManagerBalancingData,
-        BannerBalancingData => proto::BannerBalancingData,
-        ChronicleCaveBattleParticipantTableBalancingData => proto::BattleParticipantTableBalancingData,
-        ClassItemBalancingData => proto::ClassItemBalancingData,
-        EventBalancingData => proto::EventBalancingData,
-        BonusEventBalancingData => proto::BonusEventBalancingData,
-        PvPSeasonManagerBalancingData => proto::PvPSeasonManagerBalancingData,
-        EventPlacementBalancingData => proto::EventPlacementBalancingData,
-        EventManagerBalancingData => proto::EventManagerBalancingData,
-    );
+    //everything goes in in manifest order, typed containers hold an empty slot until they are all parsed at once
+    let mut json = Vec::new();
+    let mut ron = Vec::new();
+
+    for entry in manifest.containers {
+        let path = args.input_dir.join(&entry.file);
+        let typed_key = || {
+            BalancingDataTypes::from_str(&entry.key)
+                .map_err(|_| anyhow!("Unknown BalancingData type: {}, only bin and base64 can be repacked as is", entry.key))
+        };
+
+        match entry.format {
+            EntryFormat::Json => json.push((typed_key()?, std::fs::read_to_string(path)?)),
+            EntryFormat::Ron => ron.push((typed_key()?, std::fs::read_to_string(path)?)),
+            EntryFormat::Bin => archive.set_data_key_raw(&entry.key, std::fs::read(path)?),
+            EntryFormat::Base64 => archive.set_data_key_raw(&entry.key, bytes_to_raw(std::fs::read(path)?, RawFormat::Base64)?),
+        }
+
+        if let EntryFormat::Json | EntryFormat::Ron = entry.format {
+            archive.set_data_key_raw(&entry.key, Vec::new());
+        }
+    }
+
+    archive.set_all_data_any_json(json)?;
+    archive.set_all_data_any_ron(ron)?;
+
+    let file = File::create(args.output_file_path)?;
+    match manifest.compression {
+        Compression::Gzip => archive.save_gzipped(file)?,
+        Compression::None => archive.save(file)?,
+    }
+
+    Ok(())
+}
+
+pub(super) fn validate_container(
+    balancing_args: BalancingArgs,
+    _: BalancingValidateArgs,
+) -> anyhow::Result<()> {
+    let reader = balancing_args.archive(false)?;
+
+    let issues = reader.validate()?;
+    for issue in &issues {
+        println!("{}", issue);
+    }
+
+    if !issues.is_empty() {
+        bail!("Found {} issues", issues.len());
+    }
+
+    println!("No issues found");
+
+    Ok(())
+}
+
+pub(super) fn roll_loot(
+    balancing_args: BalancingArgs,
+    args: BalancingLootArgs,
+) -> anyhow::Result<()> {
+    let reader = balancing_args.archive(false)?;
+
+    let tables = LootTables::new(&reader)?;
+
+    let Some(rolls) = args.rolls else {
+        for (item, distribution) in tables.distribution(&args.loot_table, args.level)? {
+            println!(
+                "{} (level {}): {:.2}% chance, {:.3} expected",
+                item.name_id,
+                item.level,
+                distribution.chance_of_any() * 100.0,
+                distribution.expected()
+            );
+        }
+
+        return Ok(());
+    };
+
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut total = Loot::new();
+    for _ in 0..rolls {
+        for (item, amount) in tables.roll(&args.loot_table, args.level, &mut rng)? {
+            *total.entry(item).or_default() += amount;
+        }
+    }
+
+    for (item, amount) in total {
+        println!("{} (level {}): {}", item.name_id, item.level, amount);
+    }
+
+    Ok(())
+}
+
+pub(super) fn character_stats(
+    balancing_args: BalancingArgs,
+    args: BalancingStatsArgs,
+) -> anyhow::Result<()> {
+    let reader = balancing_args.archive(false)?;
+
+    let calculator = reader.stat_calculator()?;
+
+    let characters = match args.character {
+        Some(character) => vec![character],
+        None => {
+            let mut birds: Vec<_> = calculator.birds().map(str::to_string).collect();
+            let mut pigs: Vec<_> = calculator.pigs().map(str::to_string).collect();
+            birds.sort();
+            pigs.sort();
+            birds.into_iter().chain(pigs).collect()
+        }
+    };
+
+    let levels = args.level..=args.to.unwrap_or(args.level);
+
+    let header = ["name_id", "kind", "level", "health", "attack", "pig_strength", "size_scale", "power_level"];
+    let mut rows = vec![header.map(str::to_string).to_vec()];
+
+    for character in characters {
+        let stats = calculator
+            .stats_range(&character, levels.clone())
+            .ok_or(anyhow!("No bird or pig named {}", character))?;
+
+        rows.extend(stats.iter().map(stats_row));
+    }
+
+    print_report(&rows, args.output_as);
+
+    Ok(())
+}
+
+fn stats_row(stats: &CharacterStats) -> Vec<String> {
+    let optional = |value: Option<String>| value.unwrap_or_default();
+
+    vec![
+        stats.name_id.clone(),
+        match stats.kind {
+            CharacterKind::Bird => "bird".to_string(),
+            CharacterKind::Pig => "pig".to_string(),
+        },
+        stats.level.to_string(),
+        stats.health.to_string(),
+        stats.attack.to_string(),
+        optional(stats.pig_strength.map(|value| value.to_string())),
+        optional(stats.size_scale.map(|value| value.to_string())),
+        optional(stats.power_level.map(|value| value.to_string())),
+    ]
+}
+
+pub(super) fn equipment_stats(
+    balancing_args: BalancingArgs,
+    args: BalancingEquipmentArgs,
+) -> anyhow::Result<()> {
+    let reader = balancing_args.archive(false)?;
+
+    let calculator = reader.stat_calculator()?;
+
+    let mut stats = match args.equipment {
+        Some(equipment) => vec![calculator
+            .equipment_stats(&equipment, args.level, args.quality)
+            .ok_or(anyhow!("No equipment named {}", equipment))?],
+        None => calculator
+            .equipment()
+            .filter_map(|equipment| calculator.equipment_stats(equipment, args.level, args.quality))
+            .collect(),
+    };
+    stats.sort_by(|a, b| (a.item_type, &a.name_id).cmp(&(b.item_type, &b.name_id)));
+
+    let header = [
+        "name_id", "item_type", "level", "quality", "stat", "perk", "perk_chance", "perk_value", "set_item",
+        "set_item_skill", "pvp_set_item_skill",
+    ];
+    let mut rows = vec![header.map(str::to_string).to_vec()];
+
+    for stats in stats {
+        let perk = stats.perk.as_ref();
+
+        rows.push(vec![
+            stats.name_id,
+            stats.item_type.as_str_name().to_string(),
+            stats.level.to_string(),
+            stats.quality.to_string(),
+            format!("{:.2}", stats.stat),
+            perk.map(|perk| perk.r#type().as_str_name().to_string()).unwrap_or_default(),
+            perk.map(|perk| perk.probablity_in_percent().to_string()).unwrap_or_default(),
+            perk.map(|perk| perk.perk_value().to_string()).unwrap_or_default(),
+            stats.set_item.unwrap_or_default(),
+            stats.set_item_skill.unwrap_or_default(),
+            stats.pvp_set_item_skill.unwrap_or_default(),
+        ]);
+    }
+
+    print_report(&rows, args.output_as);
+
+    Ok(())
+}
+
+pub(super) fn check_requirements(
+    balancing_args: BalancingArgs,
+    args: BalancingRequirementsArgs,
+) -> anyhow::Result<()> {
+    let reader = balancing_args.archive(false)?;
+
+    let xml_file = std::fs::read_to_string(args.player_prefs_path)?;
+    let prefs = PlayerPrefsData::from_prefs_xml(xml_file.as_str())?;
+    let evaluator = RequirementEvaluator::new(&prefs, args.at.unwrap_or_else(Utc::now));
+
+    let requirements = reader.requirements_of(&args.name_id)?;
+    if requirements.is_empty() {
+        println!("{} has no requirements", args.name_id);
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for (location, requirement) in &requirements {
+        let evaluation = evaluator.evaluate(requirement);
+        if evaluation.outcome == Outcome::Failed {
+            failed += 1;
+        }
+
+        println!("{}: {}", location, evaluation);
+    }
+
+    println!("{} of {} requirements failed", failed, requirements.len());
+
+    Ok(())
+}
+
+pub(super) fn diff_containers(
+    balancing_args: BalancingArgs,
+    args: BalancingDiffArgs,
+) -> anyhow::Result<()> {
+    let old = balancing_args.archive(false)?;
+    let (mut new, _) = read_archive(&args.new_data_path, false)?;
+    balancing_args.revision.apply(&mut new)?;
+
+    let diff = old.diff(&new)?;
+
+    let output = match args.output_as {
+        DiffFormat::Text if diff.is_empty() => "No changes\n".to_string(),
+        DiffFormat::Text => diff.to_string(),
+        DiffFormat::Json => serde_json::to_string_pretty(&diff)?,
+        DiffFormat::Patch => Patch::from_diff(&diff, &new)?.to_json_pretty()?,
+    };
+
+    match args.output_file_path {
+        Some(output_file_path) => std::fs::write(output_file_path, output)?,
+        None => print!("{}", output),
+    }
+
+    Ok(())
+}
+
+pub(super) fn patch_container(
+    balancing_args: BalancingArgs,
+    args: BalancingPatchArgs,
+) -> anyhow::Result<()> {
+    let mut archive = balancing_args.archive(args.lossless)?;
+
+    let patch = Patch::from_json(&std::fs::read_to_string(args.patch_file_path)?)?;
+    if let Some(version) = patch.version.as_deref().filter(|version| *version != archive.get_version()) {
+        eprintln!("Warning: patch was made for {}, the container is {}", version, archive.get_version());
+    }
+
+    archive.apply_patch(&patch)?;
+
+    let file = File::create(args.output_file_path)?;
+    archive.save_gzipped(file)?;
+
+    println!("Applied {} operations", patch.operations.len());
+
+    Ok(())
+}
+
+pub(super) fn migrate_container(
+    balancing_args: BalancingArgs,
+    args: BalancingMigrateArgs,
+) -> anyhow::Result<()> {
+    let mut archive = balancing_args.archive(false)?;
+
+    let to = match args.to {
+        Some(name) => schema_revision(&name)?,
+        None => SchemaRevision::current(),
+    };
+    let from_name = archive.schema_revision().name;
+
+    let count = archive.convert_schema(None, to)?;
 
     let file = File::create(args.output_file_path)?;
     archive.save_gzipped(file)?;
 
+    println!("Converted {} containers from revision {} to {}", count, from_name, to.name);
+
     Ok(())
 }
 
-// fn prost_fix(key: BalancingDataTypes, reader: &mut BalancingDataArchive) -> anyhow::Result<()> {
-//     //fixes for any prost related issues for balancing
-//     match key {
-//         SkillBalancingData => {
-//             let mut skill_data = reader.get_data_enum_decoded::<proto::SkillBalancingData>(SkillBalancingData)?;
-// 
-//             skill_data.skill_data.iter_mut().for_each(|x| {
-//                 //sort btreemap
-//                 let new_map:  BTreeMap<String, f32> = x.skill_parameters.iter().sorted_by(
-//                     |(a, _), (b, _)| {
-//                         if a.starts_with("summon") && !b.starts_with("summon") {
-//                             std::cmp::Ordering::Greater
-//                         } else if !a.starts_with("summon") && b.starts_with("summon") {
-//                             std::cmp::Ordering::Less
-//                         } else {
-//                             a.cmp(b)
-//                         }
-//                     }
-//                 ).map(|(a, b)| (a.clone(), b.clone())).collect();
-//                 
-//                 println!("{:?}", new_map);
-// 
-//                 x.skill_parameters = new_map;
-//             });
-//         }
-//         _ => {}
-//     }
-// 
-//     Ok(())
-// }
\ No newline at end of file
+//gzipped or not, whichever the file is
+pub(super) fn read_archive(path: &Path, lossless: bool) -> anyhow::Result<(BalancingDataArchive, Compression)> {
+    let data = std::fs::read(path)?;
+
+    let gzipped = match lossless {
+        true => BalancingDataArchive::new_gzipped_lossless(data.as_slice()),
+        false => BalancingDataArchive::new_gzipped(data.as_slice()),
+    };
+    if let Ok(archive) = gzipped {
+        return Ok((archive, Compression::Gzip));
+    }
+
+    let archive = match lossless {
+        true => BalancingDataArchive::new_lossless(data.as_slice())?,
+        false => BalancingDataArchive::new(data.as_slice())?,
+    };
+    Ok((archive, Compression::None))
+}
+
+fn schema_revision(name: &str) -> anyhow::Result<&'static SchemaRevision> {
+    SchemaRevision::from_name(name).ok_or_else(|| {
+        let known = SchemaRevision::all().iter().map(|revision| revision.name).collect::<Vec<_>>();
+        anyhow!("Unknown schema revision {}, known revisions: {}", name, known.join(", "))
+    })
+}
+
+//first row is the header
+pub(super) fn print_report(rows: &[Vec<String>], format: ReportFormat) {
+    match format {
+        ReportFormat::Csv => {
+            for row in rows {
+                let cells: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+                println!("{}", cells.join(","));
+            }
+        }
+        ReportFormat::Table => {
+            let columns = rows.first().map(Vec::len).unwrap_or_default();
+            let widths: Vec<usize> = (0..columns)
+                .map(|column| rows.iter().map(|row| row[column].len()).max().unwrap_or_default())
+                .collect();
+
+            for row in rows {
+                let cells: Vec<String> = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                    .collect();
+                println!("{}", cells.join(" | ").trim_end());
+            }
+        }
+    }
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+impl Display for DiffFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffFormat::Text => write!(f, "text"),
+            DiffFormat::Json => write!(f, "json"),
+            DiffFormat::Patch => write!(f, "patch"),
+        }
+    }
+}
+
+impl Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReportFormat::Table => write!(f, "table"),
+            ReportFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
@@ -3,21 +3,32 @@ extern crate anyhow;
 
 mod balancing;
 mod locale;
-mod macros;
+mod mods;
 mod prefs;
+mod query;
+mod raw;
+mod schema;
 mod util;
 mod sdkv2;
 
 use std::fmt::Display;
-use crate::balancing::{decode_container, encode_container, BalancingAction, BalancingArgs};
+use crate::balancing::{
+    character_stats, check_requirements, decode_container, diff_containers, encode_container, equipment_stats,
+    extract_container, migrate_container, patch_container, repack_container, roll_loot, validate_container,
+    BalancingAction, BalancingArgs,
+};
 use crate::locale::{decode_locale, encode_locale, LocaleAction, LocaleArgs};
+use crate::mods::{build_mods, ModsAction, ModsArgs};
 use crate::prefs::{decode_prefs, encode_prefs, PrefsAction, PrefsArgs};
+use crate::query::{run_query, QueryArgs};
+use crate::raw::{inspect_raw, RawArgs};
+use crate::schema::{write_schemas, SchemaArgs};
 use clap::{Parser, ValueEnum};
 
 #[cfg(feature = "dump")]
 use {
     crate::util::key_to_string,
-    epic_balance::{proto, BalancingDataArchive, BalancingDataTypes},
+    epic_balance::{BalancingDataArchive, BalancingDataTypes},
     prost::DecodeError,
     std::cmp::Ordering,
     std::collections::HashMap,
@@ -35,6 +46,10 @@ enum Cli {
     Prefs(PrefsArgs),
     Locale(LocaleArgs),
     SdkV2(Sdkv2Args),
+    Mods(ModsArgs),
+    Query(QueryArgs),
+    Schema(SchemaArgs),
+    Raw(RawArgs),
 }
 
 #[derive(ValueEnum, Copy, Clone)]
@@ -43,6 +58,14 @@ pub(crate) enum DataFormat {
     Json,
 }
 
+//formats for containers without a known type, only bin and base64 can be encoded back
+#[derive(ValueEnum, Copy, Clone)]
+pub(crate) enum RawFormat {
+    Bin,
+    Base64,
+    Wire,
+}
+
 fn main() {
     if cfg!(feature = "dump") {
         dump_balancing()
@@ -52,6 +75,16 @@ fn main() {
         Cli::Balancing(balancing_args) => match balancing_args.clone().balancing_action {
             BalancingAction::Decode(args) => decode_container(balancing_args, args),
             BalancingAction::Encode(args) => encode_container(balancing_args, args),
+            BalancingAction::Extract(args) => extract_container(balancing_args, args),
+            BalancingAction::Repack(args) => repack_container(balancing_args, args),
+            BalancingAction::Validate(args) => validate_container(balancing_args, args),
+            BalancingAction::Loot(args) => roll_loot(balancing_args, args),
+            BalancingAction::Stats(args) => character_stats(balancing_args, args),
+            BalancingAction::Equipment(args) => equipment_stats(balancing_args, args),
+            BalancingAction::Requirements(args) => check_requirements(balancing_args, args),
+            BalancingAction::Diff(args) => diff_containers(balancing_args, args),
+            BalancingAction::Patch(args) => patch_container(balancing_args, args),
+            BalancingAction::Migrate(args) => migrate_container(balancing_args, args),
         },
         Cli::Prefs(prefs_args) => match prefs_args.clone().prefs_action {
             PrefsAction::Decode(args) => decode_prefs(prefs_args, args),
@@ -64,7 +97,13 @@ fn main() {
         Cli::SdkV2(sdkv2_args) => match sdkv2_args.clone().sdkv2_action {
             Sdkv2Action::Decode(args) => sdkv2::decode_sdkv2(sdkv2_args, args),
             Sdkv2Action::Encode(args) => sdkv2::encode_sdkv2(sdkv2_args, args),
-        }
+        },
+        Cli::Mods(mods_args) => match mods_args.clone().mods_action {
+            ModsAction::Build(args) => build_mods(mods_args, args),
+        },
+        Cli::Query(args) => run_query(args),
+        Cli::Schema(args) => write_schemas(args),
+        Cli::Raw(args) => inspect_raw(args),
     };
 
     if let Err(e) = result {
@@ -122,68 +161,13 @@ fn dump_balancing() {
 
     //reconstruct the data and then gzip under a new nameb nm
 
+    //a plain archive on purpose, this is here to show how far the canonical encoding drifts from the original
     let mut new_data = BalancingDataArchive::default();
 
     for key in reader.get_balaning_data_keys() {
         let data_type = BalancingDataTypes::from_str(&key).unwrap();
-        generate_reencode_container_data_unsafe!(reader, new_data, data_type,
-            BattleBalancingData => proto::BattleBalancingData,
-            ChronicleCaveFloorBalancingData => proto::ChronicleCaveFloorBalancingData,
-            CustomMessageBalancingData => proto::CustomMessageBalancingData,
-            HotspotBalancingData => proto::HotspotBalancingData,
-            ExperienceScalingBalancingData => proto::ExperienceScalingBalancingData,
-            CollectionGroupBalancingData => proto::CollectionGroupBalancingData,
-            ExperienceLevelBalancingData => proto::ExperienceLevelBalancingData,
-            InventoryBalancingData => proto::InventoryBalancingData,
-            ShopBalancingData => proto::ShopBalancingData,
-            BattleParticipantTableBalancingData => proto::BattleParticipantTableBalancingData,
-            ClassSkinBalancingData => proto::ClassSkinBalancingData,
-            ConditionalInventoryBalancingData => proto::ConditionalInventoryBalancingData,
-            ChronicleCaveBattleBalancingData => proto::BattleBalancingData,
-            ExperienceMasteryBalancingData => proto::ExperienceMasteryBalancingData,
-            BirdBalancingData => proto::BirdBalancingData,
-            CraftingRecipeBalancingData => proto::CraftingRecipeBalancingData,
-            MiniCampaignBalancingData => proto::MiniCampaignBalancingData,
-            LootTableBalancingData => proto::LootTableBalancingData,
-            SocialEnvironmentBalancingData => proto::SocialEnvironmentBalancingData,
-            BannerItemBalancingData => proto::BannerItemBalancingData,
-            PremiumShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-            GachaShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-            PowerLevelBalancingData => proto::PowerLevelBalancingData,
-            PigBalancingData => proto::PigBalancingData,
-            EnchantingBalancingData => proto::EnchantingBalancingData,
-            BuyableShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-            ClientConfigBalancingData => proto::ClientConfigBalancingData,
-            ConsumableItemBalancingData => proto::ConsumableItemBalancingData,
-            ResourceCostPerLevelBalancingData => proto::ResourceCostPerLevelBalancingData,
-            SkillBalancingData => proto::SkillBalancingData,
-            EventItemBalancingData => proto::EventItemBalancingData,
-            GameConstantsBalancingData => proto::GameConstantsBalancingData,
-            CraftingItemBalancingData => proto::CraftingItemBalancingData,
-            DailyLoginGiftsBalancingData => proto::DailyLoginGiftsBalancingData,
-            LoadingHintBalancingData => proto::LoadingHintBalancingData,
-            SetFusionBalancingData => proto::SetFusionBalancingData,
-            EquipmentBalancingData => proto::EquipmentBalancingData,
-            ChronicleCaveHotspotBalancingData => proto::HotspotBalancingData,
-            MasteryItemBalancingData => proto::MasteryItemBalancingData,
-            SplashScreenBalancingData => proto::SplashScreenBalancingData,
-            PigTypePowerLevelBalancingData => proto::PigTypePowerLevelBalancingData,
-            PvPObjectivesBalancingData => proto::PvPObjectivesBalancingData,
-            ThirdPartyIdBalancingData => proto::ThirdPartyIdBalancingData,
-            BasicItemBalancingData => proto::BasicItemBalancingData,
-            BattleHintBalancingData => proto::BattleHintBalancingData,
-            BossBalancingData => proto::BossBalancingData,
-            ScoreBalancingData => proto::ScoreBalancingData,
-            SalesManagerBalancingData => proto::SalesManagerBalancingData,
-            BannerBalancingData => proto::BannerBalancingData,
-            ChronicleCaveBattleParticipantTableBalancingData => proto::BattleParticipantTableBalancingData,
-            ClassItemBalancingData => proto::ClassItemBalancingData,
-            EventBalancingData => proto::EventBalancingData,
-            BonusEventBalancingData => proto::BonusEventBalancingData,
-            PvPSeasonManagerBalancingData => proto::PvPSeasonManagerBalancingData,
-            EventPlacementBalancingData => proto::EventPlacementBalancingData,
-            EventManagerBalancingData => proto::EventManagerBalancingData,
-        );
+        let data = reader.get_data_any(data_type).unwrap();
+        new_data.set_data_any(&data).unwrap();
     }
 
     for key in new_data.get_balaning_data_keys() {
@@ -243,6 +227,17 @@ fn dump_balancing() {
     println!("Done");
 }
 
+impl Display for RawFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            RawFormat::Bin => "bin",
+            RawFormat::Base64 => "b64",
+            RawFormat::Wire => "wire",
+        };
+        write!(f, "{}", str)
+    }
+}
+
 impl Display for DataFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str = match self {
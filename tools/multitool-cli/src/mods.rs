@@ -0,0 +1,93 @@
+use crate::balancing::read_archive;
+use clap::{Args, Subcommand};
+use epic_locale::LocaleDataContainer;
+use epic_mods::{Mod, ModLoader};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Combine community mods into game files"), aliases = ["m", "mod"]
+)]
+pub(super) struct ModsArgs {
+    #[command(subcommand)]
+    pub mods_action: ModsAction,
+}
+
+#[derive(Subcommand, Clone)]
+pub(super) enum ModsAction {
+    Build(ModsBuildArgs),
+}
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Apply mods to a balancing data container and locale files, the higher priority mod wins when two change the same thing"), aliases = ["b", "apply"]
+)]
+pub(super) struct ModsBuildArgs {
+    #[arg(help = "Location of the serialized balancing data container to build on")]
+    pub live_data_path: PathBuf,
+    #[arg(help = "Mod manifests to apply, the priority in each manifest decides the order and not where it is given here, only mods with the same priority keep this order", required = true)]
+    pub manifest_paths: Vec<PathBuf>,
+    #[arg(help = "Language locale file the mods can add texts to, can be given more than once", long = "locale", short, value_name = "FILE")]
+    pub locale_paths: Vec<PathBuf>,
+    #[arg(help = "Directory to save the built files to, created if it doesn't exist", long = "output", short, value_name = "DIR")]
+    pub output_dir: PathBuf,
+    #[arg(
+        long,
+        short = 'L',
+        help = "Keep the original encoding of everything that was not changed, untouched containers are written back byte for byte"
+    )]
+    pub lossless: bool,
+    #[arg(long, help = "Fail instead of building when two mods change the same thing")]
+    pub strict: bool,
+}
+
+pub(super) fn build_mods(_: ModsArgs, args: ModsBuildArgs) -> anyhow::Result<()> {
+    let (archive, _) = read_archive(&args.live_data_path, args.lossless)?;
+
+    let mut locales = Vec::with_capacity(args.locale_paths.len());
+    for path in &args.locale_paths {
+        let locale_file = std::fs::read(path)?;
+
+        locales.push(match LocaleDataContainer::new_gzipped(locale_file.as_slice()) {
+            Ok(reader) => reader,
+            Err(_) => LocaleDataContainer::new(locale_file.as_slice())?,
+        });
+    }
+
+    let mods = args
+        .manifest_paths
+        .iter()
+        .map(|path| Mod::load(path))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let mut loader = ModLoader::new(archive, locales);
+    loader.apply_all(&mods)?;
+
+    for issue in loader.issues() {
+        eprintln!("Warning: {}", issue);
+    }
+    if args.strict && loader.has_conflicts() {
+        bail!("mods conflict with each other, nothing was written");
+    }
+
+    std::fs::create_dir_all(&args.output_dir)?;
+    let (archive, locales) = loader.into_parts();
+
+    let file = File::create(output_path(&args.output_dir, &args.live_data_path)?)?;
+    archive.save_gzipped(file)?;
+
+    for (locale, path) in locales.iter().zip(&args.locale_paths) {
+        let mut buf = Vec::new();
+        locale.write_gzipped(&mut buf)?;
+        std::fs::write(output_path(&args.output_dir, path)?, buf)?;
+    }
+
+    println!("Built {} mods into {}", mods.len(), args.output_dir.display());
+
+    Ok(())
+}
+
+//built files keep the name of the file they were built from
+fn output_path(output_dir: &Path, input: &Path) -> anyhow::Result<PathBuf> {
+    let name = input.file_name().ok_or_else(|| anyhow!("{} is not a file", input.display()))?;
+    Ok(output_dir.join(name))
+}
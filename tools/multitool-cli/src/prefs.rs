@@ -1,6 +1,7 @@
 use clap::{arg, Args, Subcommand};
 use epic_prefs::PlayerPrefsData;
 use std::path::PathBuf;
+use crate::util::{reflect_from_str, reflect_to_string, ReflectArgs};
 use crate::DataFormat;
 
 #[derive(Args, Clone)]
@@ -28,6 +29,8 @@ pub(super) enum PrefsAction {
 pub(super) struct PrefsDecodeArgs {
     #[arg(help = "Data format to output the player prefs in", long, short = 'O', default_value_t=DataFormat::Ron)]
     pub output_as: DataFormat,
+    #[command(flatten)]
+    pub reflect: ReflectArgs,
 }
 
 #[derive(Args, Clone)]
@@ -39,6 +42,8 @@ pub(super) struct PrefsEncodeArgs {
         value_name = "FILE"
     )]
     pub output_prefs_path: PathBuf,
+    #[command(flatten)]
+    pub reflect: ReflectArgs,
 }
 
 pub(super) fn encode_prefs(prefs_args: PrefsArgs, args: PrefsEncodeArgs) -> anyhow::Result<()> {
@@ -47,9 +52,12 @@ pub(super) fn encode_prefs(prefs_args: PrefsArgs, args: PrefsEncodeArgs) -> anyh
     
     let data_format = if json_file.starts_with("{") { DataFormat::Json } else { DataFormat::Ron };
     
-    let prefs = match data_format {
-        DataFormat::Ron => PlayerPrefsData::from_ron(json_file.as_str())?,
-        DataFormat::Json => PlayerPrefsData::from_json(json_file.as_str())?
+    let prefs = match (args.reflect.load()?, data_format) {
+        (Some(descriptors), _) => {
+            PlayerPrefsData::from_reflect(&descriptors, args.reflect.message.as_deref(), &reflect_from_str(&json_file)?)?
+        }
+        (None, DataFormat::Ron) => PlayerPrefsData::from_ron(json_file.as_str())?,
+        (None, DataFormat::Json) => PlayerPrefsData::from_json(json_file.as_str())?
     };
 
     let xml_file = prefs.to_prefs_xml(xml_file.as_str(), None)?;
@@ -62,9 +70,12 @@ pub(super) fn decode_prefs(prefs_args: PrefsArgs, args: PrefsDecodeArgs) -> anyh
 
     let prefs = PlayerPrefsData::from_prefs_xml(xml_file.as_str())?;
 
-    let data = match args.output_as {
-        DataFormat::Ron => prefs.to_ron_pretty()?,
-        DataFormat::Json => prefs.to_json_pretty()?
+    let data = match (args.reflect.load()?, args.output_as) {
+        (Some(descriptors), format) => {
+            reflect_to_string(&prefs.to_reflect(&descriptors, args.reflect.message.as_deref())?, format)?
+        }
+        (None, DataFormat::Ron) => prefs.to_ron_pretty()?,
+        (None, DataFormat::Json) => prefs.to_json_pretty()?
     };
 
     std::fs::write(prefs_args.player_data_file, data.as_bytes()).map_err(anyhow::Error::new)
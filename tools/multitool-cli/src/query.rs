@@ -0,0 +1,107 @@
+use crate::balancing::{print_report, ReportFormat};
+use clap::{Args, ValueEnum};
+use epic_balance::BalancingDataArchive;
+use epic_prefs::PlayerPrefsData;
+use epic_query::Query;
+use serde_json::Value;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Query a balancing data container, xml player prefs file or json document with a jsonpath like expression"), aliases = ["q", "find"]
+)]
+pub(super) struct QueryArgs {
+    #[arg(help = "Location of the serialized balancing data container, xml player prefs file or json document")]
+    pub input_path: PathBuf,
+    #[arg(help = "Query to run, e.g. 'SkillBalancingData.skillData[?(@.skill_template_type == \"X\")].nameId'")]
+    pub query: Query,
+    #[arg(help = "Format to output the results in, objects get a column per field in table and csv", long, short = 'O', default_value_t = QueryFormat::Table)]
+    pub output_as: QueryFormat,
+}
+
+#[derive(ValueEnum, Copy, Clone)]
+pub(super) enum QueryFormat {
+    Table,
+    Json,
+    Csv,
+}
+
+pub(super) fn run_query(args: QueryArgs) -> anyhow::Result<()> {
+    let data = std::fs::read(&args.input_path)?;
+    let text = std::str::from_utf8(&data).ok().map(str::trim_start);
+
+    let results = match text {
+        Some(xml) if xml.starts_with('<') => args.query.run_player(&PlayerPrefsData::from_prefs_xml(xml)?)?,
+        Some(json) if json.starts_with('{') || json.starts_with('[') => {
+            let root: Value = serde_json::from_str(json)?;
+            args.query.run(&root).into_iter().cloned().collect()
+        }
+        _ => {
+            let reader = match BalancingDataArchive::new_gzipped(data.as_slice()) {
+                Ok(reader) => reader,
+                Err(_) => BalancingDataArchive::new(data.as_slice())?,
+            };
+            args.query.run_archive(&reader)?
+        }
+    };
+
+    let format = match args.output_as {
+        QueryFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(&results)?);
+            return Ok(());
+        }
+        QueryFormat::Table if results.is_empty() => {
+            println!("No results");
+            return Ok(());
+        }
+        QueryFormat::Table => ReportFormat::Table,
+        QueryFormat::Csv => ReportFormat::Csv,
+    };
+
+    print_report(&result_rows(&results), format);
+
+    Ok(())
+}
+
+//a column per field if every result is an object, otherwise a single value column
+fn result_rows(results: &[Value]) -> Vec<Vec<String>> {
+    if results.is_empty() || !results.iter().all(Value::is_object) {
+        let mut rows = vec![vec!["value".to_string()]];
+        rows.extend(results.iter().map(|value| vec![cell(Some(value))]));
+        return rows;
+    }
+
+    let mut columns: Vec<&String> = Vec::new();
+    for fields in results.iter().filter_map(Value::as_object) {
+        for field in fields.keys() {
+            if !columns.contains(&field) {
+                columns.push(field);
+            }
+        }
+    }
+
+    let mut rows = vec![columns.iter().map(|column| column.to_string()).collect()];
+    for fields in results.iter().filter_map(Value::as_object) {
+        rows.push(columns.iter().map(|column| cell(fields.get(*column))).collect());
+    }
+
+    rows
+}
+
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(string)) => string.clone(),
+        Some(value) => value.to_string(),
+    }
+}
+
+impl Display for QueryFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryFormat::Table => write!(f, "table"),
+            QueryFormat::Json => write!(f, "json"),
+            QueryFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
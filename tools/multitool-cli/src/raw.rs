@@ -0,0 +1,56 @@
+use crate::balancing::container_key;
+use clap::Args;
+use epic_balance::BalancingDataArchive;
+use epic_prefs::PlayerPrefsData;
+use flate2::read::GzDecoder;
+use std::io::Read;
+use std::path::PathBuf;
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Show a protobuf blob as raw wire format with offsets, for data that doesn't decode"), aliases = ["wire", "decode-raw"]
+)]
+pub(super) struct RawArgs {
+    #[arg(help = "Location of a balancing data container, locale file, xml player prefs file or any other protobuf blob, gzipped or not")]
+    pub input_path: PathBuf,
+    #[arg(help = "Show this container of a balancing data container instead of the whole file", long, short)]
+    pub key: Option<String>,
+    #[arg(help = "Location to save the dump to instead of printing it", long, short, value_name = "FILE")]
+    pub output_file_path: Option<PathBuf>,
+}
+
+pub(super) fn inspect_raw(args: RawArgs) -> anyhow::Result<()> {
+    let mut data = std::fs::read(&args.input_path)?;
+
+    if data.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(data.as_slice()).read_to_end(&mut decompressed)?;
+        data = decompressed;
+    }
+
+    //player prefs have the save base64 encoded in the player key
+    let is_xml = std::str::from_utf8(&data).is_ok_and(|text| text.trim_start().starts_with('<'));
+
+    let payload = match args.key {
+        Some(key) => {
+            let archive = BalancingDataArchive::new(data.as_slice())?;
+            let key = container_key(key);
+
+            archive.get_data_key(&key).ok_or(anyhow!("No container named {}", key))?.clone()
+        }
+        None if is_xml => PlayerPrefsData::payload_from_prefs_xml(std::str::from_utf8(&data)?)?,
+        None => data,
+    };
+
+    let inspection = epic_wire::inspect(&payload);
+
+    match args.output_file_path {
+        Some(output_file_path) => std::fs::write(output_file_path, inspection.to_string())?,
+        None => print!("{}", inspection),
+    }
+
+    if let Some(error) = &inspection.error {
+        eprintln!("Warning: Stopped at {:#x} of {} bytes: {}", error.offset, payload.len(), error.message);
+    }
+
+    Ok(())
+}
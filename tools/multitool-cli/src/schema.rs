@@ -0,0 +1,37 @@
+use clap::Args;
+use epic_balance::{BalancingDataArchive, BalancingDataTypes};
+use epic_locale::LocaleDataContainer;
+use epic_prefs::PlayerPrefsData;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Clone)]
+#[command(version, about, long_about = Some("Write JSON Schema files for the json forms of containers, saves and locales, for autocompletion in editors"), aliases = ["schemas"]
+)]
+pub(super) struct SchemaArgs {
+    #[arg(help = "Directory to save the schema files to, created if it doesn't exist")]
+    pub output_dir: PathBuf,
+}
+
+pub(super) fn write_schemas(args: SchemaArgs) -> anyhow::Result<()> {
+    std::fs::create_dir_all(&args.output_dir)?;
+
+    let mut count = 0;
+    for data_type in BalancingDataTypes::all() {
+        write_schema(&args.output_dir, data_type.short_name(), &data_type.json_schema())?;
+        count += 1;
+    }
+
+    write_schema(&args.output_dir, "BalancingDataArchive", &BalancingDataArchive::json_schema())?;
+    write_schema(&args.output_dir, "PlayerData", &PlayerPrefsData::json_schema())?;
+    write_schema(&args.output_dir, "SerializedLocalizedTexts", &LocaleDataContainer::json_schema())?;
+
+    println!("Wrote {} schemas to {}", count + 3, args.output_dir.display());
+
+    Ok(())
+}
+
+fn write_schema(output_dir: &Path, name: &str, schema: &Value) -> anyhow::Result<()> {
+    let path = output_dir.join(format!("{}.schema.json", name));
+    Ok(std::fs::write(path, serde_json::to_string_pretty(schema)?)?)
+}
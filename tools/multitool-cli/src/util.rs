@@ -1,143 +1,97 @@
-use crate::{generate_key_to_json_match, generate_key_to_ron_match, DataFormat};
-use epic_balance::{proto, BalancingDataArchive, BalancingDataTypes};
-use std::str::FromStr;
+use crate::{DataFormat, RawFormat};
+use base64::prelude::BASE64_STANDARD;
+use base64::Engine;
+use clap::Args;
+use epic_balance::{BalancingDataArchive, BalancingDataTypes};
+use epic_wire::Descriptors;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+//decoding against proto files given at runtime instead of the ones built in
+#[derive(Args, Clone)]
+pub(crate) struct ReflectArgs {
+    #[arg(long, help = "Use this .proto file or descriptor set instead of the built in proto files, .proto files need protoc", value_name = "FILE")]
+    pub proto: Option<PathBuf>,
+    #[arg(long, help = "Message in --proto to use, defaults to the one named like the container (PlayerData for saves)", value_name = "NAME", requires = "proto")]
+    pub message: Option<String>,
+}
+
+impl ReflectArgs {
+    pub(crate) fn load(&self) -> anyhow::Result<Option<Descriptors>> {
+        self.proto.as_deref().map(Descriptors::load).transpose()
+    }
+}
 
 pub(crate) fn key_to_string(
     key: BalancingDataTypes,
     reader: &BalancingDataArchive,
     data_format: DataFormat
 ) -> anyhow::Result<String> {
-    Ok(match data_format {
-        DataFormat::Json => generate_key_to_json_match!(reader, key,
-        BattleBalancingData => proto::BattleBalancingData,
-        ChronicleCaveFloorBalancingData => proto::ChronicleCaveFloorBalancingData,
-        CustomMessageBalancingData => proto::CustomMessageBalancingData,
-        HotspotBalancingData => proto::HotspotBalancingData,
-        ExperienceScalingBalancingData => proto::ExperienceScalingBalancingData,
-        CollectionGroupBalancingData => proto::CollectionGroupBalancingData,
-        ExperienceLevelBalancingData => proto::ExperienceLevelBalancingData,
-        InventoryBalancingData => proto::InventoryBalancingData,
-        ShopBalancingData => proto::ShopBalancingData,
-        BattleParticipantTableBalancingData => proto::BattleParticipantTableBalancingData,
-        ClassSkinBalancingData => proto::ClassSkinBalancingData,
-        ConditionalInventoryBalancingData => proto::ConditionalInventoryBalancingData,
-        ChronicleCaveBattleBalancingData => proto::BattleBalancingData,
-        ExperienceMasteryBalancingData => proto::ExperienceMasteryBalancingData,
-        BirdBalancingData => proto::BirdBalancingData,
-        CraftingRecipeBalancingData => proto::CraftingRecipeBalancingData,
-        MiniCampaignBalancingData => proto::MiniCampaignBalancingData,
-        LootTableBalancingData => proto::LootTableBalancingData,
-        SocialEnvironmentBalancingData => proto::SocialEnvironmentBalancingData,
-        BannerItemBalancingData => proto::BannerItemBalancingData,
-        PremiumShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        GachaShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        PowerLevelBalancingData => proto::PowerLevelBalancingData,
-        PigBalancingData => proto::PigBalancingData,
-        EnchantingBalancingData => proto::EnchantingBalancingData,
-        BuyableShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        ClientConfigBalancingData => proto::ClientConfigBalancingData,
-        ConsumableItemBalancingData => proto::ConsumableItemBalancingData,
-        ResourceCostPerLevelBalancingData => proto::ResourceCostPerLevelBalancingData,
-        SkillBalancingData => proto::SkillBalancingData,
-        EventItemBalancingData => proto::EventItemBalancingData,
-        GameConstantsBalancingData => proto::GameConstantsBalancingData,
-        CraftingItemBalancingData => proto::CraftingItemBalancingData,
-        DailyLoginGiftsBalancingData => proto::DailyLoginGiftsBalancingData,
-        LoadingHintBalancingData => proto::LoadingHintBalancingData,
-        SetFusionBalancingData => proto::SetFusionBalancingData,
-        EquipmentBalancingData => proto::EquipmentBalancingData,
-        ChronicleCaveHotspotBalancingData => proto::HotspotBalancingData,
-        MasteryItemBalancingData => proto::MasteryItemBalancingData,
-        SplashScreenBalancingData => proto::SplashScreenBalancingData,
-        PigTypePowerLevelBalancingData => proto::PigTypePowerLevelBalancingData,
-        PvPObjectivesBalancingData => proto::PvPObjectivesBalancingData,
-        ThirdPartyIdBalancingData => proto::ThirdPartyIdBalancingData,
-        BasicItemBalancingData => proto::BasicItemBalancingData,
-        BattleHintBalancingData => proto::BattleHintBalancingData,
-        BossBalancingData => proto::BossBalancingData,
-        ScoreBalancingData => proto::ScoreBalancingData,
-        SalesManagerBalancingData => proto::SalesManagerBalancingData,
-        BannerBalancingData => proto::BannerBalancingData,
-        ChronicleCaveBattleParticipantTableBalancingData => proto::BattleParticipantTableBalancingData,
-        ClassItemBalancingData => proto::ClassItemBalancingData,
-        EventBalancingData => proto::EventBalancingData,
-        BonusEventBalancingData => proto::BonusEventBalancingData,
-        PvPSeasonManagerBalancingData => proto::PvPSeasonManagerBalancingData,
-        EventPlacementBalancingData => proto::EventPlacementBalancingData,
-        EventManagerBalancingData => proto::EventManagerBalancingData,
-    ),
-        DataFormat::Ron => generate_key_to_ron_match!(reader, key,
-        BattleBalancingData => proto::BattleBalancingData,
-        ChronicleCaveFloorBalancingData => proto::ChronicleCaveFloorBalancingData,
-        CustomMessageBalancingData => proto::CustomMessageBalancingData,
-        HotspotBalancingData => proto::HotspotBalancingData,
-        ExperienceScalingBalancingData => proto::ExperienceScalingBalancingData,
-        CollectionGroupBalancingData => proto::CollectionGroupBalancingData,
-        ExperienceLevelBalancingData => proto::ExperienceLevelBalancingData,
-        InventoryBalancingData => proto::InventoryBalancingData,
-        ShopBalancingData => proto::ShopBalancingData,
-        BattleParticipantTableBalancingData => proto::BattleParticipantTableBalancingData,
-        ClassSkinBalancingData => proto::ClassSkinBalancingData,
-        ConditionalInventoryBalancingData => proto::ConditionalInventoryBalancingData,
-        ChronicleCaveBattleBalancingData => proto::BattleBalancingData,
-        ExperienceMasteryBalancingData => proto::ExperienceMasteryBalancingData,
-        BirdBalancingData => proto::BirdBalancingData,
-        CraftingRecipeBalancingData => proto::CraftingRecipeBalancingData,
-        MiniCampaignBalancingData => proto::MiniCampaignBalancingData,
-        LootTableBalancingData => proto::LootTableBalancingData,
-        SocialEnvironmentBalancingData => proto::SocialEnvironmentBalancingData,
-        BannerItemBalancingData => proto::BannerItemBalancingData,
-        PremiumShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        GachaShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        PowerLevelBalancingData => proto::PowerLevelBalancingData,
-        PigBalancingData => proto::PigBalancingData,
-        EnchantingBalancingData => proto::EnchantingBalancingData,
-        BuyableShopOfferBalancingData => proto::BasicShopOfferBalancingData,
-        ClientConfigBalancingData => proto::ClientConfigBalancingData,
-        ConsumableItemBalancingData => proto::ConsumableItemBalancingData,
-        ResourceCostPerLevelBalancingData => proto::ResourceCostPerLevelBalancingData,
-        SkillBalancingData => proto::SkillBalancingData,
-        EventItemBalancingData => proto::EventItemBalancingData,
-        GameConstantsBalancingData => proto::GameConstantsBalancingData,
-        CraftingItemBalancingData => proto::CraftingItemBalancingData,
-        DailyLoginGiftsBalancingData => proto::DailyLoginGiftsBalancingData,
-        LoadingHintBalancingData => proto::LoadingHintBalancingData,
-        SetFusionBalancingData => proto::SetFusionBalancingData,
-        EquipmentBalancingData => proto::EquipmentBalancingData,
-        ChronicleCaveHotspotBalancingData => proto::HotspotBalancingData,
-        MasteryItemBalancingData => proto::MasteryItemBalancingData,
-        SplashScreenBalancingData => proto::SplashScreenBalancingData,
-        PigTypePowerLevelBalancingData => proto::PigTypePowerLevelBalancingData,
-        PvPObjectivesBalancingData => proto::PvPObjectivesBalancingData,
-        ThirdPartyIdBalancingData => proto::ThirdPartyIdBalancingData,
-        BasicItemBalancingData => proto::BasicItemBalancingData,
-        BattleHintBalancingData => proto::BattleHintBalancingData,
-        BossBalancingData => proto::BossBalancingData,
-        ScoreBalancingData => proto::ScoreBalancingData,
-        SalesManagerBalancingData => proto::SalesManagerBalancingData,
-        BannerBalancingData => proto::BannerBalancingData,
-        ChronicleCaveBattleParticipantTableBalancingData => proto::BattleParticipantTableBalancingData,
-        ClassItemBalancingData => proto::ClassItemBalancingData,
-        EventBalancingData => proto::EventBalancingData,
-        BonusEventBalancingData => proto::BonusEventBalancingData,
-        PvPSeasonManagerBalancingData => proto::PvPSeasonManagerBalancingData,
-        EventPlacementBalancingData => proto::EventPlacementBalancingData,
-        EventManagerBalancingData => proto::EventManagerBalancingData,
-    ) })
+    match data_format {
+        DataFormat::Json => reader.get_data_any_json(key),
+        DataFormat::Ron => reader.get_data_any_ron(key),
+    }
+}
+
+//every container of a known type, decoded in parallel
+pub(crate) fn all_keys_to_string(
+    reader: &BalancingDataArchive,
+    data_format: DataFormat
+) -> Vec<(BalancingDataTypes, anyhow::Result<String>)> {
+    match data_format {
+        DataFormat::Json => reader.get_all_data_any_json(),
+        DataFormat::Ron => reader.get_all_data_any_ron(),
+    }
 }
+
 pub(crate) fn get_key_from_name(container_name: &str) -> anyhow::Result<BalancingDataTypes> {
-    Ok(match BalancingDataTypes::from_str(container_name) {
-        Ok(key) => key,
-        Err(_) => {
-            let container_name = container_name
-                .split('.')
-                .last()
-                .ok_or(anyhow!("Invalid container name"))?;
-            
-            match BalancingDataTypes::from_str(format!("ABH.Shared.BalancingData.{}", container_name).as_str()) {
-                Ok(v) => v,
-                Err(_) => BalancingDataTypes::from_str(format!("ABH.Shared.Events.BalancingData.{}", container_name).as_str())?
-            }
-        }
+    BalancingDataTypes::from_any_name(container_name)
+        .ok_or(anyhow!("Unknown BalancingData type: {}", container_name))
+}
+
+pub(crate) fn raw_to_bytes(data: &[u8], raw_format: RawFormat) -> anyhow::Result<Vec<u8>> {
+    Ok(match raw_format {
+        RawFormat::Bin => data.to_vec(),
+        RawFormat::Base64 => BASE64_STANDARD.encode(data).into_bytes(),
+        RawFormat::Wire => epic_wire::wire::dump(data)?.into_bytes(),
     })
 }
+
+//inverse of raw_to_bytes
+pub(crate) fn bytes_to_raw(data: Vec<u8>, raw_format: RawFormat) -> anyhow::Result<Vec<u8>> {
+    match raw_format {
+        RawFormat::Bin => Ok(data),
+        RawFormat::Base64 => Ok(BASE64_STANDARD.decode(String::from_utf8(data)?.trim())?),
+        RawFormat::Wire => bail!("Wire dumps are only for reading, export the container as bin or base64 to encode it back"),
+    }
+}
+
+//reads a container exported with raw_to_bytes, picked by file extension, None if it's not a raw export
+pub(crate) fn read_raw_container(path: &Path) -> anyhow::Result<Option<Vec<u8>>> {
+    let extension = path.extension().and_then(|extension| extension.to_str());
+
+    let raw_format = match extension {
+        Some("bin") => RawFormat::Bin,
+        Some("b64") => RawFormat::Base64,
+        Some("wire") => RawFormat::Wire,
+        _ => return Ok(None),
+    };
+
+    Ok(Some(bytes_to_raw(std::fs::read(path)?, raw_format)?))
+}
+
+pub(crate) fn reflect_to_string(value: &Value, data_format: DataFormat) -> anyhow::Result<String> {
+    Ok(match data_format {
+        DataFormat::Json => serde_json::to_string_pretty(value)?,
+        DataFormat::Ron => ron::ser::to_string_pretty(value, Default::default())?,
+    })
+}
+
+//reflected ron is a map as well, so anything that isn't json is tried as ron
+pub(crate) fn reflect_from_str(data: &str) -> anyhow::Result<Value> {
+    match serde_json::from_str(data) {
+        Ok(value) => Ok(value),
+        Err(_) if !data.trim_start().starts_with('{') => Ok(ron::from_str(data)?),
+        Err(json_error) => ron::from_str(data).map_err(|_| json_error.into()),
+    }
+}